@@ -0,0 +1,8 @@
+-- Restore global uniqueness (fails if two tenants share an email or username)
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_tenant_username_key;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_tenant_email_key;
+ALTER TABLE users ADD CONSTRAINT users_username_key UNIQUE (username);
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+
+-- Drop tenant column
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
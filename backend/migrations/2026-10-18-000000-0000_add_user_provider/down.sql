@@ -0,0 +1,3 @@
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_tenant_provider_key;
+ALTER TABLE users DROP COLUMN IF EXISTS provider_id;
+ALTER TABLE users DROP COLUMN IF EXISTS provider;
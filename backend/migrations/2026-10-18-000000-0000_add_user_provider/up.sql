@@ -0,0 +1,7 @@
+-- External identity for users signed in through an OAuth provider
+ALTER TABLE users ADD COLUMN provider VARCHAR(32);
+ALTER TABLE users ADD COLUMN provider_id VARCHAR(255);
+
+-- One account per provider identity in each tenant
+ALTER TABLE users
+    ADD CONSTRAINT users_tenant_provider_key UNIQUE (tenant_id, provider, provider_id);
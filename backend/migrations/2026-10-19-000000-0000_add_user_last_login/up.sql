@@ -0,0 +1,2 @@
+-- Time of the user's most recent successful sign-in
+ALTER TABLE users ADD COLUMN last_login_at TIMESTAMP;
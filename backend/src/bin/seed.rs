@@ -29,12 +29,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
     }
 
-    let seed_config = db::seed::SeedConfig { clear_existing };
+    let seed_config = db::seed::SeedConfig {
+        clear_existing,
+        ..Default::default()
+    };
 
     // Run seeding
-    db::seed::seed_database(&pool, seed_config).await?;
+    let created = db::seed::seed_database(&pool, seed_config).await?;
 
-    tracing::info!("✓ Database seeded successfully");
+    tracing::info!("✓ Database seeded successfully ({} users created)", created);
     tracing::info!("");
     tracing::info!("Test accounts created:");
     tracing::info!("  Email: admin@example.com       | Password: Password123!");
@@ -0,0 +1,114 @@
+//! Typed HTTP client for this API
+//!
+//! Enabled with the `client` feature. The client only depends on the shared
+//! DTOs and `reqwest`, so request and response types can't drift from what
+//! the handlers actually accept and return.
+//!
+//! ```no_run
+//! # use backend::client::{ApiClient, ClientError, LoginRequestDto};
+//! # async fn example(email: String, password: String) -> Result<(), ClientError> {
+//! let client = ApiClient::new("http://localhost:2999");
+//! let auth = client.login(&LoginRequestDto { email, password }).await?;
+//! let me = client.me(&auth.token).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize};
+
+pub use crate::models::dto::{
+    AuthResponseDto, LoginRequestDto, RegisterRequestDto, UserResponseDto,
+};
+
+/// Errors returned by [`ApiClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The API answered with its standard error body
+    #[error("{status} {error_code}: {message}")]
+    Api {
+        status: StatusCode,
+        error_code: String,
+        message: String,
+        error_id: Option<String>,
+    },
+
+    /// The request failed before a usable response was received
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Subset of the server's `ErrorResponse` the client cares about
+#[derive(Deserialize)]
+struct ErrorBody {
+    error_code: String,
+    error: String,
+    error_id: Option<String>,
+}
+
+/// Thin typed wrapper around the REST API
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl ApiClient {
+    /// Create a client for the server at `base_url` (e.g. `http://localhost:2999`)
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Create a client reusing a preconfigured `reqwest::Client`
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+
+    /// POST /api/v1/auth/register
+    pub async fn register(&self, req: &RegisterRequestDto) -> Result<AuthResponseDto, ClientError> {
+        self.send(self.http.post(self.url("/auth/register")).json(req)).await
+    }
+
+    /// POST /api/v1/auth/login
+    pub async fn login(&self, req: &LoginRequestDto) -> Result<AuthResponseDto, ClientError> {
+        self.send(self.http.post(self.url("/auth/login")).json(req)).await
+    }
+
+    /// GET /api/v1/auth/me
+    pub async fn me(&self, token: &str) -> Result<UserResponseDto, ClientError> {
+        self.send(self.http.get(self.url("/auth/me")).bearer_auth(token)).await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response.json().await?);
+        }
+
+        // Fall back to the status text when the body isn't the standard shape
+        let body = response.bytes().await?;
+        Err(match serde_json::from_slice::<ErrorBody>(&body) {
+            Ok(err) => ClientError::Api {
+                status,
+                error_code: err.error_code,
+                message: err.error,
+                error_id: err.error_id,
+            },
+            Err(_) => ClientError::Api {
+                status,
+                error_code: status.as_str().to_string(),
+                message: String::from_utf8_lossy(&body).into_owned(),
+                error_id: None,
+            },
+        })
+    }
+}
@@ -0,0 +1,93 @@
+//! Deployment environment (ENVIRONMENT)
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Which deployment the process is running as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Development,
+    Test,
+    Staging,
+    Production,
+}
+
+impl Environment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Test => "test",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+        }
+    }
+
+    pub fn is_production(&self) -> bool {
+        *self == Environment::Production
+    }
+
+    /// Read ENVIRONMENT, defaulting to development when unset
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("ENVIRONMENT") {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Environment::default()),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Environment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "development" => Ok(Environment::Development),
+            "test" => Ok(Environment::Test),
+            "staging" => Ok(Environment::Staging),
+            "production" => Ok(Environment::Production),
+            other => Err(format!(
+                "expected development, test, staging or production, got '{}'",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trips_and_ignores_case() {
+        for env in [
+            Environment::Development,
+            Environment::Test,
+            Environment::Staging,
+            Environment::Production,
+        ] {
+            assert_eq!(env.to_string().parse::<Environment>(), Ok(env));
+        }
+        assert_eq!(" Production ".parse::<Environment>(), Ok(Environment::Production));
+    }
+
+    #[test]
+    fn test_unknown_environment_is_rejected() {
+        assert!("prod".parse::<Environment>().is_err());
+        assert!("".parse::<Environment>().is_err());
+    }
+
+    #[test]
+    fn test_only_production_is_production() {
+        assert!(Environment::Production.is_production());
+        assert!(!Environment::Development.is_production());
+        assert!(!Environment::Test.is_production());
+        assert!(!Environment::Staging.is_production());
+    }
+}
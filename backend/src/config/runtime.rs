@@ -0,0 +1,98 @@
+//! Tokio runtime sizing (WORKER_THREADS, MAX_BLOCKING_THREADS)
+//!
+//! `#[tokio::main]` starts one worker per host core, which oversubscribes a
+//! container limited to a fraction of them. The runtime is built from this
+//! config instead, before anything else runs.
+
+use serde::Deserialize;
+
+use super::Config;
+
+/// Tokio's own default cap on the blocking thread pool
+pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RuntimeConfig {
+    /// Async worker threads (WORKER_THREADS, default: available CPUs)
+    pub worker_threads: usize,
+    /// Cap on the blocking pool used by `spawn_blocking` (MAX_BLOCKING_THREADS)
+    pub max_blocking_threads: usize,
+}
+
+impl RuntimeConfig {
+    pub fn from_env() -> Result<Self, config::ConfigError> {
+        let defaults = Self::default();
+        let runtime = Self {
+            worker_threads: Config::env_or("WORKER_THREADS", defaults.worker_threads)?,
+            max_blocking_threads: Config::env_or(
+                "MAX_BLOCKING_THREADS",
+                defaults.max_blocking_threads,
+            )?,
+        };
+        runtime.validate()?;
+        Ok(runtime)
+    }
+
+    pub fn validate(&self) -> Result<(), config::ConfigError> {
+        if self.worker_threads < 1 {
+            return Err(config::ConfigError::Message(
+                "WORKER_THREADS must be at least 1".to_string(),
+            ));
+        }
+        if self.max_blocking_threads < 1 {
+            return Err(config::ConfigError::Message(
+                "MAX_BLOCKING_THREADS must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Multi-threaded runtime builder with these limits applied
+    pub fn builder(&self) -> tokio::runtime::Builder {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder
+            .worker_threads(self.worker_threads)
+            .max_blocking_threads(self.max_blocking_threads)
+            .enable_all();
+        builder
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            // Honours cgroup CPU quotas on Linux, unlike the host core count
+            worker_threads: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            max_blocking_threads: DEFAULT_MAX_BLOCKING_THREADS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_applies_worker_count() {
+        let config = RuntimeConfig {
+            worker_threads: 3,
+            max_blocking_threads: 4,
+        };
+
+        let runtime = config.builder().build().unwrap();
+
+        assert_eq!(runtime.metrics().num_workers(), 3);
+    }
+
+    #[test]
+    fn test_zero_workers_is_rejected() {
+        let config = RuntimeConfig {
+            worker_threads: 0,
+            ..RuntimeConfig::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::AppError;
+use std::collections::HashMap;
 use std::env;
+use std::sync::{Arc, RwLock};
 
 /// Secret provider trait for abstracting secret retrieval
 #[async_trait::async_trait]
@@ -17,6 +19,36 @@ impl SecretProvider for EnvSecretProvider {
     }
 }
 
+/// In-memory secret provider, for tests and tooling that stage secrets by hand
+///
+/// Clones share their secrets, so a value set after the provider was handed
+/// to a [`SecretManager`] is what the next uncached lookup returns.
+#[derive(Clone, Default)]
+pub struct MemorySecretProvider {
+    secrets: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl MemorySecretProvider {
+    pub fn set(&self, key: &str, value: &str) {
+        self.secrets
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+#[async_trait::async_trait]
+impl SecretProvider for MemorySecretProvider {
+    async fn get_secret(&self, key: &str) -> Result<String, AppError> {
+        self.secrets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+            .ok_or_else(|| AppError::ConfigError(format!("Secret '{}' not found", key)))
+    }
+}
+
 /// AWS Secrets Manager provider
 #[cfg(feature = "aws-secrets")]
 pub struct AwsSecretsProvider {
@@ -105,21 +137,27 @@ impl SecretProvider for VaultSecretProvider {
 }
 
 /// Secret manager that can use different providers
+///
+/// Values are cached after the first successful lookup, so remote providers
+/// are asked once per key; [`invalidate`](Self::invalidate) forgets them so
+/// rotated secrets are fetched again.
 pub struct SecretManager {
     provider: Box<dyn SecretProvider>,
+    cache: RwLock<HashMap<String, String>>,
 }
 
 impl SecretManager {
     /// Create a new secret manager with environment variable provider (default)
     pub fn new_env() -> Self {
-        Self {
-            provider: Box::new(EnvSecretProvider),
-        }
+        Self::new(Box::new(EnvSecretProvider))
     }
 
     /// Create a new secret manager with custom provider
     pub fn new(provider: Box<dyn SecretProvider>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            cache: RwLock::new(HashMap::new()),
+        }
     }
 
     /// Initialize secret manager based on configuration
@@ -161,13 +199,27 @@ impl SecretManager {
 
     /// Get a secret by key
     pub async fn get_secret(&self, key: &str) -> Result<String, AppError> {
-        self.provider.get_secret(key).await
+        if let Some(value) = self.cache.read().unwrap_or_else(|e| e.into_inner()).get(key) {
+            return Ok(value.clone());
+        }
+
+        let value = self.provider.get_secret(key).await?;
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), value.clone());
+        Ok(value)
+    }
+
+    /// Forget every cached secret, so the next lookups ask the provider again
+    pub fn invalidate(&self) {
+        self.cache.write().unwrap_or_else(|e| e.into_inner()).clear();
     }
 
     /// Get a secret with a fallback to environment variable
     pub async fn get_secret_or_env(&self, key: &str, env_key: Option<&str>) -> Result<String, AppError> {
         // Try secret manager first
-        match self.provider.get_secret(key).await {
+        match self.get_secret(key).await {
             Ok(value) => Ok(value),
             Err(_) => {
                 // Fallback to environment variable
@@ -220,4 +272,18 @@ mod tests {
 
         env::remove_var("TEST_SECRET_2");
     }
+
+    #[tokio::test]
+    async fn test_secrets_are_cached_until_invalidated() {
+        let provider = MemorySecretProvider::default();
+        provider.set("API_KEY", "first");
+        let manager = SecretManager::new(Box::new(provider.clone()));
+
+        assert_eq!(manager.get_secret("API_KEY").await.unwrap(), "first");
+        provider.set("API_KEY", "second");
+        assert_eq!(manager.get_secret("API_KEY").await.unwrap(), "first");
+
+        manager.invalidate();
+        assert_eq!(manager.get_secret("API_KEY").await.unwrap(), "second");
+    }
 }
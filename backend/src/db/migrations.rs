@@ -0,0 +1,31 @@
+//! Embedded schema migrations, applied at startup
+
+use diesel::{Connection, PgConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+use crate::error::AppError;
+
+/// Migrations baked into the binary at compile time
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Apply pending migrations, returning how many ran
+///
+/// The migration harness is synchronous, so this opens its own short-lived
+/// connection on the blocking pool instead of borrowing one from `DbPool`.
+#[tracing::instrument(name = "db_migrations", skip(database_url))]
+pub async fn run_pending(database_url: &str) -> Result<usize, AppError> {
+    let database_url = database_url.to_string();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = PgConnection::establish(&database_url)
+            .map_err(|e| AppError::database("Failed to connect for migrations", e))?;
+        let applied = conn
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|e| AppError::DatabaseError {
+                message: "Failed to run migrations".to_string(),
+                source: Some(e),
+            })?;
+        Ok(applied.len())
+    })
+    .await
+    .map_err(|e| AppError::internal("Migration task panicked", e))?
+}
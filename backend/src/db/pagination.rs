@@ -1,12 +1,20 @@
-use crate::models::{PaginatedResponse, PaginationMeta, PaginationParams};
+use crate::{
+    config::PaginationConfig,
+    models::{PaginatedResponse, PaginationMeta, PaginationParams},
+};
 
-const MAX_PER_PAGE: i64 = 100;
+/// `per_page` when neither the client nor `PAGINATION_DEFAULT` sets one
+pub const DEFAULT_PER_PAGE: i64 = 20;
+
+/// Upper bound on `per_page` unless overridden by `PAGINATION_MAX`
+pub const DEFAULT_MAX_PER_PAGE: i64 = 100;
 
 /// Helper function to create pagination parameters
-pub fn paginate_params(params: &PaginationParams) -> (i64, i64) {
-    let page = params.page.max(1);
-    let per_page = params.per_page.clamp(1, MAX_PER_PAGE);
-    (page, per_page)
+///
+/// Applies the configured default page size and max per-page limit.
+pub fn paginate_params(params: &PaginationParams, config: &PaginationConfig) -> (i64, i64) {
+    let clamped = params.clamped(config);
+    (clamped.page, clamped.limit())
 }
 
 /// Calculate offset for pagination
@@ -44,7 +52,7 @@ pub fn create_paginated_response<T>(
 /// use diesel_async::RunQueryDsl;
 ///
 /// // Get pagination params
-/// let (page, per_page) = paginate_params(&params);
+/// let (page, per_page) = paginate_params(&params, &state.config.pagination);
 /// let offset = calculate_offset(page, per_page);
 ///
 /// // Count total
@@ -67,34 +75,62 @@ pub fn create_paginated_response<T>(
 mod tests {
     use super::*;
 
+    fn params(page: i64, per_page: Option<i64>) -> PaginationParams {
+        PaginationParams { page, per_page }
+    }
+
     #[test]
     fn test_paginate_params() {
-        let params = PaginationParams {
-            page: 1,
-            per_page: 20,
-        };
-        let (page, per_page) = paginate_params(&params);
+        let (page, per_page) = paginate_params(&params(1, Some(20)), &PaginationConfig::default());
         assert_eq!(page, 1);
         assert_eq!(per_page, 20);
     }
 
     #[test]
     fn test_paginate_params_max_per_page() {
-        let params = PaginationParams {
-            page: 1,
-            per_page: 200, // Over max
-        };
-        let (_, per_page) = paginate_params(&params);
-        assert_eq!(per_page, MAX_PER_PAGE);
+        let (_, per_page) = paginate_params(&params(1, Some(200)), &PaginationConfig::default());
+        assert_eq!(per_page, DEFAULT_MAX_PER_PAGE);
     }
 
     #[test]
     fn test_paginate_params_min_page() {
-        let params = PaginationParams {
-            page: -1, // Invalid
-            per_page: 20,
-        };
-        let (page, _) = paginate_params(&params);
+        let (page, _) = paginate_params(&params(-1, Some(20)), &PaginationConfig::default());
         assert_eq!(page, 1);
     }
+
+    #[test]
+    fn test_paginate_params_respects_custom_max() {
+        let config = PaginationConfig {
+            default_per_page: 10,
+            max_per_page: 25,
+        };
+
+        let (_, per_page) = paginate_params(&params(1, Some(50)), &config);
+        assert_eq!(per_page, 25);
+
+        let (_, per_page) = paginate_params(&params(1, Some(0)), &config);
+        assert_eq!(per_page, 1);
+    }
+
+    #[test]
+    fn test_missing_per_page_uses_configured_default() {
+        let config = PaginationConfig {
+            default_per_page: 10,
+            max_per_page: 25,
+        };
+
+        let clamped = params(3, None).clamped(&config);
+        assert_eq!(clamped.limit(), 10);
+        assert_eq!(clamped.offset(), 20);
+    }
+
+    #[test]
+    fn test_config_rejects_default_above_max() {
+        let config = PaginationConfig {
+            default_per_page: 50,
+            max_per_page: 25,
+        };
+        assert!(config.validate().is_err());
+        assert!(PaginationConfig::default().validate().is_ok());
+    }
 }
@@ -3,13 +3,18 @@
 //! Provides macros to log SQL queries in debug builds when QUERY_LOG=1 is set.
 //! This helps identify slow queries and debug N+1 query problems.
 
-/// Execute a query and log it (only in debug builds with QUERY_LOG=1)
+/// Execute a query, timing it and logging it (in debug builds with QUERY_LOG=1)
 ///
 /// Automatically logs query execution time and result count when applicable.
+/// The duration is also added to the request's `db` Server-Timing entry.
 ///
 /// Usage:
-/// ```rust
-/// use crate::db::query_log::logged_query;
+/// ```no_run
+/// use backend::logged_query;
+/// # use backend::{db::schema::users, models::user::User};
+/// # use diesel::prelude::*;
+/// # use diesel_async::{AsyncPgConnection, RunQueryDsl};
+/// # async fn example(mut conn: AsyncPgConnection, id: uuid::Uuid) -> Result<(), diesel::result::Error> {
 ///
 /// let user = logged_query!(
 ///     "SELECT * FROM users WHERE id = $1",
@@ -20,25 +25,21 @@
 ///     "SELECT * FROM users LIMIT 10",
 ///     users::table.limit(10).load::<User>(&mut conn).await
 /// )?;
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! logged_query {
     ($description:expr, $query:expr) => {{
-        #[cfg(debug_assertions)]
-        {
-            let start = std::time::Instant::now();
-            let result = $query;
-            let duration = start.elapsed();
+        let start = std::time::Instant::now();
+        let result = $query;
+        let duration = start.elapsed();
 
-            // Log the query execution
-            $crate::db::log_query($description, duration);
+        $crate::middleware::server_timing::record("db", duration);
+        // Log the query execution (no-op in release builds)
+        $crate::db::log_query($description, duration);
 
-            result
-        }
-        #[cfg(not(debug_assertions))]
-        {
-            $query
-        }
+        result
     }};
 }
 
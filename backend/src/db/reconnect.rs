@@ -0,0 +1,127 @@
+//! Recovering from connections the database dropped
+//!
+//! When Postgres restarts or terminates a backend, in-flight queries fail
+//! with connection errors. Those errors become `AppError::DatabaseUnavailable`
+//! (503 with `Retry-After`) instead of an opaque 500. The dead connection is
+//! detached from the pool rather than handed out again, and reads wrapped in
+//! [`retry_read!`](crate::retry_read) get one more attempt on a fresh
+//! connection.
+//!
+//! Writes are never retried: a dropped connection leaves it unknown whether
+//! the statement committed.
+use diesel::result::{DatabaseErrorKind, Error};
+
+use super::{DbConnection, PooledConnection};
+
+/// Messages Postgres sends when it terminates a backend; they carry an
+/// `57P01`-style SQLSTATE that diesel doesn't map to a specific kind
+const TERMINATION_MESSAGES: &[&str] = &[
+    "terminating connection",
+    "server closed the connection",
+    "connection reset",
+];
+
+/// Whether `err` means the connection itself is gone, not that the query failed
+pub fn is_connection_lost(err: &Error) -> bool {
+    match err {
+        Error::DatabaseError(
+            DatabaseErrorKind::ClosedConnection | DatabaseErrorKind::UnableToSendCommand,
+            _,
+        ) => true,
+        Error::DatabaseError(DatabaseErrorKind::Unknown, info) => {
+            let message = info.message().to_lowercase();
+            TERMINATION_MESSAGES.iter().any(|m| message.contains(m))
+        }
+        Error::BrokenTransactionManager => true,
+        _ => false,
+    }
+}
+
+/// Remove a connection from its pool for good
+///
+/// Dropping a pooled connection normally returns it for reuse; a detached
+/// one is closed and the pool opens a replacement on demand.
+pub fn discard(conn: DbConnection) {
+    crate::metrics::record_connection_lost();
+    let _ = PooledConnection::take(conn.into_inner());
+}
+
+/// Run a read query, retrying once on a fresh connection if the first
+/// connection was lost mid-query
+///
+/// Binds a pooled connection to `conn` for the query expression, which must
+/// evaluate to a `QueryResult`. Only use it for statements that are safe to
+/// run twice.
+///
+/// # Example
+/// ```no_run
+/// use backend::retry_read;
+/// # use backend::{db::{schema::users, DbPool}, error::AppError, models::user::User};
+/// # use diesel::prelude::*;
+/// # use diesel_async::RunQueryDsl;
+/// # struct Repo { db_pool: DbPool }
+/// # impl Repo {
+/// # async fn example(&self, id: uuid::Uuid) -> Result<(), AppError> {
+/// let user = retry_read!(&self.db_pool, |conn| {
+///     users::table.find(id).first::<User>(&mut conn).await
+/// })?;
+/// # Ok(())
+/// # }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! retry_read {
+    ($pool:expr, |$conn:ident| $query:expr) => {{
+        let mut retried = false;
+        loop {
+            #[allow(unused_mut)]
+            let mut $conn = $crate::db::get_connection($pool).await?;
+            let result = $query;
+            match &result {
+                Err(e) if $crate::db::reconnect::is_connection_lost(e) => {
+                    $crate::db::reconnect::discard($conn);
+                    if retried {
+                        break result;
+                    }
+                    $crate::db::reconnect::log_retry(e);
+                    retried = true;
+                }
+                _ => break result,
+            }
+        }
+    }};
+}
+
+/// Logged before [`retry_read!`](crate::retry_read) tries again
+pub fn log_retry(err: &Error) {
+    tracing::warn!(error = %err, "Database connection lost mid-query, retrying on a fresh connection");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_error(kind: DatabaseErrorKind, message: &str) -> Error {
+        Error::DatabaseError(kind, Box::new(message.to_string()))
+    }
+
+    #[test]
+    fn test_connection_errors_are_detected() {
+        assert!(is_connection_lost(&db_error(DatabaseErrorKind::ClosedConnection, "closed")));
+        assert!(is_connection_lost(&db_error(
+            DatabaseErrorKind::UnableToSendCommand,
+            "connection closed"
+        )));
+        assert!(is_connection_lost(&db_error(
+            DatabaseErrorKind::Unknown,
+            "terminating connection due to administrator command"
+        )));
+    }
+
+    #[test]
+    fn test_query_errors_are_not_connection_errors() {
+        assert!(!is_connection_lost(&db_error(DatabaseErrorKind::UniqueViolation, "duplicate key")));
+        assert!(!is_connection_lost(&db_error(DatabaseErrorKind::Unknown, "syntax error")));
+        assert!(!is_connection_lost(&Error::NotFound));
+    }
+}
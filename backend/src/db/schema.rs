@@ -11,5 +11,14 @@ diesel::table! {
         password_hash -> Varchar,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        #[max_length = 20]
+        role -> Varchar,
+        #[max_length = 63]
+        tenant_id -> Varchar,
+        #[max_length = 32]
+        provider -> Nullable<Varchar>,
+        #[max_length = 255]
+        provider_id -> Nullable<Varchar>,
+        last_login_at -> Nullable<Timestamp>,
     }
 }
@@ -8,46 +8,72 @@ use crate::{
     db::{schema::users, DbPool},
     error::AppError,
     models::user::{NewUser, User},
+    types::TenantId,
 };
 
+/// Password of the demo accounts and generated users
+const SEED_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$iXBxD0QgNIoWWchw9QwoUw$3iSquYF7p2H7IeqNeNeUXQtFv49R9OWLPyVYY6+eEUI";
+
 /// Seed data configuration
 #[derive(Default)]
 pub struct SeedConfig {
+    /// Tenant to seed (and clear)
+    pub tenant: TenantId,
     pub clear_existing: bool,
+    /// Generated users to add on top of the demo accounts
+    pub count: usize,
 }
 
 /// Main seeding function
-pub async fn seed_database(pool: &DbPool, config: SeedConfig) -> Result<(), AppError> {
+///
+/// Returns how many users were created; demo accounts that already exist
+/// are skipped.
+pub async fn seed_database(pool: &DbPool, config: SeedConfig) -> Result<usize, AppError> {
     tracing::info!("Starting database seeding");
 
     if config.clear_existing {
-        clear_users(pool).await?;
+        clear_users(pool, &config.tenant).await?;
     }
 
-    seed_users(pool).await?;
+    let created = seed_users(pool, &config.tenant, config.count).await?;
 
-    tracing::info!("Database seeding completed");
-    Ok(())
+    tracing::info!(created, "Database seeding completed");
+    Ok(created)
 }
 
-/// Clear all users (use with caution!)
-async fn clear_users(pool: &DbPool) -> Result<(), AppError> {
+/// Clear all users in the tenant (use with caution!)
+async fn clear_users(pool: &DbPool, tenant: &TenantId) -> Result<(), AppError> {
     let mut conn = pool
         .get()
         .await
         .map_err(|e| AppError::database("Failed to get connection", e))?;
 
-    diesel::delete(users::table)
+    diesel::delete(users::table.filter(users::tenant_id.eq(tenant.as_str())))
         .execute(&mut conn)
         .await
         .map_err(|e| AppError::database("Failed to clear users", e))?;
 
-    tracing::info!("Cleared existing users");
+    tracing::info!(tenant = %tenant, "Cleared existing users");
     Ok(())
 }
 
-/// Seed example users
-async fn seed_users(pool: &DbPool) -> Result<(), AppError> {
+/// `count` users with random, non-colliding names
+fn generated_users(count: usize) -> impl Iterator<Item = NewUser> {
+    (0..count).map(|_| {
+        let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+        NewUser {
+            email: format!("seed-{}@example.com", suffix),
+            username: format!("seed{}", suffix),
+            // Hash of "Password123!"
+            password_hash: SEED_PASSWORD_HASH.to_string(),
+            provider: None,
+            provider_id: None,
+        }
+    })
+}
+
+/// Seed example users into the tenant
+async fn seed_users(pool: &DbPool, tenant: &TenantId, count: usize) -> Result<usize, AppError> {
     let mut conn = pool
         .get()
         .await
@@ -60,31 +86,41 @@ async fn seed_users(pool: &DbPool) -> Result<(), AppError> {
             email: "admin@example.com".to_string(),
             username: "admin".to_string(),
             // Hash of "Password123!"
-            password_hash: "$argon2id$v=19$m=19456,t=2,p=1$iXBxD0QgNIoWWchw9QwoUw$3iSquYF7p2H7IeqNeNeUXQtFv49R9OWLPyVYY6+eEUI".to_string(),
+            password_hash: SEED_PASSWORD_HASH.to_string(),
+            provider: None,
+            provider_id: None,
         },
         NewUser {
             email: "user@example.com".to_string(),
             username: "user".to_string(),
             // Hash of "Password123!"
-            password_hash: "$argon2id$v=19$m=19456,t=2,p=1$iXBxD0QgNIoWWchw9QwoUw$3iSquYF7p2H7IeqNeNeUXQtFv49R9OWLPyVYY6+eEUI".to_string(),
+            password_hash: SEED_PASSWORD_HASH.to_string(),
+            provider: None,
+            provider_id: None,
         },
         NewUser {
             email: "test@example.com".to_string(),
             username: "testuser".to_string(),
             // Hash of "Password123!"
-            password_hash: "$argon2id$v=19$m=19456,t=2,p=1$iXBxD0QgNIoWWchw9QwoUw$3iSquYF7p2H7IeqNeNeUXQtFv49R9OWLPyVYY6+eEUI".to_string(),
+            password_hash: SEED_PASSWORD_HASH.to_string(),
+            provider: None,
+            provider_id: None,
         },
         NewUser {
             email: "logintest@example.com".to_string(),
             username: "logintest".to_string(),
             // Hash of "SecurePass123!"
             password_hash: "$argon2id$v=19$m=19456,t=2,p=1$cgotbcGnyJLuY9W9yKH2Ow$E5hJaIbdcMT5IsEciC1JJ/1BQK7lZVGBElszRBBL9DQ".to_string(),
+            provider: None,
+            provider_id: None,
         },
     ];
 
-    for new_user in test_users {
+    let mut created = 0;
+    for new_user in test_users.into_iter().chain(generated_users(count)) {
         // Check if user already exists
         let existing = users::table
+            .filter(users::tenant_id.eq(tenant.as_str()))
             .filter(users::email.eq(&new_user.email))
             .first::<User>(&mut conn)
             .await
@@ -93,18 +129,19 @@ async fn seed_users(pool: &DbPool) -> Result<(), AppError> {
 
         if existing.is_none() {
             diesel::insert_into(users::table)
-                .values(&new_user)
+                .values((&new_user, users::tenant_id.eq(tenant.as_str())))
                 .execute(&mut conn)
                 .await
                 .map_err(|e| AppError::database("Failed to insert user", e))?;
 
+            created += 1;
             tracing::info!("Seeded user: {}", new_user.username);
         } else {
             tracing::info!("User already exists, skipping: {}", new_user.username);
         }
     }
 
-    Ok(())
+    Ok(created)
 }
 
 #[cfg(test)]
@@ -118,6 +155,8 @@ mod tests {
         //
         // let config = SeedConfig {
         //     clear_existing: true,
+        //     count: 10,
+        //     ..SeedConfig::default()
         // };
         //
         // let result = seed_database(&pool, config).await;
@@ -21,20 +21,25 @@ where
 /// # Example
 /// ```no_run
 /// use backend::tx;
+/// # use backend::{db::schema::users, error::AppError, AppState};
+/// # use diesel::prelude::*;
+/// # use diesel_async::RunQueryDsl;
+/// # async fn example(state: AppState, old_owner: uuid::Uuid, new_owner: uuid::Uuid) -> Result<(), AppError> {
 ///
 /// tx!(&state.db_pool, |conn| async move {
-///     diesel::update(users::table.find(user_id))
-///         .set(balance.eq(balance - 100))
+///     diesel::update(users::table.find(old_owner))
+///         .set(users::role.eq("user"))
 ///         .execute(conn)
 ///         .await?;
 ///
-///     diesel::insert_into(transactions::table)
-///         .values(&new_transaction)
+///     diesel::update(users::table.find(new_owner))
+///         .set(users::role.eq("admin"))
 ///         .execute(conn)
 ///         .await?;
 ///
 ///     Ok(())
 /// })
+/// # }
 /// ```
 #[macro_export]
 macro_rules! tx {
@@ -6,10 +6,17 @@
 /// Pretty-print JSON values in logs (debug builds only)
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::{dbg_json, error::AppError};
+/// # async fn get_user() -> Result<serde_json::Value, AppError> {
+/// #     Ok(serde_json::json!({ "id": "123", "email": "test@example.com" }))
+/// # }
+/// # async fn example() -> Result<(), AppError> {
 /// let user = get_user().await?;
 /// dbg_json!(user);
 /// // Logs: user = { "id": "123", "email": "test@example.com" }
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! dbg_json {
@@ -27,9 +34,12 @@ macro_rules! dbg_json {
 /// Enhanced todo! with automatic logging
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::todo_with_msg;
+/// # fn cached() {
 /// todo_with_msg!("Need to implement caching here");
 /// // Logs a warning then panics with the message
+/// # }
 /// ```
 #[macro_export]
 macro_rules! todo_with_msg {
@@ -43,7 +53,8 @@ macro_rules! todo_with_msg {
 /// Quick endpoint stub for prototyping
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::stub_handler;
 /// stub_handler!(get_analytics);
 /// // Creates: pub async fn get_analytics() -> Result<Json<Value>, AppError>
 /// ```
@@ -65,12 +76,18 @@ macro_rules! stub_handler {
 /// Time a code block and log the duration
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::{error::AppError, time_block};
+/// # async fn fetch_users() -> Result<Vec<String>, AppError> { Ok(Vec::new()) }
+/// # fn process(users: Vec<String>) -> usize { users.len() }
+/// # async fn example() -> Result<(), AppError> {
 /// time_block!("database query", {
 ///     let users = fetch_users().await?;
 ///     process(users)
 /// });
 /// // Logs: ⏱️  database query took: 45ms
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! time_block {
@@ -83,7 +100,8 @@ macro_rules! time_block {
 /// Log entry and exit of a function (debug builds only)
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::trace_fn;
 /// fn process_data(id: &str) {
 ///     trace_fn!("process_data", id);
 ///     // ... function body
@@ -96,7 +114,7 @@ macro_rules! trace_fn {
     ($name:expr $(, $param:expr)*) => {
         #[cfg(debug_assertions)]
         {
-            tracing::debug!("→ {}({})", $name, format!($("{:?} ",)* $(, $param)*).trim());
+            tracing::debug!("→ {}({})", $name, <[String]>::join(&[$(format!("{:?}", $param)),*], ", "));
             let _guard = $crate::dev_macros::FnTracer::new($name);
         }
     };
@@ -105,7 +123,9 @@ macro_rules! trace_fn {
 /// Assert that a value matches a pattern, with helpful error message
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::assert_matches;
+/// # let result: Result<u32, String> = Ok(1);
 /// assert_matches!(result, Ok(_), "Expected successful result");
 /// ```
 #[macro_export]
@@ -121,11 +141,17 @@ macro_rules! assert_matches {
 /// Quick database error with context
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::{db::schema::users::dsl::users, db_err, error::AppError, models::user::User};
+/// # use diesel::prelude::*;
+/// # use diesel_async::{AsyncPgConnection, RunQueryDsl};
+/// # async fn example(mut conn: AsyncPgConnection, id: uuid::Uuid) -> Result<User, AppError> {
 /// let user = users.find(id)
 ///     .first(&mut conn)
 ///     .await
 ///     .map_err(db_err!("Failed to find user {}", id))?;
+/// # Ok(user)
+/// # }
 /// ```
 #[macro_export]
 macro_rules! db_err {
@@ -137,8 +163,13 @@ macro_rules! db_err {
 /// Quick internal error with context
 ///
 /// # Example
-/// ```
+/// ```no_run
+/// # use backend::{error::AppError, internal_err};
+/// # fn parse_config() -> Result<(), std::io::Error> { Ok(()) }
+/// # fn example() -> Result<(), AppError> {
 /// parse_config().map_err(internal_err!("Config parsing failed"))?;
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! internal_err {
@@ -1,5 +1,8 @@
+use utoipa::openapi::{self, Server};
 use utoipa::OpenApi;
 
+use crate::config::Config;
+
 #[derive(OpenApi)]
 #[openapi(
     info(
@@ -13,10 +16,21 @@ use utoipa::OpenApi;
     ),
     paths(
         crate::handlers::health::health_check,
-        // TODO: Add utoipa::path annotations to auth handlers
-        // crate::handlers::auth::register,
-        // crate::handlers::auth::login,
-        // crate::handlers::auth::me,
+        crate::handlers::health::liveness,
+        crate::handlers::health::readiness_check,
+        crate::handlers::health::version,
+        crate::handlers::auth::register,
+        crate::handlers::auth::login,
+        crate::handlers::auth::logout,
+        crate::handlers::auth::me,
+        crate::handlers::auth::update_me,
+        crate::handlers::auth::delete_me,
+        crate::handlers::auth::nonce,
+        crate::handlers::admin::stats,
+        crate::handlers::admin::reload_secrets,
+        crate::handlers::user::list_users,
+        crate::handlers::user::bulk_delete,
+        crate::handlers::user::export_csv,
         // Add more paths here as you create them
     ),
     components(
@@ -24,18 +38,121 @@ use utoipa::OpenApi;
             crate::models::HealthResponse,
             crate::models::HealthChecks,
             crate::models::SubsystemHealth,
+            crate::models::LivenessResponse,
+            crate::models::ReadinessResponse,
+            crate::models::VersionResponse,
+            crate::models::AdminStatsResponse,
+            crate::models::SecretReloadResponse,
+            crate::models::PoolStatsResponse,
+            crate::models::RequestStatsResponse,
             crate::models::PaginationParams,
             crate::models::PaginationMeta,
             crate::models::dto::RegisterRequestDto,
             crate::models::dto::LoginRequestDto,
             crate::models::dto::UserResponseDto,
             crate::models::dto::AuthResponseDto,
+            crate::models::dto::UpdateUserRequestDto,
+            crate::models::dto::NonceResponseDto,
+            crate::models::dto::ListUsersResponseDto,
+            crate::models::dto::BulkDeleteUsersRequestDto,
+            crate::models::dto::BulkDeleteUsersResponseDto,
+            crate::error::ErrorCode,
             // Add more schemas here
         )
     ),
     tags(
         (name = "health", description = "Health check endpoints"),
         (name = "auth", description = "Authentication endpoints"),
+        (name = "admin", description = "Admin-only endpoints"),
     )
 )]
 pub struct ApiDoc;
+
+/// The OpenAPI document as served, with the `servers` entry and the
+/// title/version/contact overrides taken from `config`
+///
+/// Swagger UI's "Try it out" sends requests to the first server, so it has
+/// to be the address clients actually reach (OPENAPI_SERVER_URL behind a
+/// proxy).
+pub fn openapi(config: &Config) -> openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    let docs = &config.docs;
+
+    if let Some(title) = &docs.title {
+        doc.info.title = title.clone();
+    }
+    if let Some(version) = &docs.version {
+        doc.info.version = version.clone();
+    }
+    if docs.contact_name.is_some() || docs.contact_email.is_some() || docs.contact_url.is_some() {
+        let contact = doc.info.contact.get_or_insert_with(Default::default);
+        if let Some(name) = &docs.contact_name {
+            contact.name = Some(name.clone());
+        }
+        if let Some(email) = &docs.contact_email {
+            contact.email = Some(email.clone());
+        }
+        if let Some(url) = &docs.contact_url {
+            contact.url = Some(url.clone());
+        }
+    }
+
+    let mut server = Server::new(server_url(config));
+    server.description = Some(config.server.environment.to_string());
+    doc.servers = Some(vec![server]);
+    doc
+}
+
+/// OPENAPI_SERVER_URL, or the address the server listens on
+///
+/// Wildcard bind addresses aren't reachable as such, so `0.0.0.0` and `::`
+/// become `localhost`.
+pub fn server_url(config: &Config) -> String {
+    if let Some(url) = &config.docs.server_url {
+        return url.clone();
+    }
+    let scheme = if config.server.tls.is_some() { "https" } else { "http" };
+    let host = match config.server.host.as_str() {
+        "0.0.0.0" | "::" | "[::]" => "localhost",
+        host => host,
+    };
+    if host.contains(':') && !host.starts_with('[') {
+        format!("{}://[{}]:{}", scheme, host, config.server.port)
+    } else {
+        format!("{}://{}:{}", scheme, host, config.server.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_url_from_bind_address() {
+        let mut config = Config::default_test_config();
+        config.server.host = "0.0.0.0".to_string();
+        config.server.port = 8080;
+        assert_eq!(server_url(&config), "http://localhost:8080");
+
+        config.server.host = "::1".to_string();
+        assert_eq!(server_url(&config), "http://[::1]:8080");
+
+        config.docs.server_url = Some("https://api.example.com".to_string());
+        assert_eq!(server_url(&config), "https://api.example.com");
+    }
+
+    #[test]
+    fn test_metadata_overrides() {
+        let mut config = Config::default_test_config();
+        config.docs.title = Some("Acme API".to_string());
+        config.docs.contact_email = Some("api@acme.test".to_string());
+
+        let doc = openapi(&config);
+
+        assert_eq!(doc.info.title, "Acme API");
+        assert_eq!(doc.info.version, ApiDoc::openapi().info.version);
+        let contact = doc.info.contact.unwrap();
+        assert_eq!(contact.email.as_deref(), Some("api@acme.test"));
+        assert_eq!(contact.name.as_deref(), Some("API Support"));
+    }
+}
@@ -1,9 +1,12 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// Convenient type alias for Results with AppError
@@ -142,6 +145,69 @@ macro_rules! bail_unauthorized {
     };
 }
 
+/// Machine-readable error code sent as `error_code` in error responses
+///
+/// Serialized in SCREAMING_SNAKE_CASE (`NOT_FOUND`, `TOKEN_EXPIRED`, ...).
+/// Clients should match on these rather than on the human-readable message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    DatabaseError,
+    DatabaseUnavailable,
+    NotFound,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    TokenExpired,
+    TokenInvalid,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    UriTooLong,
+    TooManyRequests,
+    ServiceUnavailable,
+    UnsupportedMediaType,
+    Conflict,
+    PreconditionFailed,
+    InternalServerError,
+    ValidationError,
+    ConfigError,
+    ExternalServiceError,
+}
+
+impl ErrorCode {
+    /// The serialized form, for logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DatabaseError => "DATABASE_ERROR",
+            Self::DatabaseUnavailable => "DATABASE_UNAVAILABLE",
+            Self::NotFound => "NOT_FOUND",
+            Self::BadRequest => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::TokenExpired => "TOKEN_EXPIRED",
+            Self::TokenInvalid => "TOKEN_INVALID",
+            Self::MethodNotAllowed => "METHOD_NOT_ALLOWED",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::UriTooLong => "URI_TOO_LONG",
+            Self::TooManyRequests => "TOO_MANY_REQUESTS",
+            Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            Self::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            Self::Conflict => "CONFLICT",
+            Self::PreconditionFailed => "PRECONDITION_FAILED",
+            Self::InternalServerError => "INTERNAL_SERVER_ERROR",
+            Self::ValidationError => "VALIDATION_ERROR",
+            Self::ConfigError => "CONFIG_ERROR",
+            Self::ExternalServiceError => "EXTERNAL_SERVICE_ERROR",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Application error type with context chaining support
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -152,6 +218,14 @@ pub enum AppError {
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
+    /// The database dropped the connection or can't be reached; worth retrying
+    #[error("Database unavailable: {message}")]
+    DatabaseUnavailable {
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
     #[error("Not found: {0}")]
     NotFound(String),
 
@@ -161,6 +235,42 @@ pub enum AppError {
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Token expired: {0}")]
+    TokenExpired(String),
+
+    #[error("Token invalid: {0}")]
+    TokenInvalid(String),
+
+    #[error("Method not allowed: {0}")]
+    MethodNotAllowed(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("URI too long: {0}")]
+    UriTooLong(String),
+
+    /// Throttled; the client may retry after `retry_after_secs`
+    #[error("Too many requests: {message}")]
+    TooManyRequests { message: String, retry_after_secs: u64 },
+
+    /// The server is at capacity; the client may retry after `retry_after_secs`
+    #[error("Service unavailable: {message}")]
+    ServiceUnavailable { message: String, retry_after_secs: u64 },
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// A conditional request header (e.g. `If-Unmodified-Since`) didn't hold
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
     #[error("Internal server error: {message}")]
     InternalServerError {
         message: String,
@@ -180,14 +290,34 @@ pub enum AppError {
         #[source]
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
+
+    /// Another error plus client-safe `details`; see [`AppError::with_details`]
+    #[error("{error}")]
+    WithDetails {
+        error: Box<AppError>,
+        details: serde_json::Value,
+    },
 }
 
 impl AppError {
     /// Create a database error with context
+    ///
+    /// A query that failed because its connection was lost becomes
+    /// `DatabaseUnavailable` (503) instead of a plain database error.
     pub fn database<E>(message: impl Into<String>, error: E) -> Self
     where
         E: std::error::Error + Send + Sync + 'static,
     {
+        let lost = (&error as &dyn std::any::Any)
+            .downcast_ref::<diesel::result::Error>()
+            .is_some_and(crate::db::reconnect::is_connection_lost);
+        if lost {
+            return Self::DatabaseUnavailable {
+                message: message.into(),
+                source: Some(Box::new(error)),
+            };
+        }
+
         Self::DatabaseError {
             message: message.into(),
             source: Some(Box::new(error)),
@@ -205,6 +335,28 @@ impl AppError {
         }
     }
 
+    /// Attach client-facing `details` to the response body
+    ///
+    /// Unlike `debug_info`, details are sent in every build, so only attach
+    /// what a client may see (the field that failed, the allowed values),
+    /// never internals. Attaching again replaces earlier details.
+    ///
+    /// ```no_run
+    /// # use backend::error::AppError;
+    /// # use serde_json::json;
+    /// # let _ =
+    /// AppError::BadRequest("Unsupported currency".into())
+    ///     .with_details(json!({ "field": "currency", "allowed": ["EUR", "USD"] }))
+    /// # ;
+    /// ```
+    pub fn with_details(self, details: serde_json::Value) -> Self {
+        let error = match self {
+            AppError::WithDetails { error, .. } => error,
+            error => Box::new(error),
+        };
+        AppError::WithDetails { error, details }
+    }
+
     /// Create an external service error with context
     pub fn external_service<E>(service: impl Into<String>, error: E) -> Self
     where
@@ -217,13 +369,42 @@ impl AppError {
     }
 }
 
+/// How long clients should wait before retrying when the database is unavailable
+const DATABASE_RETRY_AFTER_SECS: u64 = 1;
+
+/// Pretty-print error bodies (PRETTY_ERRORS=1, debug builds only)
+#[cfg(debug_assertions)]
+static PRETTY_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Serialize error bodies with indentation for reading in a terminal
+///
+/// Only takes effect in debug builds; release builds always answer with
+/// compact JSON.
+pub fn set_pretty_errors(enabled: bool) {
+    #[cfg(debug_assertions)]
+    PRETTY_ERRORS.store(enabled, Ordering::Relaxed);
+    #[cfg(not(debug_assertions))]
+    let _ = enabled;
+}
+
+fn pretty_errors() -> bool {
+    #[cfg(debug_assertions)]
+    return PRETTY_ERRORS.load(Ordering::Relaxed);
+    #[cfg(not(debug_assertions))]
+    false
+}
+
 #[derive(Serialize)]
 struct ErrorResponse {
     error_id: String,
-    error_code: String,
+    error_code: ErrorCode,
     error: String,
+    /// OpenTelemetry trace of the failed request, for looking it up in Jaeger/Tempo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace_id: Option<String>,
+    /// Client-safe specifics attached with `AppError::with_details`
     #[serde(skip_serializing_if = "Option::is_none")]
-    details: Option<String>,
+    details: Option<serde_json::Value>,
     #[cfg(debug_assertions)]
     #[serde(skip_serializing_if = "Option::is_none")]
     debug_info: Option<DebugInfo>,
@@ -236,104 +417,164 @@ struct ErrorResponse {
 #[derive(Serialize)]
 struct DebugInfo {
     error_chain: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     backtrace: Option<String>,
 }
 
 impl AppError {
-    fn error_code(&self) -> &str {
+    /// Code sent as `error_code` in the response body
+    pub fn error_code(&self) -> ErrorCode {
         match self {
-            AppError::DatabaseError { .. } => "DATABASE_ERROR",
-            AppError::NotFound(_) => "NOT_FOUND",
-            AppError::BadRequest(_) => "BAD_REQUEST",
-            AppError::Unauthorized(_) => "UNAUTHORIZED",
-            AppError::InternalServerError { .. } => "INTERNAL_SERVER_ERROR",
-            AppError::ValidationError(_) => "VALIDATION_ERROR",
-            AppError::ConfigError(_) => "CONFIG_ERROR",
-            AppError::ExternalServiceError { .. } => "EXTERNAL_SERVICE_ERROR",
+            AppError::DatabaseError { .. } => ErrorCode::DatabaseError,
+            AppError::DatabaseUnavailable { .. } => ErrorCode::DatabaseUnavailable,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::TokenExpired(_) => ErrorCode::TokenExpired,
+            AppError::TokenInvalid(_) => ErrorCode::TokenInvalid,
+            AppError::MethodNotAllowed(_) => ErrorCode::MethodNotAllowed,
+            AppError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            AppError::UriTooLong(_) => ErrorCode::UriTooLong,
+            AppError::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+            AppError::ServiceUnavailable { .. } => ErrorCode::ServiceUnavailable,
+            AppError::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::PreconditionFailed(_) => ErrorCode::PreconditionFailed,
+            AppError::InternalServerError { .. } => ErrorCode::InternalServerError,
+            AppError::ValidationError(_) => ErrorCode::ValidationError,
+            AppError::ConfigError(_) => ErrorCode::ConfigError,
+            AppError::ExternalServiceError { .. } => ErrorCode::ExternalServiceError,
+            AppError::WithDetails { error, .. } => error.error_code(),
         }
     }
 
     fn status_code(&self) -> StatusCode {
         match self {
             AppError::DatabaseError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::DatabaseUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::TokenExpired(_) => StatusCode::UNAUTHORIZED,
+            AppError::TokenInvalid(_) => StatusCode::UNAUTHORIZED,
+            AppError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UriTooLong(_) => StatusCode::URI_TOO_LONG,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
             AppError::InternalServerError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::ConfigError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::ExternalServiceError { .. } => StatusCode::BAD_GATEWAY,
+            AppError::WithDetails { error, .. } => error.status_code(),
         }
     }
 
     fn user_message(&self) -> String {
         match self {
             AppError::DatabaseError { .. } => "A database error occurred".to_string(),
+            AppError::DatabaseUnavailable { .. } => {
+                "The database is temporarily unavailable, please retry".to_string()
+            }
             AppError::NotFound(msg) => msg.clone(),
             AppError::BadRequest(msg) => msg.clone(),
             AppError::Unauthorized(msg) => msg.clone(),
+            AppError::Forbidden(msg) => msg.clone(),
+            AppError::TokenExpired(msg) => msg.clone(),
+            AppError::TokenInvalid(msg) => msg.clone(),
+            AppError::MethodNotAllowed(msg) => msg.clone(),
+            AppError::PayloadTooLarge(msg) => msg.clone(),
+            AppError::UriTooLong(msg) => msg.clone(),
+            AppError::TooManyRequests { message, .. } => message.clone(),
+            AppError::ServiceUnavailable { message, .. } => message.clone(),
+            AppError::UnsupportedMediaType(msg) => msg.clone(),
+            AppError::Conflict(msg) => msg.clone(),
+            AppError::PreconditionFailed(msg) => msg.clone(),
             AppError::InternalServerError { .. } => "An internal server error occurred".to_string(),
             AppError::ValidationError(msg) => msg.clone(),
             AppError::ConfigError(_) => "A configuration error occurred".to_string(),
             AppError::ExternalServiceError { service, .. } => {
                 format!("External service '{}' is unavailable", service)
             }
+            AppError::WithDetails { error, .. } => error.user_message(),
+        }
+    }
+
+    /// `WWW-Authenticate` challenge for 401 responses (RFC 6750)
+    fn www_authenticate(&self) -> Option<&'static str> {
+        match self {
+            AppError::Unauthorized(_) => Some("Bearer"),
+            AppError::TokenExpired(_) => Some(
+                r#"Bearer error="invalid_token", error_description="The access token expired""#,
+            ),
+            AppError::TokenInvalid(_) => Some(
+                r#"Bearer error="invalid_token", error_description="The access token is malformed or has an invalid signature""#,
+            ),
+            AppError::WithDetails { error, .. } => error.www_authenticate(),
+            _ => None,
+        }
+    }
+
+    /// `Retry-After` seconds for errors a client can simply retry
+    fn retry_after(&self) -> Option<u64> {
+        match self {
+            AppError::DatabaseUnavailable { .. } => Some(DATABASE_RETRY_AFTER_SECS),
+            AppError::TooManyRequests { retry_after_secs, .. }
+            | AppError::ServiceUnavailable { retry_after_secs, .. } => Some(*retry_after_secs),
+            AppError::WithDetails { error, .. } => error.retry_after(),
+            _ => None,
         }
     }
 
     /// Log the error with full context chain
+    ///
+    /// Server errors log at `error`, client errors at `debug`. Any `source`
+    /// chain is always walked, so release logs keep the underlying cause.
     fn log_with_context(&self, error_id: &str) {
         let error_code = self.error_code();
+        let server_error = self.status_code().is_server_error();
 
         // Log the main error
         match self {
-            AppError::DatabaseError { message, source } |
-            AppError::InternalServerError { message, source } => {
+            AppError::DatabaseError { message, .. } |
+            AppError::DatabaseUnavailable { message, .. } |
+            AppError::InternalServerError { message, .. } => {
                 tracing::error!(
                     error_id = %error_id,
                     error_code = %error_code,
                     message = %message,
                     "Error occurred"
                 );
-
-                // Log the error chain
-                if let Some(src) = source {
-                    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(src.as_ref());
-                    let mut depth = 1;
-                    while let Some(err) = current {
-                        tracing::error!(
-                            error_id = %error_id,
-                            depth = depth,
-                            "Caused by: {}",
-                            err
-                        );
-                        current = err.source();
-                        depth += 1;
-                    }
-                }
             }
-            AppError::ExternalServiceError { service, source } => {
+            AppError::ExternalServiceError { service, .. } => {
                 tracing::error!(
                     error_id = %error_id,
                     error_code = %error_code,
                     service = %service,
                     "External service error"
                 );
-
-                if let Some(src) = source {
-                    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(src.as_ref());
-                    let mut depth = 1;
-                    while let Some(err) = current {
-                        tracing::error!(
-                            error_id = %error_id,
-                            depth = depth,
-                            "Caused by: {}",
-                            err
-                        );
-                        current = err.source();
-                        depth += 1;
-                    }
-                }
+            }
+            // Expected under load, and the shedding site already warned
+            AppError::ServiceUnavailable { .. } => {
+                tracing::debug!(
+                    error_id = %error_id,
+                    error_code = %error_code,
+                    "Error: {}",
+                    self
+                );
+            }
+            _ if server_error => {
+                tracing::error!(
+                    error_id = %error_id,
+                    error_code = %error_code,
+                    "Error: {}",
+                    self
+                );
             }
             _ => {
                 tracing::debug!(
@@ -344,14 +585,39 @@ impl AppError {
                 );
             }
         }
+
+        // Log the error chain; a client error that carries a cause is still
+        // worth a warning
+        let mut current = std::error::Error::source(self);
+        let mut depth = 1;
+        while let Some(err) = current {
+            if server_error {
+                tracing::error!(error_id = %error_id, depth = depth, "Caused by: {}", err);
+            } else {
+                tracing::warn!(error_id = %error_id, depth = depth, "Caused by: {}", err);
+            }
+            current = err.source();
+            depth += 1;
+        }
     }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        match self {
+            AppError::WithDetails { error, details } => error.response_with_details(Some(details)),
+            error => error.response_with_details(None),
+        }
+    }
+}
+
+impl AppError {
+    fn response_with_details(self, details: Option<serde_json::Value>) -> Response {
         let error_id = Uuid::new_v4().to_string();
-        let error_code = self.error_code().to_string();
+        let error_code = self.error_code();
         let status = self.status_code();
+        let www_authenticate = self.www_authenticate();
+        let retry_after = self.retry_after();
 
         // Log with full error context chain
         self.log_with_context(&error_id);
@@ -363,6 +629,7 @@ impl IntoResponse for AppError {
             // Collect error chain
             let source: Option<&(dyn std::error::Error + 'static)> = match &self {
                 AppError::DatabaseError { source, .. }
+                | AppError::DatabaseUnavailable { source, .. }
                 | AppError::InternalServerError { source, .. }
                 | AppError::ExternalServiceError { source, .. } => {
                     source.as_ref().map(|s| s.as_ref() as &(dyn std::error::Error + 'static))
@@ -401,18 +668,38 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(ErrorResponse {
+        let body = ErrorResponse {
             error_id,
             error_code,
             error: self.user_message(),
-            details: None,
+            trace_id: crate::tracing_config::current_trace_id(),
+            details,
             #[cfg(debug_assertions)]
             debug_info,
             #[cfg(debug_assertions)]
             operation,
-        });
+        };
 
-        (status, body).into_response()
+        let pretty = pretty_errors()
+            .then(|| serde_json::to_string_pretty(&body).ok())
+            .flatten();
+        let mut response = match pretty {
+            Some(pretty) => {
+                (status, [(header::CONTENT_TYPE, "application/json")], pretty).into_response()
+            }
+            None => (status, Json(body)).into_response(),
+        };
+        if let Some(challenge) = www_authenticate {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static(challenge));
+        }
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
     }
 }
 
@@ -421,16 +708,24 @@ impl From<diesel::result::Error> for AppError {
     fn from(err: diesel::result::Error) -> Self {
         match err {
             diesel::result::Error::NotFound => AppError::NotFound("Resource not found".to_string()),
-            _ => AppError::DatabaseError {
-                message: "Database operation failed".to_string(),
-                source: Some(Box::new(err)),
-            },
+            _ => AppError::database("Database operation failed", err),
         }
     }
 }
 
 impl From<validator::ValidationErrors> for AppError {
     fn from(err: validator::ValidationErrors) -> Self {
+        let mut fields: Vec<&str> = err.errors().keys().copied().collect();
+        fields.sort_unstable();
+        for field in &fields {
+            crate::metrics::record_validation_failure(field);
+        }
+        tracing::debug!(
+            fields = %fields.join(","),
+            count = fields.len(),
+            "Request failed validation"
+        );
+
         AppError::ValidationError(err.to_string())
     }
 }
@@ -452,6 +747,169 @@ impl From<reqwest::Error> for AppError {
 
 impl From<jsonwebtoken::errors::Error> for AppError {
     fn from(err: jsonwebtoken::errors::Error) -> Self {
-        AppError::Unauthorized(format!("JWT error: {}", err))
+        match err.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                AppError::TokenExpired("Token has expired".to_string())
+            }
+            _ => AppError::TokenInvalid(format!("Invalid token: {}", err)),
+        }
+    }
+}
+
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pretty_errors_are_indented_and_keep_error_chain() {
+        set_pretty_errors(true);
+        let error = AppError::InternalServerError {
+            message: "Failed to load profile".to_string(),
+            source: Some(Box::new(std::io::Error::other("disk on fire"))),
+        };
+        let response = error.into_response();
+        set_pretty_errors(false);
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains('\n'));
+        assert!(text.contains("\"error_chain\""));
+        assert!(text.contains("disk on fire"));
+    }
+
+    #[tokio::test]
+    async fn test_lost_connection_is_retryable_503() {
+        let lost = diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ClosedConnection,
+            Box::new("connection closed".to_string()),
+        );
+
+        let error = AppError::database("Failed to query user", lost);
+        assert!(matches!(error, AppError::DatabaseUnavailable { .. }));
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+
+    #[tokio::test]
+    async fn test_trace_id_is_included_only_inside_a_trace() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, TracerProvider as _,
+        };
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        async fn body_of(response: Response) -> serde_json::Value {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice(&body).unwrap()
+        }
+
+        let untraced = body_of(AppError::NotFound("User not found".to_string()).into_response()).await;
+        assert!(untraced.get("trace_id").is_none(), "{}", untraced);
+
+        // The tracer only holds a weak reference, so the provider must outlive it
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // As if the request arrived with a traceparent header
+        let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let parent = SpanContext::new(
+            TraceId::from_hex(trace_id).unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let span = tracing::info_span!("request");
+        span.set_parent(opentelemetry::Context::new().with_remote_span_context(parent));
+
+        let response = span.in_scope(|| AppError::NotFound("User not found".to_string()).into_response());
+        let traced = body_of(response).await;
+        assert_eq!(traced["trace_id"], trace_id);
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("query planner gave up")]
+    struct PlannerError(#[source] std::io::Error);
+
+    #[test]
+    fn test_log_includes_every_level_of_source_chain() {
+        use crate::test_support::CapturedLogs;
+
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+
+        let error = AppError::database(
+            "Failed to list users",
+            PlannerError(std::io::Error::other("disk on fire")),
+        );
+        tracing::subscriber::with_default(subscriber, || error.log_with_context("err-123"));
+
+        let logs = logs.contents();
+        let lines: Vec<&str> = logs.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", logs);
+        assert!(lines.iter().all(|line| line.contains("ERROR") && line.contains("err-123")));
+        assert!(lines[0].contains("Failed to list users"));
+        assert!(lines[1].contains("Caused by: query planner gave up") && lines[1].contains("depth=1"));
+        assert!(lines[2].contains("Caused by: disk on fire") && lines[2].contains("depth=2"));
+    }
+}
+
+/// Behaviour shared by debug and release builds (`cargo test --release`)
+#[cfg(test)]
+mod details_tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn body_of(error: AppError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_attached_details_are_serialized_in_every_build() {
+        let error = AppError::BadRequest("Unsupported currency".to_string())
+            .with_details(json!({ "field": "currency", "allowed": ["EUR", "USD"] }));
+
+        let (status, body) = body_of(error).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error_code"], "BAD_REQUEST");
+        assert_eq!(body["error"], "Unsupported currency");
+        assert_eq!(body["details"]["field"], "currency");
+        assert_eq!(body["details"]["allowed"], json!(["EUR", "USD"]));
+        #[cfg(not(debug_assertions))]
+        assert!(body.get("debug_info").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_details_keep_the_wrapped_error_behaviour() {
+        let error = AppError::Unauthorized("Missing token".to_string())
+            .with_details(json!("first"))
+            .with_details(json!("second"));
+        assert_eq!(error.error_code(), ErrorCode::Unauthorized);
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["details"], "second");
+    }
+
+    #[tokio::test]
+    async fn test_errors_without_details_omit_the_field() {
+        let (_, body) = body_of(AppError::NotFound("User not found".to_string())).await;
+        assert!(body.get("details").is_none());
     }
 }
@@ -0,0 +1,85 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    db,
+    error::AppError,
+    handlers::health::process_memory_mb,
+    metrics,
+    middleware::auth::AdminUser,
+    models::{AdminStatsResponse, PoolStatsResponse, RequestStatsResponse, SecretReloadResponse},
+    services::rotation,
+    AppState,
+};
+
+/// Runtime statistics for admin dashboards
+/// GET /api/v1/admin/stats
+/// Headers: { "Authorization": "Bearer <admin token>" }
+///
+/// JSON counterpart of the Prometheus metrics, safe to expose in production
+/// because it requires the `admin` role.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/stats",
+    responses(
+        (status = 200, description = "Runtime statistics", body = AdminStatsResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "User is not an admin")
+    ),
+    tag = "admin"
+)]
+#[tracing::instrument(name = "admin_stats", skip(state, admin), fields(user_id = %admin.0.user_id))]
+pub async fn stats(
+    State(state): State<AppState>,
+    admin: AdminUser,
+) -> Json<AdminStatsResponse> {
+    let pool = db::pool_stats(&state.db_pool);
+    let requests = metrics::request_counts();
+
+    Json(AdminStatsResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_seconds: metrics::uptime_seconds(),
+        memory_usage_mb: process_memory_mb(),
+        pool: PoolStatsResponse {
+            size: pool.size,
+            available: pool.available,
+            max_size: pool.max_size,
+            active_connections: pool.size.saturating_sub(pool.available),
+        },
+        requests: RequestStatsResponse {
+            total: requests.total,
+            in_flight: requests.in_flight,
+        },
+    })
+}
+
+/// Re-fetch rotated secrets without a restart
+/// POST /api/v1/admin/secrets/reload
+/// Headers: { "Authorization": "Bearer <admin token>" }
+///
+/// Clears the secret cache and applies the current JWT_SECRET,
+/// JWT_SECRET_PREVIOUS and RATE_LIMIT_BYPASS_KEYS from the secret provider.
+/// Tokens signed with a secret moved to JWT_SECRET_PREVIOUS keep working.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/secrets/reload",
+    responses(
+        (status = 200, description = "Secrets reloaded", body = SecretReloadResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "User is not an admin"),
+        (status = 500, description = "A secret is missing or rejected; the old keys stay in place")
+    ),
+    tag = "admin"
+)]
+#[tracing::instrument(name = "admin_reload_secrets", skip(state, admin), fields(user_id = %admin.0.user_id))]
+pub async fn reload_secrets(
+    State(state): State<AppState>,
+    admin: AdminUser,
+) -> Result<Json<SecretReloadResponse>, AppError> {
+    let reloaded = rotation::reload_secrets(&state.services, &state.config).await?;
+
+    Ok(Json(SecretReloadResponse {
+        jwt_secret_rotated: reloaded.jwt_secret_rotated,
+        jwt_previous_secrets: reloaded.jwt_previous_secrets,
+        rate_limit_bypass_keys: reloaded.rate_limit_bypass_keys,
+    }))
+}
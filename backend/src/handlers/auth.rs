@@ -1,49 +1,126 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use validator::Validate;
 
 use crate::{
+    config::TokenTransport,
     error::{AppError, JsonResult},
-    middleware::auth::AuthUser,
+    middleware::{
+        auth::{auth_cookie, AuthUser, ClientFingerprint},
+        cache,
+        context::RequestContext,
+        json::LimitedJson,
+        nonce::Nonce,
+    },
     models::{
-        dto::{AuthResponseDto, LoginRequestDto, RegisterRequestDto, UserResponseDto},
-        user::{LoginRequest, RegisterRequest},
+        dto::{
+            AuthResponseDto, FieldsQueryDto, LoginRequestDto, NonceResponseDto, RegisterRequestDto,
+            UpdateUserRequestDto, UserResponseDto,
+        },
+        user::{LoginRequest, RegisterRequest, UserChangeset},
     },
+    types::TenantId,
     AppState,
 };
 
+/// Hand a freshly issued token to the client as AUTH_TOKEN_TRANSPORT asks
+///
+/// In cookie mode the token travels only in an HttpOnly cookie and is left
+/// out of the body, where page scripts could read it.
+pub(crate) fn deliver_token(
+    state: &AppState,
+    mut dto: AuthResponseDto,
+) -> Result<(HeaderMap, Json<AuthResponseDto>), AppError> {
+    let mut headers = HeaderMap::new();
+
+    if state.config.jwt.transport == TokenTransport::Cookie {
+        let max_age = u64::try_from(state.config.jwt.expiration_hours * 3600).unwrap_or(0);
+        let cookie = auth_cookie(&dto.token, max_age, state.config.is_production());
+        let cookie = HeaderValue::try_from(cookie)
+            .map_err(|e| AppError::internal("Invalid auth cookie", e))?;
+        headers.insert(header::SET_COOKIE, cookie);
+        dto.token.clear();
+    }
+
+    Ok((headers, Json(dto)))
+}
+
 /// Register a new user
 ///
 /// POST /api/v1/auth/register
 /// Body: { "email": "user@example.com", "username": "username", "password": "password123" }
-#[tracing::instrument(name = "register_handler", skip(state, dto), fields(email = %dto.email, username = %dto.username))]
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    request_body = RegisterRequestDto,
+    responses(
+        (status = 201, description = "User registered", body = AuthResponseDto),
+        (status = 400, description = "User already exists"),
+        (status = 422, description = "Validation failed"),
+        (status = 429, description = "Global registration rate exceeded")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "register_handler", skip(state, ctx, fingerprint, dto), fields(email = %dto.email, username = %dto.username))]
 pub async fn register(
     State(state): State<AppState>,
-    Json(dto): Json<RegisterRequestDto>,
-) -> Result<(StatusCode, Json<AuthResponseDto>), AppError> {
+    ctx: RequestContext,
+    tenant: TenantId,
+    fingerprint: ClientFingerprint,
+    LimitedJson(dto): LimitedJson<RegisterRequestDto>,
+) -> Result<(StatusCode, HeaderMap, Json<AuthResponseDto>), AppError> {
     tracing::info!("Registration request received");
 
     // Validate request
     dto.validate()?;
     tracing::debug!("Request validation passed");
 
+    // Global cap across all clients (REGISTRATION_RATE_PER_MINUTE)
+    if let Some(throttle) = &state.services.registration_throttle {
+        throttle.try_acquire().map_err(|wait| AppError::TooManyRequests {
+            message: "Too many registrations, please retry later".to_string(),
+            retry_after_secs: wait.as_secs_f64().ceil() as u64,
+        })?;
+    }
+
     // Register user using service from AppState
     let request: RegisterRequest = dto.into();
-    let response = state.auth().register(request).await?;
+    let response = state.auth().register(&ctx, &tenant, request, fingerprint.as_deref()).await?;
     let response_dto: AuthResponseDto = response.into();
 
+    let (headers, body) = deliver_token(&state, response_dto)?;
+
     tracing::info!("User registered successfully");
-    Ok((StatusCode::CREATED, Json(response_dto)))
+    Ok((StatusCode::CREATED, headers, body))
 }
 
 /// Login with email and password
 ///
 /// POST /api/v1/auth/login
 /// Body: { "email": "user@example.com", "password": "password123" }
-#[tracing::instrument(name = "login_handler", skip(state, dto), fields(email = %dto.email))]
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    request_body = LoginRequestDto,
+    responses(
+        (status = 200, description = "Login successful", body = AuthResponseDto),
+        (status = 401, description = "Invalid email or password"),
+        (status = 422, description = "Validation failed")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "login_handler", skip(state, ctx, fingerprint, dto), fields(email = %dto.email))]
 pub async fn login(
     State(state): State<AppState>,
-    Json(dto): Json<LoginRequestDto>,
-) -> JsonResult<AuthResponseDto> {
+    ctx: RequestContext,
+    tenant: TenantId,
+    fingerprint: ClientFingerprint,
+    LimitedJson(dto): LimitedJson<LoginRequestDto>,
+) -> Result<(HeaderMap, Json<AuthResponseDto>), AppError> {
     tracing::info!("Login request received");
 
     // Validate request
@@ -52,28 +129,185 @@ pub async fn login(
 
     // Login user using service from AppState
     let request: LoginRequest = dto.into();
-    let response = state.auth().login(request).await?;
+    let response = state.auth().login(&ctx, &tenant, request, fingerprint.as_deref()).await?;
     let response_dto: AuthResponseDto = response.into();
+    let response = deliver_token(&state, response_dto)?;
 
     tracing::info!("User logged in successfully");
-    Ok(Json(response_dto))
+    Ok(response)
+}
+
+/// Log out by clearing the auth cookie
+///
+/// POST /api/v1/auth/logout
+///
+/// The cookie is HttpOnly, so only the server can remove it. Tokens are
+/// stateless: one copied out of the cookie stays valid until it expires.
+/// Needs no token, so a client whose token has already expired can still
+/// drop the cookie.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    responses(
+        (status = 204, description = "Auth cookie cleared")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "logout_handler", skip(state))]
+pub async fn logout(State(state): State<AppState>) -> Result<(StatusCode, HeaderMap), AppError> {
+    let cookie = auth_cookie("", 0, state.config.is_production());
+    let cookie = HeaderValue::try_from(cookie)
+        .map_err(|e| AppError::internal("Invalid auth cookie", e))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, cookie);
+    Ok((StatusCode::NO_CONTENT, headers))
 }
 
 /// Get current user information
 ///
-/// GET /api/v1/auth/me
+/// GET /api/v1/auth/me[?fields=id,email]
 /// Headers: { "Authorization": "Bearer <token>" }
-#[tracing::instrument(name = "get_current_user", skip(state, auth_user), fields(user_id = %auth_user.user_id, email = %auth_user.email))]
+///
+/// With `fields`, only the named fields of the user are returned.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/me",
+    params(FieldsQueryDto),
+    responses(
+        (status = 200, description = "Current user", body = UserResponseDto),
+        (status = 400, description = "Unknown field in `fields`"),
+        (status = 401, description = "Missing or invalid token")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "get_current_user", skip(state, auth_user, query), fields(user_id = %auth_user.user_id, email = %auth_user.email))]
 pub async fn me(
     State(state): State<AppState>,
     auth_user: AuthUser,
-) -> JsonResult<UserResponseDto> {
+    Query(query): Query<FieldsQueryDto>,
+) -> Result<Response, AppError> {
     tracing::info!("Fetching current user information");
+    let selection = super::user_fields(query.fields.as_deref())?;
 
     // Get user by ID from token using service from AppState
-    let user = state.auth().get_user_by_id(&auth_user.user_id).await?;
+    let user = state
+        .auth()
+        .get_user_by_id(&auth_user.tenant_id, &auth_user.user_id)
+        .await?;
     let user_dto: UserResponseDto = user.into();
 
     tracing::debug!("User information retrieved successfully");
-    Ok(Json(user_dto))
+    let Some(selection) = selection else {
+        return Ok(Json(user_dto).into_response());
+    };
+    let mut body = serde_json::to_value(&user_dto).expect("user DTOs always serialize");
+    selection.retain(&mut body);
+    Ok(Json(body).into_response())
+}
+
+/// Partially update current user's profile
+///
+/// PATCH /api/v1/auth/me
+/// Headers: { "Authorization": "Bearer <token>", "If-Unmodified-Since": "<HTTP date>" (optional) }
+/// Body: { "username": "newname" } - omitted fields are left unchanged
+#[utoipa::path(
+    patch,
+    path = "/api/v1/auth/me",
+    request_body = UpdateUserRequestDto,
+    responses(
+        (status = 200, description = "Updated user", body = UserResponseDto),
+        (status = 400, description = "Email or username already taken"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 412, description = "Profile modified after If-Unmodified-Since"),
+        (status = 422, description = "Validation failed")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "update_current_user", skip(state, ctx, auth_user, headers, dto), fields(user_id = %auth_user.user_id))]
+pub async fn update_me(
+    State(state): State<AppState>,
+    ctx: RequestContext,
+    auth_user: AuthUser,
+    headers: HeaderMap,
+    LimitedJson(dto): LimitedJson<UpdateUserRequestDto>,
+) -> JsonResult<UserResponseDto> {
+    tracing::info!("Profile update request received");
+
+    // Validate each provided field
+    dto.validate()?;
+
+    let changes: UserChangeset = dto.into();
+    let user = state
+        .auth()
+        .update_profile(
+            &ctx,
+            &auth_user.tenant_id,
+            &auth_user.user_id,
+            changes,
+            cache::if_unmodified_since(&headers),
+        )
+        .await?;
+
+    Ok(Json(user.into()))
+}
+
+/// Delete the current user's account
+///
+/// DELETE /api/v1/auth/me
+/// Headers: { "Authorization": "Bearer <token>", "X-Nonce": "<nonce from /auth/nonce>" }
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/me",
+    params(("X-Nonce" = String, Header, description = "Nonce from GET /api/v1/auth/nonce")),
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 400, description = "Missing, invalid or expired nonce"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 409, description = "Nonce has already been used")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "delete_current_user", skip(state, ctx, auth_user, nonce), fields(user_id = %auth_user.user_id))]
+pub async fn delete_me(
+    State(state): State<AppState>,
+    ctx: RequestContext,
+    auth_user: AuthUser,
+    Nonce(nonce): Nonce,
+) -> Result<StatusCode, AppError> {
+    state.services.nonces.consume(&nonce, &auth_user.owner_key())?;
+
+    state
+        .auth()
+        .delete_account(&ctx, &auth_user.tenant_id, &auth_user.user_id)
+        .await?;
+
+    tracing::info!("Account deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Issue a single-use nonce for a replay-protected request
+///
+/// GET /api/v1/auth/nonce
+/// Headers: { "Authorization": "Bearer <token>" }
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/nonce",
+    responses(
+        (status = 200, description = "Fresh nonce", body = NonceResponseDto),
+        (status = 401, description = "Missing or invalid token")
+    ),
+    tag = "auth"
+)]
+#[tracing::instrument(name = "issue_nonce", skip(state, auth_user), fields(user_id = %auth_user.user_id))]
+pub async fn nonce(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> JsonResult<NonceResponseDto> {
+    let nonces = &state.services.nonces;
+
+    Ok(Json(NonceResponseDto {
+        nonce: nonces.issue(&auth_user.owner_key()),
+        expires_in: nonces.ttl().as_secs(),
+    }))
 }
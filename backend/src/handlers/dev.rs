@@ -3,10 +3,25 @@
 //! These endpoints are only available in debug builds and should NEVER
 //! be compiled into production releases.
 
-use axum::{extract::State, Json, response::Html};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::header,
+    response::Html,
+    Json,
+};
+use chrono::DateTime;
 use serde_json::{json, Value};
 
-use crate::{error::AppError, AppState};
+use crate::{
+    config::TokenTransport,
+    error::AppError,
+    middleware::{
+        auth::{extract_token, read_cookie, AuthUser, AUTH_COOKIE},
+        rate_limit::client_ip,
+    },
+    repositories::UserRepositoryTrait,
+    AppState,
+};
 
 /// Development dashboard with links to all dev tools
 ///
@@ -151,6 +166,18 @@ pub async fn dashboard() -> Html<String> {
                         <h3>App State</h3>
                         <p>View current application state, database pool stats, and configuration</p>
                     </a>
+                    <a href="/dev/config" class="link-card">
+                        <h3>Effective Config</h3>
+                        <p>Every setting with its value and whether it came from env or a default</p>
+                    </a>
+                    <a href="/dev/whoami" class="link-card">
+                        <h3>Who Am I</h3>
+                        <p>How this request authenticated, the resolved user, token expiry and client IP</p>
+                    </a>
+                    <a href="/dev/captures" class="link-card">
+                        <h3>Captured Requests</h3>
+                        <p>Full request and response of calls sent with X-Debug-Capture: 1 (secrets redacted)</p>
+                    </a>
                     <a href="/dev/health" class="link-card">
                         <h3>Dev Health Check</h3>
                         <p>Quick health status for development environment</p>
@@ -177,6 +204,10 @@ pub async fn dashboard() -> Html<String> {
                         <h3>Error Simulator</h3>
                         <p>Trigger various error types for testing error handling</p>
                     </a>
+                    <a href="/dev/slow-query?ms=200" class="link-card">
+                        <h3>Slow Query</h3>
+                        <p>Run pg_sleep to exercise slow-query logging (QUERY_LOG=1)</p>
+                    </a>
                 </div>
             </div>
 
@@ -259,6 +290,122 @@ pub async fn debug_state(State(state): State<AppState>) -> Result<Json<Value>, A
     })))
 }
 
+/// Effective configuration and where each value came from
+///
+/// GET /dev/config
+///
+/// Lists only DEV_CONFIG_KEYS when set; sensitive settings are masked
+/// regardless. `source` is `env` when the variable was set at startup and
+/// `default` otherwise.
+pub async fn config(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "environment": state.config.server.environment,
+        "settings": crate::config::sources::effective_config(&state.config),
+    }))
+}
+
+/// How the request authenticated, for troubleshooting auth
+///
+/// GET /dev/whoami
+///
+/// Runs the same checks as the `AuthUser` extractor but reports the outcome
+/// instead of rejecting: the credential presented (`bearer`, `cookie`,
+/// `api-key` or `none`), the resolved user and role, token times, and which
+/// IP the server attributes the request to given TRUST_PROXY.
+pub async fn whoami(State(state): State<AppState>, req: Request) -> Json<Value> {
+    let trust_proxy = state.config.server.trust_proxy;
+    let ip = client_ip(&req, trust_proxy);
+    let (mut parts, _) = req.into_parts();
+    let headers = &parts.headers;
+
+    let has_proxy_headers =
+        headers.contains_key("x-forwarded-for") || headers.contains_key("x-real-ip");
+    let ip_source = match () {
+        _ if trust_proxy && headers.contains_key("x-forwarded-for") => Some("x-forwarded-for"),
+        _ if trust_proxy && headers.contains_key("x-real-ip") => Some("x-real-ip"),
+        _ if ip.is_some() => Some("connection"),
+        _ => None,
+    };
+
+    let method = if headers.contains_key(header::AUTHORIZATION) {
+        "bearer"
+    } else if state.config.jwt.transport == TokenTransport::Cookie
+        && read_cookie(headers, AUTH_COOKIE).is_some_and(|token| !token.is_empty())
+    {
+        "cookie"
+    } else if headers.contains_key("x-api-key") {
+        // API keys only exempt callers from rate limits; they carry no user
+        "api-key"
+    } else {
+        "none"
+    };
+
+    let claims = extract_token(headers, &state)
+        .ok()
+        .and_then(|token| state.jwt().verify_token(token).ok());
+    let token = claims.map(|claims| {
+        let at = |secs: i64| DateTime::from_timestamp(secs, 0).map(|t| t.to_rfc3339());
+        json!({
+            "issued_at": at(claims.iat),
+            "not_before": claims.nbf.and_then(at),
+            "expires_at": at(claims.exp),
+            "expires_in_seconds": claims.exp - chrono::Utc::now().timestamp(),
+            "fingerprint_bound": claims.fpt.is_some(),
+        })
+    });
+
+    let (user, error) = match AuthUser::from_request_parts(&mut parts, &state).await {
+        Ok(auth) => {
+            let role = match uuid::Uuid::parse_str(&auth.user_id) {
+                Ok(id) => state
+                    .user_repo()
+                    .find_by_id(&auth.tenant_id, id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|user| user.role),
+                Err(_) => None,
+            };
+            let user = json!({
+                "id": auth.user_id,
+                "email": auth.email,
+                "username": auth.username,
+                "tenant": auth.tenant_id,
+                "role": role,
+            });
+            (Some(user), None)
+        }
+        Err(e) if method == "bearer" || method == "cookie" => (None, Some(e.to_string())),
+        Err(_) => (None, None),
+    };
+
+    Json(json!({
+        "authenticated": user.is_some(),
+        "method": method,
+        "error": error,
+        "user": user,
+        "token": token,
+        "client": {
+            "ip": ip,
+            "ip_source": ip_source,
+            "trust_proxy": trust_proxy,
+            "proxy_headers_ignored": has_proxy_headers && !trust_proxy,
+        },
+    }))
+}
+
+/// Requests recorded because they carried `X-Debug-Capture: 1`
+///
+/// GET /dev/captures
+///
+/// Newest first, at most the last 20, with credentials redacted (see
+/// [`crate::middleware::capture`]).
+pub async fn captures(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "captures": state.services.captures.list(),
+    }))
+}
+
 /// Health check specifically for development
 ///
 /// GET /dev/health
@@ -314,18 +461,22 @@ pub async fn simulate_error(
 ///
 /// POST /dev/token
 pub async fn generate_test_token(State(state): State<AppState>) -> Result<Json<Value>, AppError> {
-    use crate::services::jwt::JwtService;
+    use crate::services::jwt::{JwtService, TokenOptions};
 
     let jwt_service = JwtService::new(
         state.config.jwt.secret.clone(),
         state.config.jwt.expiration_hours,
     );
 
-    let token = jwt_service.generate_token(
-        uuid::Uuid::new_v4(),
-        "dev@example.com".to_string(),
-        "devuser".to_string(),
-    )?;
+    let token = jwt_service
+        .issue_token(
+            uuid::Uuid::new_v4(),
+            "dev@example.com".to_string(),
+            "devuser".to_string(),
+            crate::types::TenantId::default(),
+            TokenOptions::default(),
+        )?
+        .token;
 
     Ok(Json(json!({
         "token": token,
@@ -356,7 +507,7 @@ pub async fn db_info(State(state): State<AppState>) -> Result<Json<Value>, AppEr
 
     Ok(Json(json!({
         "database": {
-            "url_masked": mask_db_url(&state.config.database.url),
+            "url_masked": crate::config::summary::mask_database_url(&state.config.database.url),
             "pool_size": state.config.database.pool_size,
         },
         "tables": {
@@ -372,14 +523,187 @@ pub async fn db_info(State(state): State<AppState>) -> Result<Json<Value>, AppEr
     })))
 }
 
-/// Mask sensitive parts of database URL
-fn mask_db_url(url: &str) -> String {
-    if let Some(at_pos) = url.find('@') {
-        if let Some(proto_end) = url.find("://") {
-            let proto = &url[..proto_end + 3];
-            let rest = &url[at_pos..];
-            return format!("{}***{}", proto, rest);
-        }
+/// Upper bound for /dev/seed `count`
+const MAX_SEED_COUNT: usize = 1_000;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SeedParams {
+    /// Delete every user before seeding
+    #[serde(default)]
+    pub clear_existing: bool,
+    /// Generated users to add on top of the demo accounts
+    #[serde(default)]
+    pub count: usize,
+}
+
+/// Seed the request's tenant with demo accounts and generated users
+///
+/// POST /dev/seed
+/// Body: { "clear_existing": false, "count": 10 }
+///
+/// Same data as `cargo run --bin seed`, so frontend developers can reset
+/// test data without leaving the browser.
+pub async fn seed(
+    State(state): State<AppState>,
+    tenant: crate::types::TenantId,
+    Json(params): Json<SeedParams>,
+) -> Result<Json<Value>, AppError> {
+    if params.count > MAX_SEED_COUNT {
+        return Err(AppError::BadRequest(format!(
+            "count must be at most {}",
+            MAX_SEED_COUNT
+        )));
+    }
+
+    let created = crate::db::seed::seed_database(
+        &state.db_pool,
+        crate::db::seed::SeedConfig {
+            tenant,
+            clear_existing: params.clear_existing,
+            count: params.count,
+        },
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "created": created,
+        "cleared": params.clear_existing,
+    })))
+}
+
+#[cfg(feature = "dev-tools")]
+/// Upper bound for /dev/generate `users`
+const MAX_GENERATE_USERS: usize = 10_000;
+
+#[cfg(feature = "dev-tools")]
+/// Rows per INSERT, well under Postgres' 65535 bind-parameter limit
+const GENERATE_BATCH_SIZE: usize = 1_000;
+
+#[cfg(feature = "dev-tools")]
+/// Password of every generated user
+const GENERATED_PASSWORD: &str = "Password123!";
+
+#[cfg(feature = "dev-tools")]
+#[derive(Debug, serde::Deserialize)]
+pub struct GenerateParams {
+    pub users: usize,
+}
+
+#[cfg(feature = "dev-tools")]
+/// Bulk-insert fake users into the request's tenant for load testing
+///
+/// POST /dev/generate?users=1000
+///
+/// Names come from the `fake` crate; every user shares one password
+/// ("Password123!"), hashed once with the configured algorithm. The response
+/// reports how long hashing and inserting took.
+pub async fn generate(
+    State(state): State<AppState>,
+    tenant: crate::types::TenantId,
+    axum::extract::Query(params): axum::extract::Query<GenerateParams>,
+) -> Result<Json<Value>, AppError> {
+    use crate::models::user::NewUser;
+    use fake::{
+        faker::name::en::{FirstName, LastName},
+        Fake,
+    };
+
+    if params.users > MAX_GENERATE_USERS {
+        return Err(AppError::BadRequest(format!(
+            "users must be at most {}",
+            MAX_GENERATE_USERS
+        )));
+    }
+
+    let start = std::time::Instant::now();
+    let policy = crate::services::password::PasswordPolicy::from_config(&state.config.password);
+    let password_hash = tokio::task::spawn_blocking(move || policy.hash(GENERATED_PASSWORD))
+        .await
+        .map_err(|e| AppError::internal("Password hashing task failed", e))??;
+    let hash_ms = start.elapsed().as_millis();
+
+    let new_users: Vec<NewUser> = (0..params.users)
+        .map(|_| {
+            let first: String = FirstName().fake();
+            let last: String = LastName().fake();
+            // The suffix keeps emails and usernames unique across runs
+            let suffix = &uuid::Uuid::new_v4().simple().to_string()[..8];
+            NewUser {
+                email: format!("{}.{}.{}@example.com", first, last, suffix).to_lowercase(),
+                username: format!("{}{}{}", first, last, suffix).to_lowercase(),
+                password_hash: password_hash.clone(),
+                provider: None,
+                provider_id: None,
+            }
+        })
+        .collect();
+
+    let insert_start = std::time::Instant::now();
+    let mut created = 0;
+    for batch in new_users.chunks(GENERATE_BATCH_SIZE) {
+        created += state.services.user_repo.create_many(&tenant, batch).await?;
     }
-    "***".to_string()
+    let insert_ms = insert_start.elapsed().as_millis();
+
+    tracing::info!(tenant = %tenant, created, "Generated fake users");
+    Ok(Json(json!({
+        "requested": params.users,
+        "created": created,
+        "password": GENERATED_PASSWORD,
+        "timing_ms": {
+            "hash": hash_ms,
+            "insert": insert_ms,
+            "total": start.elapsed().as_millis(),
+        },
+    })))
+}
+
+/// Upper bound for /dev/slow-query so a typo can't pin a connection for minutes
+const MAX_SLOW_QUERY_MS: u64 = 10_000;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SlowQueryParams {
+    pub ms: Option<u64>,
+}
+
+/// Run a deliberately slow query
+///
+/// GET /dev/slow-query?ms=200
+///
+/// Executes `SELECT pg_sleep(ms / 1000)` through `logged_query!` so the
+/// slow-query warning (QUERY_LOG=1, >100ms) can be checked without a real slow query.
+pub async fn slow_query(
+    State(state): State<AppState>,
+    axum::extract::Query(params): axum::extract::Query<SlowQueryParams>,
+) -> Result<Json<Value>, AppError> {
+    use diesel::sql_types::Double;
+    use diesel_async::RunQueryDsl;
+
+    let requested_ms = params.ms.unwrap_or(200);
+    if requested_ms > MAX_SLOW_QUERY_MS {
+        return Err(AppError::BadRequest(format!(
+            "ms must be at most {}",
+            MAX_SLOW_QUERY_MS
+        )));
+    }
+
+    let mut conn = crate::db::get_connection(&state.db_pool).await?;
+
+    let start = std::time::Instant::now();
+    crate::logged_query!(
+        "SELECT pg_sleep($1)",
+        diesel::sql_query("SELECT pg_sleep($1)")
+            .bind::<Double, _>(requested_ms as f64 / 1000.0)
+            .execute(&mut conn)
+            .await
+    )
+    .map_err(|e| AppError::database("Slow query failed", e))?;
+    let duration = start.elapsed();
+
+    Ok(Json(json!({
+        "requested_ms": requested_ms,
+        "duration_ms": duration.as_millis() as u64,
+        "query_log_enabled": std::env::var("QUERY_LOG").is_ok(),
+        "hint": "Set QUERY_LOG=1 to see the slow query warning for queries over 100ms",
+    })))
 }
@@ -0,0 +1,79 @@
+//! Fallback responses for requests that don't reach a handler
+//!
+//! Axum answers unmatched paths and unsupported methods with empty bodies,
+//! and oversized bodies with plain text. These helpers rewrite those cases
+//! into the standard `ErrorResponse` JSON shape so clients can parse every
+//! error the same way.
+
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::AppError;
+
+/// Fallback handler for unmatched routes
+///
+/// Registered with `Router::fallback`, so it also covers nested routers.
+pub async fn not_found() -> AppError {
+    AppError::NotFound("Route not found".to_string())
+}
+
+/// Middleware converting axum's empty 405 into a JSON error
+///
+/// Axum's method router already computes the `Allow` header for paths that
+/// exist but don't support the request method; this keeps that header and
+/// replaces the empty body with the standard error shape.
+pub async fn method_not_allowed(req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let response = next.run(req).await;
+
+    // Only rewrite axum's own rejection, never a handler-produced 405
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED
+        || response.headers().contains_key(header::CONTENT_TYPE)
+    {
+        return response;
+    }
+
+    let allow = response.headers().get(header::ALLOW).cloned();
+    let mut json_response =
+        AppError::MethodNotAllowed(format!("Method {} not allowed for this route", method))
+            .into_response();
+
+    if let Some(allow) = allow {
+        json_response.headers_mut().insert(header::ALLOW, allow);
+    }
+
+    json_response
+}
+
+/// Middleware converting axum's plain-text 413 into a JSON error
+///
+/// Extractors reject bodies over `DefaultBodyLimit` with a text response.
+/// Debug builds include the configured limit in the message.
+pub async fn payload_too_large(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE || is_json {
+        return response;
+    }
+
+    let message = if cfg!(debug_assertions) {
+        format!(
+            "Request body too large (limit: {} bytes)",
+            crate::routes::DEFAULT_BODY_LIMIT
+        )
+    } else {
+        "Request body too large".to_string()
+    };
+
+    AppError::PayloadTooLarge(message).into_response()
+}
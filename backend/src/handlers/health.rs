@@ -1,7 +1,18 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::future::Future;
+use std::time::Duration;
+
 use crate::{
     db,
-    models::{HealthChecks, HealthResponse, SubsystemHealth},
+    models::{
+        HealthChecks, HealthResponse, LivenessResponse, ReadinessResponse, SubsystemHealth,
+        VersionResponse,
+    },
     AppState,
 };
 
@@ -21,24 +32,49 @@ pub async fn health_check(
 ) -> (StatusCode, Json<HealthResponse>) {
     tracing::debug!("Starting health check");
 
-    // Check database
-    let database_health = match db::test_connection(&state.db_pool).await {
-        Ok(_) => {
-            let pool_status = state.db_pool.status();
-            SubsystemHealth {
-                status: "healthy".to_string(),
-                message: None,
-                details: Some(serde_json::json!({
-                    "available_connections": pool_status.available,
-                    "max_connections": pool_status.max_size,
-                })),
+    // Check database (bounded so a stalled database can't hang the probe)
+    let probe_timeout = Duration::from_millis(state.config.health.db_timeout_ms);
+    let database_health = probe_with_timeout("database", probe_timeout, async {
+        match db::probe_connection(&state.db_pool, &state.config.database.probe_query).await {
+            Ok(_) => {
+                let pool_status = state.db_pool.status();
+                SubsystemHealth {
+                    status: "healthy".to_string(),
+                    message: None,
+                    details: Some(serde_json::json!({
+                        "available_connections": pool_status.available,
+                        "max_connections": pool_status.max_size,
+                    })),
+                }
             }
+            Err(e) => SubsystemHealth {
+                status: "unhealthy".to_string(),
+                message: Some(format!("Database connection failed: {}", e)),
+                details: None,
+            },
         }
-        Err(e) => SubsystemHealth {
-            status: "unhealthy".to_string(),
-            message: Some(format!("Database connection failed: {}", e)),
-            details: None,
-        },
+    })
+    .await;
+
+    // Check replica lag, only when a read replica is configured
+    let replica_health = match &state.replica_pool {
+        Some(replica_pool) => {
+            let max_lag = Duration::from_millis(state.config.database.replica_max_lag_ms);
+            Some(
+                probe_with_timeout("replica", probe_timeout, async {
+                    match db::replica_lag(replica_pool).await {
+                        Ok(lag) => replica_lag_health(lag, max_lag),
+                        Err(e) => SubsystemHealth {
+                            status: "unhealthy".to_string(),
+                            message: Some(format!("Replica lag check failed: {}", e)),
+                            details: None,
+                        },
+                    }
+                })
+                .await,
+            )
+        }
+        None => None,
     };
 
     // Check memory usage
@@ -47,10 +83,17 @@ pub async fn health_check(
     let checks = HealthChecks {
         database: database_health,
         memory: memory_health,
+        replica: replica_health,
     };
 
-    // Determine overall status
-    let overall_status = if checks.database.status == "healthy" && checks.memory.status == "healthy"
+    // Determine overall status (a lagging or unreachable replica only degrades)
+    let replica_healthy = checks
+        .replica
+        .as_ref()
+        .map_or(true, |replica| replica.status == "healthy");
+    let overall_status = if checks.database.status == "healthy"
+        && checks.memory.status == "healthy"
+        && replica_healthy
     {
         "healthy"
     } else if checks.database.status == "unhealthy" {
@@ -82,6 +125,173 @@ pub async fn health_check(
     )
 }
 
+/// Liveness probe: the process is up and answering
+///
+/// Checks no dependencies, so it stays cheap enough to poll every second.
+/// Clients preferring `text/plain` in `Accept`, and probes whose User-Agent
+/// starts with one of HEALTH_PLAIN_PROBE_AGENTS, get a bare `OK`; everyone
+/// else gets JSON.
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/live",
+    responses(
+        (status = 200, description = "Process is alive", content(
+            ("application/json" = LivenessResponse),
+            ("text/plain" = String, example = json!("OK"))
+        ))
+    ),
+    tag = "health"
+)]
+pub async fn liveness(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if wants_plain_text(&headers, &state.config.health.plain_probe_agents) {
+        return ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], "OK").into_response();
+    }
+
+    Json(LivenessResponse {
+        status: "alive".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+    .into_response()
+}
+
+/// Whether a probe should get the plain-text liveness body
+///
+/// `text/plain` must be ranked above `application/json`; wildcards (as
+/// browsers send) leave the JSON default in place.
+fn wants_plain_text(headers: &HeaderMap, probe_agents: &[String]) -> bool {
+    let is_probe = headers
+        .get(header::USER_AGENT)
+        .and_then(|ua| ua.to_str().ok())
+        .is_some_and(|ua| probe_agents.iter().any(|agent| ua.starts_with(agent.as_str())));
+    if is_probe {
+        return true;
+    }
+
+    let text_q = super::accept_quality(headers, "text/plain");
+    text_q > 0.0 && text_q > super::accept_quality(headers, "application/json")
+}
+
+/// Map a measured replica lag to a subsystem status
+///
+/// Lag above `max_lag` is `degraded`: reads still work but may be stale.
+pub fn replica_lag_health(lag: Option<Duration>, max_lag: Duration) -> SubsystemHealth {
+    let Some(lag) = lag else {
+        return SubsystemHealth {
+            status: "degraded".to_string(),
+            message: Some("Replica is not replaying WAL".to_string()),
+            details: None,
+        };
+    };
+
+    let lagging = lag > max_lag;
+    SubsystemHealth {
+        status: if lagging { "degraded" } else { "healthy" }.to_string(),
+        message: lagging.then(|| {
+            format!(
+                "Replica lag {}ms exceeds {}ms",
+                lag.as_millis(),
+                max_lag.as_millis()
+            )
+        }),
+        details: Some(serde_json::json!({
+            "lag_ms": lag.as_millis() as u64,
+            "max_lag_ms": max_lag.as_millis() as u64,
+        })),
+    }
+}
+
+/// Readiness probe for load balancers
+///
+/// Reports 503 until embedded migrations have run and the pool warmup (when
+/// DB_WARMUP is on) has completed, so traffic isn't routed to an instance that
+/// can't serve it yet, and whenever a pooled connection fails DB_PROBE_QUERY.
+#[utoipa::path(
+    get,
+    path = "/api/v1/ready",
+    responses(
+        (status = 200, description = "Service is ready for traffic", body = ReadinessResponse),
+        (status = 503, description = "Startup has not finished or the database probe failed", body = ReadinessResponse)
+    ),
+    tag = "health"
+)]
+pub async fn readiness_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let readiness = &state.readiness;
+    let probe_timeout = Duration::from_millis(state.config.health.db_timeout_ms);
+    let probe = db::probe_connection(&state.db_pool, &state.config.database.probe_query);
+    let database = match tokio::time::timeout(probe_timeout, probe).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "Readiness database probe failed");
+            false
+        }
+        Err(_) => {
+            tracing::warn!("Readiness database probe timed out");
+            false
+        }
+    };
+    let (status_code, status) = if readiness.is_ready() && database {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+    };
+
+    (
+        status_code,
+        Json(ReadinessResponse {
+            status: status.to_string(),
+            migrations: readiness.migrations_done(),
+            warmup: readiness.warmup_done(),
+            database,
+        }),
+    )
+}
+
+/// Service name and version
+///
+/// Only changes on deploy, so it is cacheable (see `middleware::cache`).
+#[utoipa::path(
+    get,
+    path = "/api/v1/version",
+    responses(
+        (status = 200, description = "Service version", body = VersionResponse),
+        (status = 304, description = "The client's cached copy is current")
+    ),
+    tag = "health"
+)]
+pub async fn version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+/// Run a dependency check, reporting it unhealthy if it exceeds `timeout`
+///
+/// Use this for every external dependency probe so the health endpoint
+/// always answers, even when a dependency stalls instead of failing.
+pub async fn probe_with_timeout<F>(name: &str, timeout: Duration, check: F) -> SubsystemHealth
+where
+    F: Future<Output = SubsystemHealth>,
+{
+    match tokio::time::timeout(timeout, check).await {
+        Ok(health) => health,
+        Err(_) => {
+            tracing::warn!(
+                check = name,
+                timeout_ms = timeout.as_millis() as u64,
+                "Health probe timed out"
+            );
+            SubsystemHealth {
+                status: "unhealthy".to_string(),
+                message: Some(format!("{} check timed out after {}ms", name, timeout.as_millis())),
+                details: None,
+            }
+        }
+    }
+}
+
 fn check_memory_health() -> SubsystemHealth {
     // Get process memory info (basic check)
     // NOTE: Reading /proc on every request has minimal overhead, but for high-traffic
@@ -127,22 +337,15 @@ fn check_memory_health() -> SubsystemHealth {
 
 #[cfg(target_os = "linux")]
 fn read_memory_status_linux() -> SubsystemHealth {
-    if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
-        if let Some(line) = status.lines().find(|l| l.starts_with("VmRSS:")) {
-            if let Some(kb_str) = line.split_whitespace().nth(1) {
-                if let Ok(kb) = kb_str.parse::<u64>() {
-                    let mb = kb / 1024;
-                    let status = if mb > 1024 { "degraded" } else { "healthy" };
-                    return SubsystemHealth {
-                        status: status.to_string(),
-                        message: None,
-                        details: Some(serde_json::json!({
-                            "memory_usage_mb": mb,
-                        })),
-                    };
-                }
-            }
-        }
+    if let Some(mb) = process_memory_mb() {
+        let status = if mb > 1024 { "degraded" } else { "healthy" };
+        return SubsystemHealth {
+            status: status.to_string(),
+            message: None,
+            details: Some(serde_json::json!({
+                "memory_usage_mb": mb,
+            })),
+        };
     }
 
     SubsystemHealth {
@@ -151,3 +354,69 @@ fn read_memory_status_linux() -> SubsystemHealth {
         details: None,
     }
 }
+
+/// Resident set size of this process in MB, where the platform exposes it
+pub fn process_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+        let kb = line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+        Some(kb / 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replica_lag_over_threshold_is_degraded() {
+        let max_lag = Duration::from_millis(500);
+
+        let healthy = replica_lag_health(Some(Duration::from_millis(120)), max_lag);
+        let lagging = replica_lag_health(Some(Duration::from_millis(1500)), max_lag);
+
+        assert_eq!(healthy.status, "healthy");
+        assert_eq!(lagging.status, "degraded");
+        assert_eq!(lagging.details.unwrap()["lag_ms"], 1500);
+    }
+
+    #[test]
+    fn test_replica_not_replaying_is_degraded() {
+        let health = replica_lag_health(None, Duration::from_millis(500));
+
+        assert_eq!(health.status, "degraded");
+    }
+
+    #[test]
+    fn test_plain_text_needs_to_outrank_json() {
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, value.parse().unwrap());
+            wants_plain_text(&headers, &[])
+        };
+
+        assert!(accept("text/plain"));
+        assert!(accept("text/plain, */*;q=0.8"));
+        assert!(accept("application/json;q=0.5, text/plain"));
+        assert!(!accept("application/json, text/plain"));
+        assert!(!accept("text/html,application/xhtml+xml,*/*;q=0.8"));
+        assert!(!accept("text/plain;q=0"));
+        assert!(!wants_plain_text(&HeaderMap::new(), &[]));
+    }
+
+    #[test]
+    fn test_configured_probe_agents_get_plain_text() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, "ELB-HealthChecker/2.0".parse().unwrap());
+
+        assert!(wants_plain_text(&headers, &["ELB-HealthChecker".to_string()]));
+        assert!(!wants_plain_text(&headers, &["kube-probe".to_string()]));
+    }
+}
@@ -1,7 +1,48 @@
+pub mod admin;
 pub mod auth;
+pub mod fallback;
 pub mod health;
+#[cfg(feature = "oauth")]
+pub mod oauth;
+pub mod user;
 
 #[cfg(debug_assertions)]
 pub mod dev;
 
-pub use health::health_check;
+pub use health::{health_check, liveness, readiness_check, version};
+
+use axum::http::{header, HeaderMap};
+
+use crate::{error::AppError, models::dto::UserResponseDto, types::FieldSelection};
+
+/// Quality (`q`) the request's `Accept` header gives `media_type`
+///
+/// Only exact media types count; wildcards are left to the caller's default.
+/// 0 when the type isn't listed or there is no `Accept` header.
+pub(crate) fn accept_quality(headers: &HeaderMap, media_type: &str) -> f32 {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|a| a.to_str().ok()) else {
+        return 0.0;
+    };
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let listed = params.next().unwrap_or_default().trim();
+            listed.eq_ignore_ascii_case(media_type).then(|| {
+                params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0)
+            })
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Parse a `fields` query parameter naming `UserResponseDto` fields
+///
+/// `None` when the parameter is absent, meaning the full response.
+pub(crate) fn user_fields(raw: Option<&str>) -> Result<Option<FieldSelection>, AppError> {
+    raw.map(|raw| FieldSelection::parse(raw, UserResponseDto::FIELDS))
+        .transpose()
+        .map_err(AppError::BadRequest)
+}
@@ -0,0 +1,125 @@
+//! Google sign-in endpoints (`oauth` feature)
+//!
+//! The start endpoint remembers the `state` it sent to Google, and the tenant
+//! the flow started in, in a short-lived HttpOnly cookie; the callback only
+//! accepts a `state` matching that cookie.
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+
+use crate::{
+    error::AppError,
+    handlers::auth::deliver_token,
+    middleware::{
+        auth::{read_cookie, ClientFingerprint},
+        context::RequestContext,
+    },
+    models::dto::AuthResponseDto,
+    services::oauth::{self, OAuthProvider},
+    types::TenantId,
+    AppState,
+};
+
+/// Cookie holding `<state>.<tenant>` between redirect and callback
+const STATE_COOKIE: &str = "oauth_state";
+
+/// How long the user has to finish signing in at the provider
+const STATE_MAX_AGE_SECS: u64 = 600;
+
+/// Query parameters the provider redirects back with
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set instead of `code` when the user denied access
+    pub error: Option<String>,
+}
+
+fn google(state: &AppState) -> Result<&OAuthProvider, AppError> {
+    state
+        .services
+        .google
+        .as_deref()
+        .ok_or_else(|| AppError::NotFound("Google sign-in is not configured".to_string()))
+}
+
+fn state_cookie(value: &str, max_age: u64, secure: bool) -> String {
+    format!(
+        "{}={}; Path=/api/v1/auth/oauth; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        STATE_COOKIE,
+        value,
+        max_age,
+        if secure { "; Secure" } else { "" }
+    )
+}
+
+
+/// Start Google sign-in
+///
+/// GET /api/v1/auth/oauth/google
+pub async fn google_login(
+    State(state): State<AppState>,
+    tenant: TenantId,
+) -> Result<Response, AppError> {
+    let provider = google(&state)?;
+    let csrf = oauth::generate_state();
+    let url = provider.authorize_url(&csrf)?;
+
+    let cookie = state_cookie(
+        &format!("{}.{}", csrf, tenant),
+        STATE_MAX_AGE_SECS,
+        state.config.is_production(),
+    );
+
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(&url)).into_response())
+}
+
+/// Finish Google sign-in and issue this API's JWT
+///
+/// GET /api/v1/auth/oauth/google/callback?code=...&state=...
+#[tracing::instrument(name = "oauth_callback", skip_all, fields(provider = "google"))]
+pub async fn google_callback(
+    State(state): State<AppState>,
+    ctx: RequestContext,
+    headers: HeaderMap,
+    fingerprint: ClientFingerprint,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> Result<Response, AppError> {
+    let provider = google(&state)?;
+
+    if let Some(error) = query.error {
+        tracing::info!(error = %error, "Sign-in was not completed at the provider");
+        return Err(AppError::Unauthorized(format!("Sign-in failed: {}", error)));
+    }
+
+    let (Some(code), Some(returned_state)) = (query.code, query.state) else {
+        return Err(AppError::BadRequest("Missing code or state".to_string()));
+    };
+
+    let (expected_state, tenant) = read_cookie(&headers, STATE_COOKIE)
+        .and_then(|value| value.split_once('.'))
+        .ok_or_else(|| AppError::BadRequest("Sign-in session expired, start again".to_string()))?;
+    if expected_state != returned_state {
+        tracing::warn!("OAuth state mismatch");
+        return Err(AppError::BadRequest("Invalid sign-in state".to_string()));
+    }
+    let tenant = TenantId::parse(tenant)
+        .map_err(|e| AppError::BadRequest(format!("Invalid tenant id: {}", e)))?;
+
+    let identity = provider.exchange_code(&code).await?;
+    let response = state.auth().login_external(&ctx, &tenant, identity, fingerprint.as_deref())
+        .await?;
+    let response_dto: AuthResponseDto = response.into();
+
+    let (mut headers, body) = deliver_token(&state, response_dto)?;
+
+    // The state is single-use
+    let cookie = state_cookie("", 0, state.config.is_production());
+    let cookie = HeaderValue::try_from(cookie)
+        .map_err(|e| AppError::internal("Invalid state cookie", e))?;
+    headers.append(header::SET_COOKIE, cookie);
+    Ok((headers, body).into_response())
+}
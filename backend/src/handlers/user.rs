@@ -0,0 +1,333 @@
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::{StreamExt, TryStreamExt};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    middleware::{auth::AdminUser, cache, json::LimitedJson},
+    models::{
+        dto::{
+            BulkDeleteUsersRequestDto, BulkDeleteUsersResponseDto, ListUsersRequestDto,
+            ListUsersResponseDto, UserResponseDto,
+        },
+        user::User,
+    },
+    repositories::{
+        query::parse_filters, SortDirection, UserFilter, UserRepository, UserRepositoryTrait,
+        UserSort,
+    },
+    types::{FieldSelection, TenantId},
+    AppState,
+};
+
+/// Rows fetched per query while exporting
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+const CSV_HEADER: &str = "id,email,username,role,created_at,last_login_at\n";
+
+/// Newline-delimited JSON, one user object per line
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// List users in the admin's tenant
+///
+/// GET /api/v1/users?limit=20&offset=0&sort=-created_at&filter[username][contains]=ann
+/// Headers: { "Authorization": "Bearer <admin token>" }
+///
+/// Newest users come first unless `sort` says otherwise; ties are broken by
+/// id so that pages are stable. `filter[<field>][<op>]=<value>` narrows the
+/// list to users matching every filter (see `UserFilter` for the allowed
+/// fields and operators) and `total` counts only those users.
+///
+/// Pages carry an `ETag` derived from the user count and latest change; a
+/// request whose `If-None-Match` still matches gets an empty `304` without
+/// the page being loaded.
+///
+/// `fields=id,email` returns only those fields of each user.
+///
+/// With `Accept: application/x-ndjson` the whole tenant is streamed instead,
+/// one user per line in id order, fetched in batches like the CSV export;
+/// `limit`, `offset`, `sort` and filters don't apply.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    params(ListUsersRequestDto),
+    responses(
+        (status = 200, description = "A page of users, or every user as NDJSON", content(
+            ("application/json" = ListUsersResponseDto),
+            ("application/x-ndjson" = UserResponseDto)
+        )),
+        (status = 304, description = "The page is unchanged since the `If-None-Match` ETag"),
+        (status = 400, description = "Unknown sort field or direction, disallowed filter field or operator, or unknown field in `fields`"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "User is not an admin"),
+        (status = 422, description = "Validation failed")
+    ),
+    tag = "admin"
+)]
+#[tracing::instrument(name = "list_users", skip(state, admin, headers, query), fields(user_id = %admin.0.user_id))]
+pub async fn list_users(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    headers: HeaderMap,
+    Query(params): Query<ListUsersRequestDto>,
+    Query(query): Query<Vec<(String, String)>>,
+) -> Result<Response, AppError> {
+    params.validate()?;
+    let selection = super::user_fields(params.fields.as_deref())?;
+    if wants_ndjson(&headers) {
+        let stream = user_batches(state.user_repo().clone(), admin.0.tenant_id).map_ok(move |batch| {
+            batch
+                .into_iter()
+                .map(|user| ndjson_line(user, selection.as_ref()))
+                .collect::<String>()
+        });
+        return Ok(([(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)], Body::from_stream(stream))
+            .into_response());
+    }
+
+    let sort = params
+        .sort
+        .as_deref()
+        .map(str::parse::<UserSort>)
+        .transpose()
+        .map_err(AppError::BadRequest)?
+        .unwrap_or_default();
+    let filters: Vec<UserFilter> = parse_filters(&query)
+        .and_then(|filters| filters.into_iter().map(UserFilter::try_from).collect())
+        .map_err(AppError::BadRequest)?;
+    let limit = params
+        .limit
+        .unwrap_or(state.config.pagination.default_per_page);
+    let offset = params.offset.unwrap_or(0);
+
+    let tenant = &admin.0.tenant_id;
+    let version = state.user_repo().collection_version(tenant).await?;
+    let etag = cache::collection_etag(
+        version.count,
+        version.last_changed(),
+        &format!(
+            "{}:{}:{}:{:?}:{}:{}:{}",
+            tenant,
+            sort.field.as_str(),
+            sort.direction == SortDirection::Asc,
+            filters,
+            limit,
+            offset,
+            selection.as_ref().map(|s| s.fields().join(",")).unwrap_or_default()
+        ),
+    );
+    if cache::not_modified(&headers, &etag) {
+        return Ok(cache::not_modified_response(etag));
+    }
+
+    let users = state
+        .user_repo()
+        .list_sorted(tenant, sort, &filters, limit, offset)
+        .await?;
+    let total = if filters.is_empty() {
+        version.count
+    } else {
+        state.user_repo().count_matching(tenant, &filters).await?
+    };
+    let page = ListUsersResponseDto {
+        users: users.into_iter().map(Into::into).collect(),
+        total,
+        limit,
+        offset,
+    };
+    let headers = [
+        (header::ETAG, etag),
+        (header::CACHE_CONTROL, HeaderValue::from_static(cache::REVALIDATE)),
+    ];
+
+    let Some(selection) = selection else {
+        return Ok((headers, Json(page)).into_response());
+    };
+    let mut body = serde_json::to_value(&page).expect("user DTOs always serialize");
+    if let Some(users) = body["users"].as_array_mut() {
+        users.iter_mut().for_each(|user| selection.retain(user));
+    }
+    Ok((headers, Json(body)).into_response())
+}
+
+/// Whether the client ranks NDJSON above a plain JSON array
+fn wants_ndjson(headers: &HeaderMap) -> bool {
+    let ndjson_q = super::accept_quality(headers, NDJSON_CONTENT_TYPE);
+    ndjson_q > 0.0 && ndjson_q > super::accept_quality(headers, "application/json")
+}
+
+fn ndjson_line(user: User, selection: Option<&FieldSelection>) -> String {
+    let user = UserResponseDto::from(user);
+    let mut line = match selection {
+        Some(selection) => {
+            let mut value = serde_json::to_value(&user).expect("user DTOs always serialize");
+            selection.retain(&mut value);
+            value.to_string()
+        }
+        None => serde_json::to_string(&user).expect("user DTOs always serialize"),
+    };
+    line.push('\n');
+    line
+}
+
+/// Delete several users in the admin's tenant
+///
+/// DELETE /api/v1/users
+/// Headers: { "Authorization": "Bearer <admin token>" }
+/// Body: { "ids": ["<uuid>", ...], "confirm": true }
+///
+/// Runs as a single statement; ids that don't exist are ignored.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users",
+    request_body = BulkDeleteUsersRequestDto,
+    responses(
+        (status = 200, description = "Users deleted", body = BulkDeleteUsersResponseDto),
+        (status = 400, description = "`confirm` was not true"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "User is not an admin"),
+        (status = 422, description = "Validation failed")
+    ),
+    tag = "admin"
+)]
+#[tracing::instrument(name = "bulk_delete_users", skip(state, admin, dto), fields(user_id = %admin.0.user_id, count = dto.ids.len()))]
+pub async fn bulk_delete(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    LimitedJson(dto): LimitedJson<BulkDeleteUsersRequestDto>,
+) -> Result<Json<BulkDeleteUsersResponseDto>, AppError> {
+    dto.validate()?;
+    if !dto.confirm {
+        return Err(AppError::BadRequest(
+            "Set \"confirm\": true to delete these users".to_string(),
+        ));
+    }
+
+    let deleted = state
+        .user_repo()
+        .delete_many(&admin.0.tenant_id, &dto.ids)
+        .await?;
+
+    tracing::info!(deleted, "Users deleted in bulk");
+    Ok(Json(BulkDeleteUsersResponseDto { deleted }))
+}
+
+/// Export every user in the admin's tenant as CSV
+///
+/// GET /api/v1/users/export.csv
+/// Headers: { "Authorization": "Bearer <admin token>" }
+///
+/// Rows are streamed in batches of 500 fetched by keyset pagination on `id`,
+/// so memory use does not grow with the size of the table. A database error
+/// mid-export aborts the response, leaving the client with a truncated file.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/export.csv",
+    responses(
+        (status = 200, description = "Users as CSV", content_type = "text/csv", body = String),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 403, description = "User is not an admin")
+    ),
+    tag = "admin"
+)]
+#[tracing::instrument(name = "export_users_csv", skip(state, admin), fields(user_id = %admin.0.user_id))]
+pub async fn export_csv(State(state): State<AppState>, admin: AdminUser) -> Response {
+    let rows = user_batches(state.user_repo().clone(), admin.0.tenant_id)
+        .map_ok(|batch| batch.iter().map(csv_row).collect::<String>());
+    let stream = futures::stream::once(async { Ok(CSV_HEADER.to_string()) }).chain(rows);
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"users.csv\""),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+/// Every user in the tenant, in batches fetched by keyset pagination on `id`
+fn user_batches(
+    repo: UserRepository,
+    tenant: TenantId,
+) -> impl futures::Stream<Item = Result<Vec<User>, AppError>> {
+    enum Cursor {
+        After(Option<Uuid>),
+        Done,
+    }
+
+    futures::stream::try_unfold(Cursor::After(None), move |cursor| {
+        let repo = repo.clone();
+        let tenant = tenant.clone();
+        async move {
+            let after = match cursor {
+                Cursor::After(after) => after,
+                Cursor::Done => return Ok(None),
+            };
+
+            let batch = repo.list_after(&tenant, after, EXPORT_BATCH_SIZE).await?;
+            let Some(last) = batch.last() else {
+                return Ok(None);
+            };
+            let next = if (batch.len() as i64) < EXPORT_BATCH_SIZE {
+                Cursor::Done
+            } else {
+                Cursor::After(Some(last.id))
+            };
+
+            Ok(Some((batch, next)))
+        }
+    })
+}
+
+fn csv_row(user: &User) -> String {
+    let last_login = user
+        .last_login_at
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    format!(
+        "{},{},{},{},{},{}\n",
+        user.id,
+        csv_field(&user.email),
+        csv_field(&user.username),
+        csv_field(&user.role),
+        user.created_at.format("%Y-%m-%dT%H:%M:%S"),
+        last_login,
+    )
+}
+
+/// Quote a field when needed, and defuse values a spreadsheet would run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("alice"), "alice");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+    }
+}
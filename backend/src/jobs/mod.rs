@@ -1,6 +1,7 @@
-use crate::AppState;
+use crate::{tracing_config::STARTUP_STEP_TARGET, AppState};
 use tokio_cron_scheduler::{Job, JobScheduler};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub mod tasks;
 
@@ -34,8 +35,23 @@ pub async fn init_scheduler(state: Arc<AppState>) -> Result<JobScheduler, Box<dy
     })?;
     scheduler.add(health_check_job).await?;
 
+    // Prune expired rate-limit buckets and nonces every PRUNE_INTERVAL_SECS
+    let prune_interval = state.config.rate_limit.prune_interval_secs;
+    if prune_interval > 0 {
+        let state_clone = state.clone();
+        let prune_job = Job::new_repeated_async(Duration::from_secs(prune_interval), move |_uuid, _lock| {
+            let state = state_clone.clone();
+            Box::pin(async move {
+                if let Err(e) = tasks::prune_expired_entries(state).await {
+                    tracing::error!("Prune task failed: {}", e);
+                }
+            })
+        })?;
+        scheduler.add(prune_job).await?;
+    }
+
     scheduler.start().await?;
-    tracing::info!("Job scheduler started successfully");
+    tracing::info!(target: STARTUP_STEP_TARGET, "Job scheduler started successfully");
 
     Ok(scheduler)
 }
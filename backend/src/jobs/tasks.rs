@@ -1,4 +1,4 @@
-use crate::{db, error::AppError, AppState};
+use crate::{db, error::AppError, metrics, AppState};
 use std::sync::Arc;
 
 /// Example background task: Clean up old records
@@ -22,7 +22,7 @@ pub async fn cleanup_old_records(_state: Arc<AppState>) -> Result<(), AppError>
 /// Example background task: Periodic health check
 pub async fn periodic_health_check(state: Arc<AppState>) -> Result<(), AppError> {
     // Verify database connectivity
-    db::test_connection(&state.db_pool).await?;
+    db::probe_connection(&state.db_pool, &state.config.database.probe_query).await?;
 
     // You could add more checks here:
     // - External API connectivity
@@ -34,6 +34,32 @@ pub async fn periodic_health_check(state: Arc<AppState>) -> Result<(), AppError>
     Ok(())
 }
 
+/// Expired entries dropped by [`prune_expired_entries`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrunedEntries {
+    pub rate_limit_keys: usize,
+    pub nonces: usize,
+}
+
+/// Drop expired rate-limit buckets and nonces from memory
+///
+/// Counts are recorded in `in_memory_entries_pruned_total{store}`.
+pub async fn prune_expired_entries(state: Arc<AppState>) -> Result<PrunedEntries, AppError> {
+    let pruned = PrunedEntries {
+        rate_limit_keys: state.services.auth_rate_limiter.prune().await,
+        nonces: state.services.nonces.prune(),
+    };
+    metrics::record_pruned("rate_limit", pruned.rate_limit_keys);
+    metrics::record_pruned("nonce", pruned.nonces);
+
+    tracing::debug!(
+        rate_limit_keys = pruned.rate_limit_keys,
+        nonces = pruned.nonces,
+        "Pruned expired in-memory entries"
+    );
+    Ok(pruned)
+}
+
 /// Example background task: Send notification emails
 pub async fn send_notification_emails(_state: Arc<AppState>) -> Result<(), AppError> {
     tracing::info!("Processing notification emails");
@@ -1,35 +1,65 @@
 // Library exports for binary crates and tests
+//
+// Without the default `server` feature only `models::dto`, `types` and the
+// `client` are built, so API consumers don't compile the server's stack.
 
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "server")]
 pub mod config;
+#[cfg(feature = "server")]
 pub mod db;
+#[cfg(feature = "server")]
 pub mod dev_macros;
+#[cfg(feature = "server")]
 pub mod docs;
+#[cfg(feature = "server")]
 pub mod error;
+#[cfg(feature = "server")]
 pub mod handlers;
+#[cfg(feature = "server")]
 pub mod jobs;
+#[cfg(feature = "server")]
 pub mod metrics;
+#[cfg(feature = "server")]
 pub mod middleware;
 pub mod models;
+#[cfg(feature = "server")]
+pub mod readiness;
+#[cfg(feature = "server")]
 pub mod repositories;
+#[cfg(feature = "server")]
 pub mod routes;
+#[cfg(feature = "server")]
 pub mod services;
+#[cfg(feature = "server")]
+pub mod storage;
+#[cfg(all(test, feature = "server"))]
+mod test_support;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(feature = "server")]
 pub mod tracing_config;
 pub mod types;
 
 // Development-only utilities (compiled out in release builds)
-#[cfg(debug_assertions)]
+#[cfg(all(feature = "server", debug_assertions))]
 pub mod dev;
 
 // Re-export dev panic handler setup
-#[cfg(debug_assertions)]
+#[cfg(all(feature = "server", debug_assertions))]
 pub use dev::setup_dev_panic_handler;
 
-use config::Config;
-use db::DbPool;
-use repositories::UserRepository;
-use services::{auth::AuthService, jwt::JwtService};
-use std::sync::Arc;
+#[cfg(feature = "server")]
+use {
+    config::Config,
+    db::DbPool,
+    repositories::UserRepository,
+    services::{auth::AuthService, jwt::JwtService},
+    std::sync::Arc,
+};
 
+#[cfg(feature = "server")]
 /// Application services layer
 /// Groups all business logic services together
 #[derive(Clone)]
@@ -37,45 +67,114 @@ pub struct Services {
     pub auth: Arc<AuthService>,
     pub user_repo: Arc<UserRepository>,
     pub jwt: Arc<JwtService>,
+    /// Domain events published by services (see `services::events`)
+    pub events: services::events::EventBus,
+    /// Outbound HTTP client with the HTTP_RETRY_* policy
+    pub http: services::http::HttpClient,
+    /// Single-use nonces for sensitive mutations
+    pub nonces: services::nonce::NonceStore,
+    /// Limiter on the `/auth` routes (applied in release builds only)
+    pub auth_rate_limiter: middleware::rate_limit::RateLimiter,
+    /// Global cap on registrations, when REGISTRATION_RATE_PER_MINUTE is set
+    pub registration_throttle: Option<services::throttle::TokenBucket>,
+    /// Requests recorded with `X-Debug-Capture: 1`, listed at `/dev/captures`
+    #[cfg(debug_assertions)]
+    pub captures: middleware::capture::CaptureStore,
+    /// Where uploaded files are stored (STORAGE_BACKEND)
+    pub storage: Arc<dyn storage::StorageBackend>,
+    /// Source of rotatable secrets (SECRET_PROVIDER), see `services::rotation`
+    pub secrets: Arc<config::secrets::SecretManager>,
+    /// Google sign-in, when configured
+    #[cfg(feature = "oauth")]
+    pub google: Option<Arc<services::oauth::OAuthProvider>>,
 }
 
+#[cfg(feature = "server")]
 impl Services {
     pub fn new(db_pool: DbPool, config: &Config) -> Self {
         let jwt_service = JwtService::new(
             config.jwt.secret.clone(),
             config.jwt.expiration_hours,
-        );
+        )
+        .with_previous_secrets(config.jwt.previous_secrets.clone());
         let user_repository = UserRepository::new(db_pool);
-        let auth_service = AuthService::new(user_repository.clone(), jwt_service.clone());
+        let events = services::events::EventBus::default();
+        let http = services::http::HttpClient::new(config.http_client.clone());
+        let auth_service = AuthService::new(user_repository.clone(), jwt_service.clone())
+            .with_password_config(&config.password)
+            .with_event_bus(events.clone());
 
         Self {
             auth: Arc::new(auth_service),
             user_repo: Arc::new(user_repository),
             jwt: Arc::new(jwt_service),
+            events,
+            #[cfg(feature = "oauth")]
+            google: config.oauth.google.clone().map(|google| {
+                Arc::new(services::oauth::OAuthProvider::google(google, http.clone()))
+            }),
+            http,
+            nonces: services::nonce::NonceStore::default(),
+            auth_rate_limiter: middleware::rate_limit::RateLimiter::auth(config.server.trust_proxy)
+                .with_exemptions(config.rate_limit.clone()),
+            registration_throttle: match config.rate_limit.registrations_per_minute {
+                0 => None,
+                per_minute => Some(services::throttle::TokenBucket::per_minute(per_minute)),
+            },
+            #[cfg(debug_assertions)]
+            captures: middleware::capture::CaptureStore::default(),
+            storage: Arc::new(storage::LocalStorage::new("uploads")),
+            secrets: Arc::new(config::secrets::SecretManager::new_env()),
         }
     }
 }
 
+#[cfg(feature = "server")]
 /// Application state shared across all handlers
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
     pub db_pool: DbPool,
+    /// Read replica pool, when DATABASE_REPLICA_URL is set
+    pub replica_pool: Option<DbPool>,
     pub services: Services,
+    /// Startup milestones reported by `GET /api/v1/ready`
+    pub readiness: Arc<readiness::Readiness>,
 }
 
+#[cfg(feature = "server")]
 impl AppState {
     pub fn new(config: Config, db_pool: DbPool) -> Self {
         let config = Arc::new(config);
         let services = Services::new(db_pool.clone(), &config);
+        let readiness = Arc::new(readiness::Readiness::new(config.database.warmup));
+        let replica_pool = config.database.replica_url.as_deref().and_then(|url| {
+            db::create_pool(url, config.database.pool_size)
+                .map_err(|e| tracing::warn!("Read replica pool unavailable: {}", e))
+                .ok()
+        });
 
         Self {
             config,
             db_pool,
+            replica_pool,
             services,
+            readiness,
         }
     }
 
+    /// Replace the storage backend (see `storage::from_env`)
+    pub fn with_storage(mut self, storage: Arc<dyn storage::StorageBackend>) -> Self {
+        self.services.storage = storage;
+        self
+    }
+
+    /// Replace the secret manager (see `SecretManager::from_config`)
+    pub fn with_secret_manager(mut self, secrets: Arc<config::secrets::SecretManager>) -> Self {
+        self.services.secrets = secrets;
+        self
+    }
+
     /// Convenient access to auth service
     #[inline]
     pub fn auth(&self) -> &AuthService {
@@ -93,4 +192,22 @@ impl AppState {
     pub fn jwt(&self) -> &JwtService {
         &self.services.jwt
     }
+
+    /// Convenient access to the domain event bus
+    #[inline]
+    pub fn events(&self) -> &services::events::EventBus {
+        &self.services.events
+    }
+
+    /// Convenient access to the outbound HTTP client
+    #[inline]
+    pub fn http(&self) -> &services::http::HttpClient {
+        &self.services.http
+    }
+
+    /// Convenient access to the file storage backend
+    #[inline]
+    pub fn storage(&self) -> &dyn storage::StorageBackend {
+        self.services.storage.as_ref()
+    }
 }
@@ -1,16 +1,26 @@
-use backend::{config::Config, db, jobs, metrics, routes, tracing_config, AppState};
+use backend::{
+    config::{summary::StartupSummary, Config, Environment, RuntimeConfig},
+    db, jobs, metrics, routes, tracing_config, AppState,
+};
+// Boot steps; QUIET_STARTUP=1 leaves only the startup summary and errors
+use backend::tracing_config::STARTUP_STEP_TARGET as STEP;
 use std::sync::Arc;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Sized from WORKER_THREADS / MAX_BLOCKING_THREADS rather than the host's core count
+    let runtime = RuntimeConfig::from_env()?;
+    runtime.builder().build()?.block_on(run(runtime))
+}
+
+async fn run(runtime: RuntimeConfig) -> Result<(), Box<dyn std::error::Error>> {
     // Determine environment
-    let environment = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+    let environment = Environment::from_env()?;
 
     // Initialize tracing - check if file logging is requested in dev mode
-    if environment != "production" && std::env::var("LOG_TO_FILE").is_ok() {
+    if !environment.is_production() && std::env::var("LOG_TO_FILE").is_ok() {
         tracing_config::init_dev_with_file_logging("logs/dev.log")?;
     } else {
-        tracing_config::init_tracing("backend", &environment)?;
+        tracing_config::init_tracing("backend", environment)?;
     }
 
     // Setup better panic messages in development
@@ -18,53 +28,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     backend::setup_dev_panic_handler();
 
     tracing::info!(
+        target: STEP,
         environment = %environment,
         version = %env!("CARGO_PKG_VERSION"),
         "Starting backend service"
     );
 
     // Initialize metrics
-    metrics::init_metrics();
-    tracing::info!("Metrics initialized");
+    if let Err(e) = metrics::init_metrics() {
+        tracing::warn!("Failed to initialize metrics: {}. Metrics will not be available.", e);
+    }
 
     // Load configuration (with smart defaults in dev mode)
-    let config = Config::load()?;
-    tracing::info!("Configuration loaded successfully");
-    tracing::info!("Environment: {}", config.server.environment);
+    let mut config = Config::load()?;
+    // The dev fallback doesn't read the environment; report the runtime actually running
+    config.runtime = runtime;
+    tracing::info!(target: STEP, "Configuration loaded");
+    let summary = StartupSummary::from_config(&config);
+    summary.log();
+    if !config.is_production() {
+        tracing::info!(target: STEP, "Effective configuration:\n{}", summary.table());
+    }
+    backend::error::set_pretty_errors(config.server.pretty_errors);
 
     // Create database pool
-    let db_pool = db::create_pool(&config.database.url, config.database.pool_size)?;
-    tracing::info!("Database pool created successfully");
+    let db_pool = db::create_pool_with_statement_timeout(
+        &config.database.url,
+        config.database.pool_size,
+        config.database.statement_timeout_ms,
+    )?;
+    tracing::info!(target: STEP, "Database pool created successfully");
 
     // Test database connection
-    db::test_connection(&db_pool).await?;
-    tracing::info!("Database connection validated");
+    db::probe_connection(&db_pool, &config.database.probe_query).await?;
+    tracing::info!(target: STEP, "Database connection validated");
+    db::check_pool_size(&db_pool, &config.database, config.is_production()).await?;
 
     // Create application state (services are initialized inside)
-    let state = AppState::new(config.clone(), db_pool);
+    let storage = backend::storage::from_env().await?;
+    tracing::info!(target: STEP, backend = storage.name(), "File storage initialized");
+    let secrets = backend::config::secrets::SecretManager::from_config().await?;
+    let state = AppState::new(config.clone(), db_pool)
+        .with_storage(Arc::from(storage))
+        .with_secret_manager(Arc::new(secrets));
 
     // Initialize background job scheduler
     let scheduler = jobs::init_scheduler(Arc::new(state.clone())).await?;
-    tracing::info!("Background job scheduler initialized");
+    tracing::info!(target: STEP, "Background job scheduler initialized");
 
     // Create router
-    let app = routes::create_router(state);
+    let app = routes::create_router(state.clone());
 
     // Start server
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
-    tracing::info!("Server listening on {}", addr);
+    let scheme = if config.server.tls.is_some() { "https" } else { "http" };
+    tracing::info!(target: STEP, "Server listening on {}://{}", scheme, addr);
 
     // Setup graceful shutdown
     let graceful_shutdown = shutdown_signal();
 
+    // Migrations and warmup run while serving; /api/v1/ready reports 503 until
+    // they finish. A failure stops the server like any other startup error.
+    let mut startup_error = None;
     tokio::select! {
-        result = axum::serve(listener, app) => {
+        result = serve(listener, app, &config) => {
             if let Err(e) = result {
                 tracing::error!("Server error: {}", e);
             }
         }
+        Err(e) = prepare(&state) => {
+            tracing::error!("Startup failed: {}", e);
+            startup_error = Some(e);
+        }
         _ = graceful_shutdown => {
             tracing::info!("Graceful shutdown initiated");
         }
@@ -78,6 +115,56 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Shutdown tracing and flush spans (do this last to ensure all logs are flushed)
     tracing_config::shutdown_tracing().await;
 
+    match startup_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+/// Serve HTTPS when TLS_CERT_PATH/TLS_KEY_PATH are set, plain HTTP otherwise
+async fn serve(
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "tls")]
+    if let Some(tls) = &config.server.tls {
+        return Ok(backend::tls::serve(listener, app, tls).await?);
+    }
+    #[cfg(not(feature = "tls"))]
+    let _ = config;
+
+    axum::serve(
+        listener,
+        // Client addresses feed IP-based rate limiting
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Apply migrations, then optionally prime the pool, marking each milestone
+/// on `state.readiness`
+async fn prepare(state: &AppState) -> Result<(), backend::error::AppError> {
+    let config = &state.config;
+
+    let applied = db::migrations::run_pending(&config.database.url).await?;
+    tracing::info!(target: STEP, applied = applied, "Database migrations up to date");
+    state.readiness.mark_migrations_done();
+
+    // Optionally prime the pool so the first requests don't pay connection setup
+    if config.database.warmup {
+        let report = db::warmup(&state.db_pool, config.database.warmup_connections).await?;
+        tracing::info!(
+            target: STEP,
+            connections = report.connections,
+            duration_ms = report.duration.as_millis() as u64,
+            "Database pool warmed up"
+        );
+        state.readiness.mark_warmup_done();
+    }
+
+    tracing::info!(target: STEP, "Service ready");
     Ok(())
 }
 
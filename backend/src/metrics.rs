@@ -1,8 +1,90 @@
-use axum::{body::Body, extract::MatchedPath, http::Request, middleware::Next, response::Response};
-use metrics::{counter, histogram};
+use axum::{
+    body::{Body, HttpBody},
+    extract::MatchedPath,
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
 use std::time::Instant;
 
+use crate::tracing_config::STARTUP_STEP_TARGET;
+
+/// Handle to the Prometheus recorder installed by `init_metrics`
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Process start, captured the first time metrics are touched
+static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// Requests seen by `track_metrics` since startup
+static REQUESTS_TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// Requests currently being handled
+static REQUESTS_IN_FLIGHT: AtomicU64 = AtomicU64::new(0);
+
+/// Bucket bounds (seconds) for `db_connection_acquire_seconds`
+///
+/// A healthy pool hands out connections in well under a millisecond; the
+/// upper buckets show waits on an exhausted pool up to its timeout.
+const DB_ACQUIRE_BUCKETS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Bucket bounds (seconds) for `http_request_duration_seconds`
+///
+/// Only set with the `otlp` feature: exemplars attach to histogram buckets,
+/// so the metric is exported as a histogram rather than a summary.
+#[cfg(feature = "otlp")]
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Seconds since the process started
+pub fn uptime_seconds() -> u64 {
+    STARTED_AT.get_or_init(Instant::now).elapsed().as_secs()
+}
+
+/// In-process request counters, for endpoints that report stats as JSON
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct RequestCounts {
+    pub total: u64,
+    pub in_flight: u64,
+}
+
+/// Current request counters
+pub fn request_counts() -> RequestCounts {
+    RequestCounts {
+        total: REQUESTS_TOTAL.load(Ordering::Relaxed),
+        in_flight: REQUESTS_IN_FLIGHT.load(Ordering::Relaxed),
+    }
+}
+
+/// Decrements the in-flight counter even if the request future is dropped
+struct InFlightGuard;
+
+impl InFlightGuard {
+    fn new() -> Self {
+        REQUESTS_TOTAL.fetch_add(1, Ordering::Relaxed);
+        let in_flight = REQUESTS_IN_FLIGHT.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge!("http_requests_in_flight").set(in_flight as f64);
+        InFlightGuard
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let in_flight = REQUESTS_IN_FLIGHT.fetch_sub(1, Ordering::Relaxed) - 1;
+        gauge!("http_requests_in_flight").set(in_flight as f64);
+    }
+}
+
 /// Middleware to track HTTP metrics
+///
+/// Safe without `init_metrics`: the `metrics` macros are no-ops until a
+/// global recorder is installed.
 pub async fn track_metrics(req: Request<Body>, next: Next) -> Response {
     let start = Instant::now();
     let path = req
@@ -11,39 +93,395 @@ pub async fn track_metrics(req: Request<Body>, next: Next) -> Response {
         .map(|p| p.as_str().to_string())
         .unwrap_or_else(|| "unknown".to_string());
     let method = req.method().to_string();
+    #[cfg(feature = "otlp")]
+    let trace_id = crate::tracing_config::current_trace_id();
+    let _in_flight = InFlightGuard::new();
+    let request_size = body_size(req.headers(), req.body());
 
     let response = next.run(req).await;
 
     let latency = start.elapsed().as_secs_f64();
     let status = response.status().as_u16().to_string();
+    let response_size = body_size(response.headers(), response.body());
 
     // Record metrics
     counter!("http_requests_total", "method" => method.clone(), "path" => path.clone(), "status" => status.clone()).increment(1);
-    histogram!("http_request_duration_seconds", "method" => method, "path" => path).record(latency);
+    #[cfg(feature = "otlp")]
+    if let Some(trace_id) = trace_id {
+        exemplars::record(&method, &path, latency, trace_id);
+    }
+    histogram!("http_request_duration_seconds", "method" => method, "path" => path.clone()).record(latency);
+    if let Some(size) = request_size {
+        histogram!("http_request_size_bytes", "path" => path.clone()).record(size as f64);
+    }
+    if let Some(size) = response_size {
+        histogram!("http_response_size_bytes", "path" => path).record(size as f64);
+    }
+    gauge!("process_uptime_seconds").set(uptime_seconds() as f64);
 
     response
 }
 
-/// Initialize metrics recorder
-pub fn init_metrics() {
-    match metrics_exporter_prometheus::PrometheusBuilder::new().install() {
-        Ok(_) => {
-            tracing::info!("Prometheus metrics exporter initialized");
-        }
-        Err(e) => {
-            tracing::warn!("Failed to install Prometheus metrics exporter: {}. Metrics will not be available.", e);
+/// Body size from `Content-Length`, or the body itself when its length is known
+///
+/// Chunked and streamed bodies of unknown length aren't recorded rather than
+/// buffered just to be measured.
+fn body_size(headers: &HeaderMap, body: &Body) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok()?.parse().ok())
+        .or_else(|| body.size_hint().exact())
+}
+
+/// Count a rejected request field in `validation_errors_total{field}`
+///
+/// Shows which API fields clients most often get wrong.
+pub fn record_validation_failure(field: &str) {
+    counter!("validation_errors_total", "field" => field.to_string()).increment(1);
+}
+
+/// Count a request turned away by load shedding in `http_requests_shed_total`
+pub fn record_load_shed() {
+    counter!("http_requests_shed_total").increment(1);
+}
+
+/// Count expired entries dropped from an in-memory store by the prune job
+/// in `in_memory_entries_pruned_total{store}`
+pub fn record_pruned(store: &'static str, count: usize) {
+    counter!("in_memory_entries_pruned_total", "store" => store).increment(count as u64);
+}
+
+/// Record how long taking a connection from the pool took in
+/// `db_connection_acquire_seconds{outcome}`, whether or not it succeeded
+pub fn record_connection_acquire(duration: std::time::Duration, success: bool) {
+    let outcome = if success { "ok" } else { "error" };
+    histogram!("db_connection_acquire_seconds", "outcome" => outcome).record(duration.as_secs_f64());
+}
+
+/// Count a pooled connection discarded because the database dropped it
+/// (`db_connections_lost_total`)
+pub fn record_connection_lost() {
+    counter!("db_connections_lost_total").increment(1);
+}
+
+/// Initialize the Prometheus metrics recorder
+///
+/// Idempotent: once a recorder has been installed, later calls return `Ok`.
+/// Fails if some other global recorder was installed first; callers should
+/// log that and carry on, since requests are still served without metrics.
+pub fn init_metrics() -> Result<(), BuildError> {
+    STARTED_AT.get_or_init(Instant::now);
+    if PROMETHEUS.get().is_some() {
+        return Ok(());
+    }
+
+    let builder = PrometheusBuilder::new().set_buckets_for_metric(
+        Matcher::Full("db_connection_acquire_seconds".to_string()),
+        DB_ACQUIRE_BUCKETS,
+    )?;
+    #[cfg(feature = "otlp")]
+    let builder = builder.set_buckets_for_metric(
+        Matcher::Full("http_request_duration_seconds".to_string()),
+        REQUEST_DURATION_BUCKETS,
+    )?;
+    match builder.install_recorder() {
+        Ok(handle) => {
+            let _ = PROMETHEUS.set(handle);
+            tracing::info!(target: STARTUP_STEP_TARGET, "Prometheus metrics recorder installed");
+            Ok(())
         }
+        // Lost a race with a concurrent call that installed ours
+        Err(_) if PROMETHEUS.get().is_some() => Ok(()),
+        Err(e) => Err(e),
     }
 }
 
 /// Get metrics handler - returns Prometheus formatted metrics
 pub async fn metrics_handler() -> String {
-    // Get the handle that was created during init
-    match metrics_exporter_prometheus::PrometheusBuilder::new().install_recorder() {
-        Ok(handle) => handle.render(),
-        Err(e) => {
-            tracing::error!("Failed to get Prometheus handle: {}", e);
-            format!("# Metrics unavailable: {}", e)
+    match PROMETHEUS.get() {
+        Some(handle) => handle.render(),
+        None => "# Metrics unavailable: recorder not initialized\n".to_string(),
+    }
+}
+
+/// Metrics in OpenMetrics format, with exemplars, for scrapers that ask for it
+///
+/// Prometheus only reads exemplars from OpenMetrics, so a scrape whose
+/// `Accept` names `application/openmetrics-text` gets the trace ids attached
+/// to `http_request_duration_seconds` buckets; anything else gets the plain
+/// text format from [`metrics_handler`]. Only routed with METRICS_EXEMPLARS=1.
+#[cfg(feature = "otlp")]
+pub async fn openmetrics_handler(headers: HeaderMap) -> Response {
+    use axum::response::IntoResponse;
+
+    let wants_openmetrics = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(exemplars::OPENMETRICS_CONTENT_TYPE_NAME));
+    if !wants_openmetrics {
+        return metrics_handler().await.into_response();
+    }
+
+    (
+        [(header::CONTENT_TYPE, exemplars::OPENMETRICS_CONTENT_TYPE)],
+        render_openmetrics(),
+    )
+        .into_response()
+}
+
+/// Rendered metrics with exemplars appended to the request-duration buckets
+#[cfg(feature = "otlp")]
+pub fn render_openmetrics() -> String {
+    let Some(handle) = PROMETHEUS.get() else {
+        return "# EOF\n".to_string();
+    };
+    let mut rendered = exemplars::annotate(&to_openmetrics(&handle.render()));
+    rendered.push_str("# EOF\n");
+    rendered
+}
+
+/// Rewrite the Prometheus text format into OpenMetrics (without `# EOF`)
+///
+/// The two differ where the exporter's output is concerned: OpenMetrics
+/// names a counter family without its `_total` suffix (which every sample
+/// carries), calls `untyped` metrics `unknown`, and allows no blank lines.
+#[cfg(feature = "otlp")]
+fn to_openmetrics(rendered: &str) -> String {
+    use std::collections::HashSet;
+
+    let counters: HashSet<&str> = rendered
+        .lines()
+        .filter_map(|line| line.strip_prefix("# TYPE ")?.strip_suffix(" counter"))
+        .collect();
+    let family = |name: &str| -> String {
+        if counters.contains(name) {
+            name.strip_suffix("_total").unwrap_or(name).to_string()
+        } else {
+            name.to_string()
+        }
+    };
+
+    let mut out = String::with_capacity(rendered.len());
+    for line in rendered.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').unwrap_or((rest, "unknown"));
+            let kind = if kind == "untyped" { "unknown" } else { kind };
+            out.push_str(&format!("# TYPE {} {}", family(name), kind));
+        } else if let Some(rest) = line.strip_prefix("# HELP ") {
+            let (name, help) = rest.split_once(' ').unwrap_or((rest, ""));
+            out.push_str(&format!("# HELP {} {}", family(name), help));
+        } else if line.starts_with('#') {
+            out.push_str(line);
+        } else {
+            // Counter samples must end in `_total`
+            let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+            let (name, rest) = line.split_at(name_end);
+            if counters.contains(name) && !name.ends_with("_total") {
+                out.push_str(&format!("{}_total{}", name, rest));
+            } else {
+                out.push_str(line);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Trace-id exemplars for `http_request_duration_seconds`
+///
+/// Keeps the latest sample per method, path and bucket, so a latency spike
+/// on a dashboard links to a trace that landed in the same bucket.
+#[cfg(feature = "otlp")]
+mod exemplars {
+    use super::REQUEST_DURATION_BUCKETS;
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    pub(super) const OPENMETRICS_CONTENT_TYPE_NAME: &str = "application/openmetrics-text";
+    pub(super) const OPENMETRICS_CONTENT_TYPE: &str =
+        "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+    const BUCKET_PREFIX: &str = "http_request_duration_seconds_bucket{";
+
+    struct Exemplar {
+        trace_id: String,
+        value: f64,
+        timestamp: f64,
+    }
+
+    /// Keyed by method, path and index into the bucket bounds (`len` is `+Inf`)
+    type Store = Mutex<HashMap<(String, String, usize), Exemplar>>;
+
+    static EXEMPLARS: OnceLock<Store> = OnceLock::new();
+
+    fn store() -> &'static Store {
+        EXEMPLARS.get_or_init(Default::default)
+    }
+
+    /// Remember `trace_id` as the exemplar for the bucket `value` falls in
+    pub(super) fn record(method: &str, path: &str, value: f64, trace_id: String) {
+        let bucket = REQUEST_DURATION_BUCKETS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(REQUEST_DURATION_BUCKETS.len());
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|since| since.as_secs_f64())
+            .unwrap_or_default();
+
+        let mut exemplars = store().lock().unwrap_or_else(|e| e.into_inner());
+        exemplars.insert(
+            (method.to_string(), path.to_string(), bucket),
+            Exemplar { trace_id, value, timestamp },
+        );
+    }
+
+    /// Append ` # {trace_id="..."} value timestamp` to bucket lines that have one
+    pub(super) fn annotate(rendered: &str) -> String {
+        let exemplars = store().lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::with_capacity(rendered.len());
+        for line in rendered.lines() {
+            out.push_str(line);
+            if let Some(exemplar) = bucket_key(line).and_then(|key| exemplars.get(&key)) {
+                out.push_str(&format!(
+                    " # {{trace_id=\"{}\"}} {} {:.3}",
+                    exemplar.trace_id, exemplar.value, exemplar.timestamp
+                ));
+            }
+            out.push('\n');
         }
+        out
+    }
+
+    /// Method, path and bucket index of a request-duration bucket line
+    fn bucket_key(line: &str) -> Option<(String, String, usize)> {
+        let labels = line.strip_prefix(BUCKET_PREFIX)?.split_once('}')?.0;
+        let (mut method, mut path, mut le) = (None, None, None);
+        for label in labels.split(',') {
+            let (name, value) = label.split_once('=')?;
+            let value = value.trim_matches('"');
+            match name {
+                "method" => method = Some(value),
+                "path" => path = Some(value),
+                "le" => le = Some(value),
+                _ => {}
+            }
+        }
+
+        let bucket = match le? {
+            "+Inf" => REQUEST_DURATION_BUCKETS.len(),
+            le => {
+                let bound: f64 = le.parse().ok()?;
+                REQUEST_DURATION_BUCKETS.iter().position(|b| *b == bound)?
+            }
+        };
+        Some((method?.to_string(), path?.to_string(), bucket))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_metrics_twice_does_not_panic() {
+        init_metrics().unwrap();
+        init_metrics().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_size_is_recorded() {
+        use axum::{routing::post, Router};
+        use tower::ServiceExt;
+
+        init_metrics().unwrap();
+        let app = Router::new()
+            .route("/size-probe", post(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(track_metrics));
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/size-probe")
+            .body(Body::from(vec![b'x'; 1234]))
+            .unwrap();
+        app.oneshot(req).await.unwrap();
+
+        let rendered = metrics_handler().await;
+        assert!(
+            rendered.contains("http_request_size_bytes_sum{path=\"/size-probe\"} 1234"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[cfg(feature = "otlp")]
+    #[tokio::test]
+    async fn test_duration_sample_carries_trace_exemplar() {
+        use axum::{routing::get, Router};
+        use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+        use tower::ServiceExt;
+        use tracing::Instrument;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        init_metrics().unwrap();
+        // The tracer only holds a weak reference, so the provider must outlive it
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let tracer = provider.tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/exemplar-probe", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(track_metrics));
+        let span = tracing::info_span!("request");
+        let trace_id = span.context().span().span_context().trace_id().to_string();
+
+        let req = Request::builder().uri("/exemplar-probe").body(Body::empty()).unwrap();
+        app.oneshot(req).instrument(span).await.unwrap();
+
+        let rendered = render_openmetrics();
+        let exemplar = format!("# {{trace_id=\"{}\"}}", trace_id);
+        assert!(
+            rendered.lines().any(|line| line
+                .starts_with("http_request_duration_seconds_bucket{method=\"GET\",path=\"/exemplar-probe\"")
+                && line.contains(&exemplar)),
+            "{}",
+            rendered
+        );
+        assert!(rendered.ends_with("# EOF\n"));
+        assert_eq!(rendered.matches("# EOF").count(), 1);
+        assert!(rendered.lines().all(|line| !line.is_empty()));
+    }
+
+    #[cfg(feature = "otlp")]
+    #[test]
+    fn test_to_openmetrics_names_families_by_the_spec() {
+        let prometheus = "# HELP http_requests_total Requests served\n\
+# TYPE http_requests_total counter\n\
+http_requests_total{path=\"/\"} 3\n\
+\n\
+# TYPE jobs_done counter\n\
+jobs_done 2\n\
+\n\
+# TYPE legacy untyped\n\
+legacy 1\n\
+\n\
+# TYPE in_flight gauge\n\
+in_flight 0\n";
+
+        assert_eq!(
+            to_openmetrics(prometheus),
+            "# HELP http_requests Requests served\n\
+# TYPE http_requests counter\n\
+http_requests_total{path=\"/\"} 3\n\
+# TYPE jobs_done counter\n\
+jobs_done_total 2\n\
+# TYPE legacy unknown\n\
+legacy 1\n\
+# TYPE in_flight gauge\n\
+in_flight 0\n"
+        );
     }
 }
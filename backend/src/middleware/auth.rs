@@ -1,12 +1,98 @@
 use axum::extract::FromRequestParts;
-use axum::http::request::Parts;
+use axum::http::{header, request::Parts, HeaderMap};
 
 use crate::{
+    config::TokenTransport,
     error::AppError,
+    middleware::{server_timing::ServerTimings, tenant::RequestTenant},
+    repositories::UserRepositoryTrait,
     services::jwt::Claims,
+    tracing_config::add_span_context,
+    types::TenantId,
     AppState,
 };
 
+/// Cookie carrying the token when AUTH_TOKEN_TRANSPORT=cookie
+pub const AUTH_COOKIE: &str = "auth_token";
+
+/// `Set-Cookie` value for the auth cookie; an empty token with `max_age` 0 clears it
+pub fn auth_cookie(token: &str, max_age: u64, secure: bool) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        AUTH_COOKIE,
+        token,
+        max_age,
+        if secure { "; Secure" } else { "" }
+    )
+}
+
+/// Value of the named cookie, if the request carries one
+pub fn read_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(cookie, _)| *cookie == name)
+        .map(|(_, value)| value)
+}
+
+/// Header clients may send to tell apart installs sharing a user agent
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// Fingerprint of the calling client: a SHA-256 of its user agent and
+/// `X-Client-Id`, so the raw values never end up in the token
+pub fn client_fingerprint(headers: &HeaderMap) -> String {
+    use sha2::{Digest, Sha256};
+
+    let header = |name| {
+        headers
+            .get(name)
+            .map(|value| value.as_bytes())
+            .unwrap_or_default()
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(header(header::USER_AGENT.as_str()));
+    hasher.update([0]);
+    hasher.update(header(CLIENT_ID_HEADER));
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Fingerprint to bind newly issued tokens to; `None` unless
+/// JWT_BIND_FINGERPRINT is enabled
+#[derive(Debug, Clone)]
+pub struct ClientFingerprint(pub Option<String>);
+
+impl ClientFingerprint {
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for ClientFingerprint {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(ClientFingerprint(
+            state
+                .config
+                .jwt
+                .bind_fingerprint
+                .then(|| client_fingerprint(&parts.headers)),
+        ))
+    }
+}
+
 /// Extractor for authenticated user information
 /// Use this in your handlers to ensure the user is authenticated
 #[derive(Debug, Clone)]
@@ -15,6 +101,15 @@ pub struct AuthUser {
     pub user_id: String,
     pub email: String,
     pub username: String,
+    /// Tenant the token was issued in
+    pub tenant_id: TenantId,
+}
+
+impl AuthUser {
+    /// Key that ties per-user state (e.g. nonces) to this user and tenant
+    pub fn owner_key(&self) -> String {
+        format!("{}:{}", self.tenant_id, self.user_id)
+    }
 }
 
 impl From<Claims> for AuthUser {
@@ -23,6 +118,7 @@ impl From<Claims> for AuthUser {
             user_id: claims.sub,
             email: claims.email,
             username: claims.username,
+            tenant_id: claims.tenant,
         }
     }
 }
@@ -35,25 +131,74 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        // Extract authorization header
-        let auth_header = parts
-            .headers
-            .get("authorization")
-            .and_then(|h| h.to_str().ok())
-            .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
-
-        // Extract bearer token
-        let token = auth_header
-            .strip_prefix("Bearer ")
-            .ok_or_else(|| AppError::Unauthorized("Invalid authorization header format".to_string()))?;
-
-        // Verify token using JWT service from app state
-        let claims = state.jwt().verify_token(token)?;
-
-        Ok(claims.into())
+        let start = std::time::Instant::now();
+        let result = authenticate(parts, state);
+        ServerTimings::record_in(&parts.extensions, "auth", start.elapsed());
+        if let Ok(user) = &result {
+            // Tag the request span so every later log line names the caller
+            add_span_context("user.id", &user.user_id);
+            add_span_context("tenant.id", user.tenant_id.as_str());
+        }
+        result
     }
 }
 
+/// Find the token: the bearer header, or in cookie mode the auth cookie
+pub(crate) fn extract_token<'a>(headers: &'a HeaderMap, state: &AppState) -> Result<&'a str, AppError> {
+    if let Some(auth_header) = headers.get("authorization") {
+        return auth_header
+            .to_str()
+            .ok()
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                AppError::Unauthorized("Invalid authorization header format".to_string())
+            });
+    }
+
+    if state.config.jwt.transport == TokenTransport::Cookie {
+        if let Some(token) = read_cookie(headers, AUTH_COOKIE).filter(|t| !t.is_empty()) {
+            return Ok(token);
+        }
+    }
+
+    Err(AppError::Unauthorized("Missing authorization header".to_string()))
+}
+
+/// Verify the token and check it belongs to the request's tenant
+fn authenticate(parts: &Parts, state: &AppState) -> Result<AuthUser, AppError> {
+    let token = extract_token(&parts.headers, state)?;
+
+    // Verify token using JWT service from app state
+    let claims = state.jwt().verify_token(token)?;
+
+    // A bound token is only valid from the client it was issued to; unbound
+    // tokens (issued before binding was enabled) are refused too
+    if state.config.jwt.bind_fingerprint
+        && claims.fpt.as_deref() != Some(client_fingerprint(&parts.headers).as_str())
+    {
+        tracing::warn!("Rejected token presented by a different client");
+        return Err(AppError::Unauthorized(
+            "Token was issued to a different client".to_string(),
+        ));
+    }
+
+    // A token is only valid in the tenant it was issued for
+    if let Some(RequestTenant(tenant)) = parts.extensions.get::<RequestTenant>() {
+        if *tenant != claims.tenant {
+            tracing::warn!(
+                request_tenant = %tenant,
+                token_tenant = %claims.tenant,
+                "Rejected token issued for a different tenant"
+            );
+            return Err(AppError::Unauthorized(
+                "Token was issued for a different tenant".to_string(),
+            ));
+        }
+    }
+
+    Ok(claims.into())
+}
+
 /// Optional authentication extractor
 /// Use this when authentication is optional
 #[derive(Debug, Clone)]
@@ -74,3 +219,37 @@ impl FromRequestParts<AppState> for OptionalAuthUser {
         }
     }
 }
+
+/// Extractor for authenticated users with the `admin` role
+///
+/// The role is read from the database rather than the token, so demoting a
+/// user takes effect immediately.
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        let user_id = uuid::Uuid::parse_str(&auth_user.user_id)
+            .map_err(|_| AppError::Unauthorized("Invalid user id in token".to_string()))?;
+
+        let user = state
+            .user_repo()
+            .find_by_id(&auth_user.tenant_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User no longer exists".to_string()))?;
+
+        if !user.is_admin() {
+            return Err(AppError::Forbidden("Admin role required".to_string()));
+        }
+
+        Ok(AdminUser(auth_user))
+    }
+}
@@ -0,0 +1,215 @@
+//! `Cache-Control` and `ETag` headers
+//!
+//! Responses that only change on deploy (the version and the OpenAPI
+//! document) are cacheable for `CACHE_MAX_AGE` seconds and carry an `ETag`
+//! hashed from the body, so a client revalidating with `If-None-Match` gets
+//! an empty `304`. Every other response that hasn't chosen its own caching
+//! is marked `no-store`, since it may be per-user or change at any moment.
+//!
+//! List handlers can do better than hashing a body they'd have to build
+//! first: [`collection_etag`] derives a tag from the row count and latest
+//! `updated_at`, checked with [`not_modified`] before the page is loaded.
+//!
+//! Updates go the other way: [`if_unmodified_since`] reads the client's
+//! `If-Unmodified-Since`, and [`modified_since`] tells whether the row changed
+//! after it, in which case the update is refused with `412`.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Paths whose GET responses are cacheable
+const CACHEABLE_PATHS: &[&str] = &["/api/v1/version", "/api-docs/openapi.json"];
+
+/// Create a cache headers middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+pub fn cache_control_layer(
+    max_age: u64,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            let cacheable =
+                req.method() == Method::GET && CACHEABLE_PATHS.contains(&req.uri().path());
+            let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+
+            let response = next.run(req).await;
+            if cacheable && response.status() == StatusCode::OK {
+                return cacheable_response(response, max_age, if_none_match.as_ref()).await;
+            }
+
+            let mut response = response;
+            response
+                .headers_mut()
+                .entry(header::CACHE_CONTROL)
+                .or_insert(HeaderValue::from_static("no-store"));
+            response
+        })
+    }
+}
+
+/// Add `Cache-Control` and `ETag`, answering `304` when the client's copy is current
+async fn cacheable_response(
+    response: Response,
+    max_age: u64,
+    if_none_match: Option<&HeaderValue>,
+) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to buffer cacheable response");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let etag = etag_for(&bytes);
+    let cache_control = HeaderValue::try_from(format!("public, max-age={}", max_age))
+        .expect("cache-control value is ASCII");
+
+    if if_none_match.is_some_and(|value| etag_matches(value, &etag)) {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ETAG, etag);
+        headers.insert(header::CACHE_CONTROL, cache_control);
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    parts.headers.insert(header::ETAG, etag);
+    parts.headers.insert(header::CACHE_CONTROL, cache_control);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Strong ETag: the first 16 bytes of the body's SHA-256, hex encoded
+fn etag_for(body: &[u8]) -> HeaderValue {
+    use sha2::{Digest, Sha256};
+
+    let hex: String = Sha256::digest(body)[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    HeaderValue::try_from(format!("\"{}\"", hex)).expect("hex is a valid header value")
+}
+
+/// Cache-Control for responses validated with a collection ETag: private to
+/// the caller, and revalidated on every use
+pub const REVALIDATE: &str = "private, no-cache";
+
+/// ETag for a listing, from the collection's size and latest change
+///
+/// Any insert, delete or update moves one of the two. `variant` separates
+/// different views of the same rows (page, sort order, tenant).
+pub fn collection_etag(
+    count: i64,
+    last_updated: Option<chrono::NaiveDateTime>,
+    variant: &str,
+) -> HeaderValue {
+    let last_updated = last_updated
+        .map(|at| at.and_utc().timestamp_micros())
+        .unwrap_or_default();
+    etag_for(format!("{}:{}:{}", count, last_updated, variant).as_bytes())
+}
+
+/// Whether the request's `If-None-Match` names `etag`
+pub fn not_modified(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| etag_matches(value, etag))
+}
+
+/// Empty `304` carrying the current `etag`
+pub fn not_modified_response(etag: HeaderValue) -> Response {
+    (
+        StatusCode::NOT_MODIFIED,
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, HeaderValue::from_static(REVALIDATE)),
+        ],
+    )
+        .into_response()
+}
+
+/// `If-None-Match` comparison (RFC 9110 weak comparison)
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    let etag = etag.to_str().unwrap_or_default();
+    value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// The request's `If-Unmodified-Since` date
+///
+/// `None` when absent or not an HTTP date (IMF-fixdate, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`); RFC 9110 has an invalid value ignored.
+pub fn if_unmodified_since(headers: &HeaderMap) -> Option<chrono::DateTime<chrono::Utc>> {
+    let value = headers.get(header::IF_UNMODIFIED_SINCE)?.to_str().ok()?;
+    chrono::DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.with_timezone(&chrono::Utc))
+}
+
+/// Whether a row last updated at `updated_at` changed after `since`
+///
+/// HTTP dates have whole-second precision, so `updated_at` is truncated to
+/// the second: a client quoting a row's own timestamp isn't refused.
+pub fn modified_since(updated_at: chrono::NaiveDateTime, since: chrono::DateTime<chrono::Utc>) -> bool {
+    updated_at.and_utc().timestamp() > since.timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matching() {
+        let etag = etag_for(b"{}");
+        let tag = etag.to_str().unwrap().to_string();
+
+        assert!(etag_matches(&HeaderValue::from_str(&tag).unwrap(), &etag));
+        assert!(etag_matches(&HeaderValue::from_str(&format!("W/{}", tag)).unwrap(), &etag));
+        assert!(etag_matches(&HeaderValue::from_str(&format!("\"other\", {}", tag)).unwrap(), &etag));
+        assert!(etag_matches(&HeaderValue::from_static("*"), &etag));
+        assert!(!etag_matches(&HeaderValue::from_static("\"other\""), &etag));
+    }
+
+    #[test]
+    fn test_collection_etag_tracks_count_change_and_variant() {
+        let at = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let etag = collection_etag(3, Some(at), "page=1");
+
+        assert_eq!(etag, collection_etag(3, Some(at), "page=1"));
+        assert_ne!(etag, collection_etag(4, Some(at), "page=1"));
+        assert_ne!(etag, collection_etag(3, Some(at + chrono::Duration::seconds(1)), "page=1"));
+        assert_ne!(etag, collection_etag(3, Some(at), "page=2"));
+    }
+
+    #[test]
+    fn test_if_unmodified_since_parses_http_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(if_unmodified_since(&headers), None);
+
+        headers.insert(header::IF_UNMODIFIED_SINCE, HeaderValue::from_static("yesterday"));
+        assert_eq!(if_unmodified_since(&headers), None);
+
+        headers.insert(
+            header::IF_UNMODIFIED_SINCE,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        let since = if_unmodified_since(&headers).unwrap();
+        assert_eq!(since.timestamp(), 784_111_777);
+
+        let at = |secs: i64, micros: u32| {
+            chrono::DateTime::from_timestamp(secs, micros * 1000).unwrap().naive_utc()
+        };
+        assert!(!modified_since(at(784_111_777, 500_000), since));
+        assert!(modified_since(at(784_111_778, 0), since));
+    }
+}
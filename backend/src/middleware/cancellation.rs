@@ -0,0 +1,124 @@
+//! Client disconnect detection
+//!
+//! When a client goes away mid-request, hyper drops the request future: the
+//! handler stops at its next `.await` and nothing after it runs. Work it
+//! already handed off (`tokio::spawn`, `spawn_blocking`) keeps going, though,
+//! and anything committed stays committed. A handler that spawns expensive
+//! optional work takes a [`Cancellation`] and gives it to the task, which can
+//! then skip the work once nobody is waiting for the result:
+//!
+//! ```no_run
+//! # use axum::http::StatusCode;
+//! # use backend::middleware::cancellation::Cancellation;
+//! # async fn warm_cache() {}
+//! pub async fn handler(cancellation: Cancellation) -> StatusCode {
+//!     tokio::spawn(async move {
+//!         tokio::select! {
+//!             _ = cancellation.cancelled() => tracing::debug!("Client gone, skipping warm-up"),
+//!             _ = warm_cache() => {}
+//!         }
+//!     });
+//!     StatusCode::ACCEPTED
+//! }
+//! ```
+//!
+//! The signal is owned by the logging layer, which fires it (and logs
+//! "Request cancelled" instead of "Request completed") when the request is
+//! dropped before a response was produced. That includes requests cut off by
+//! REQUEST_TIMEOUT, since the timeout layer drops the inner future too.
+use axum::{extract::FromRequestParts, http::request::Parts};
+use tokio::sync::watch;
+
+/// Fires the paired [`Cancellation`] when dropped, unless disarmed first
+#[derive(Debug)]
+pub struct CancelOnDrop {
+    tx: Option<watch::Sender<bool>>,
+}
+
+impl CancelOnDrop {
+    /// The request finished normally; its [`Cancellation`] never fires
+    pub fn disarm(mut self) {
+        self.tx = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(true);
+        }
+    }
+}
+
+/// Whether the client stopped waiting for the current request
+///
+/// Outside the logging layer (e.g. a bare router in a test) this never fires.
+#[derive(Debug, Clone)]
+pub struct Cancellation {
+    rx: watch::Receiver<bool>,
+}
+
+impl Cancellation {
+    pub fn is_cancelled(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolve once the request is cancelled; pends forever if it completes
+    pub async fn cancelled(&self) {
+        let mut rx = self.rx.clone();
+        if rx.wait_for(|cancelled| *cancelled).await.is_err() {
+            // Disarmed: the request completed, so this never fires
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Default for Cancellation {
+    fn default() -> Self {
+        let (_tx, rx) = watch::channel(false);
+        Self { rx }
+    }
+}
+
+/// A cancellation signal for one request
+pub fn channel() -> (CancelOnDrop, Cancellation) {
+    let (tx, rx) = watch::channel(false);
+    (CancelOnDrop { tx: Some(tx) }, Cancellation { rx })
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Cancellation {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<Cancellation>().cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_dropping_the_signal_cancels() {
+        let (signal, cancellation) = channel();
+        assert!(!cancellation.is_cancelled());
+
+        drop(signal);
+        assert!(cancellation.is_cancelled());
+        tokio::time::timeout(Duration::from_secs(1), cancellation.cancelled())
+            .await
+            .expect("cancelled() should resolve");
+    }
+
+    #[tokio::test]
+    async fn test_disarmed_signal_never_cancels() {
+        let (signal, cancellation) = channel();
+        signal.disarm();
+
+        assert!(!cancellation.is_cancelled());
+        let waited = tokio::time::timeout(Duration::from_millis(20), cancellation.cancelled()).await;
+        assert!(waited.is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! Request/response body capture for single requests
+//!
+//! Development aid (debug builds only): a request sent with
+//! `X-Debug-Capture: 1` has its request and response recorded in memory,
+//! headers and bodies included, and the last [`DEFAULT_CAPTURE_CAPACITY`]
+//! captures are listed at `GET /dev/captures`. Nothing else is recorded, so
+//! this is a way to look at one request in detail without turning on body
+//! logging for all traffic.
+//!
+//! Credentials are redacted before storing: the `Authorization`, `Cookie`,
+//! `Set-Cookie` and `X-Api-Key` headers, and JSON fields, form fields and
+//! query parameters whose name mentions a password, token or secret. Bodies
+//! are redacted first and then cut at [`MAX_CAPTURED_BODY`]; a body that is
+//! neither JSON nor a form can't be redacted, so it is not stored at all.
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    http::{header, HeaderMap, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::{error::AppError, middleware::request_id::RequestId, routes::DEFAULT_BODY_LIMIT};
+
+/// Header that opts a request into capture (`1` or `true`)
+pub const CAPTURE_HEADER: &str = "x-debug-capture";
+
+/// Captures kept before the oldest is dropped
+pub const DEFAULT_CAPTURE_CAPACITY: usize = 20;
+
+/// Bytes of each body kept in a capture
+pub const MAX_CAPTURED_BODY: usize = 16 * 1024;
+
+const REDACTED: &str = "[REDACTED]";
+
+const SECRET_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie", "x-api-key"];
+
+const SECRET_FIELD_MARKERS: &[&str] = &["password", "token", "secret"];
+
+/// One captured exchange
+#[derive(Debug, Clone, Serialize)]
+pub struct Capture {
+    pub request_id: Option<String>,
+    pub at: DateTime<Utc>,
+    pub method: String,
+    pub uri: String,
+    pub status: u16,
+    pub duration_ms: u64,
+    pub request: CapturedMessage,
+    pub response: CapturedMessage,
+}
+
+/// Headers and body of one side of the exchange
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedMessage {
+    pub headers: BTreeMap<String, String>,
+    /// Parsed JSON when the body is JSON, text for forms and truncated JSON,
+    /// a placeholder for anything else, `null` when empty
+    pub body: Value,
+    /// Whether the body was cut at `MAX_CAPTURED_BODY`
+    pub truncated: bool,
+}
+
+impl CapturedMessage {
+    fn new(headers: &HeaderMap, body: &[u8]) -> Self {
+        let (body, truncated) = redacted_body(headers, body);
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.as_str().to_string(), value)
+            })
+            .collect();
+
+        Self {
+            headers,
+            body,
+            truncated,
+        }
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_FIELD_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// The body with credentials redacted, and whether it was then truncated
+fn redacted_body(headers: &HeaderMap, body: &[u8]) -> (Value, bool) {
+    if body.is_empty() {
+        return (Value::Null, false);
+    }
+
+    if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+        redact_json(&mut json);
+        let text = json.to_string();
+        if text.len() <= MAX_CAPTURED_BODY {
+            return (json, false);
+        }
+        return (Value::String(truncate(text)), true);
+    }
+
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+    if is_form {
+        let text = redact_pairs(url::form_urlencoded::parse(body));
+        let truncated = text.len() > MAX_CAPTURED_BODY;
+        return (Value::String(truncate(text)), truncated);
+    }
+
+    let placeholder = format!("{} ({} bytes, not JSON or form data)", REDACTED, body.len());
+    (Value::String(placeholder), false)
+}
+
+/// At most `MAX_CAPTURED_BODY` bytes of `text`, cut on a char boundary
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_CAPTURED_BODY {
+        let mut end = MAX_CAPTURED_BODY;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// Re-encode urlencoded pairs with credential-looking values replaced
+fn redact_pairs<'a>(pairs: impl Iterator<Item = (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>) -> String {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (name, value) in pairs {
+        let value = if is_secret_name(&name) { REDACTED } else { &value };
+        serializer.append_pair(&name, value);
+    }
+    serializer.finish()
+}
+
+/// The URI with credential-looking query parameters redacted
+fn redacted_uri(uri: &Uri) -> String {
+    match uri.query() {
+        Some(query) => format!(
+            "{}?{}",
+            uri.path(),
+            redact_pairs(url::form_urlencoded::parse(query.as_bytes()))
+        ),
+        None => uri.to_string(),
+    }
+}
+
+/// Replace values of credential-looking fields, at any depth
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if is_secret_name(name) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// The most recent captures, shared between the layer and `/dev/captures`
+#[derive(Debug, Clone)]
+pub struct CaptureStore {
+    captures: Arc<Mutex<VecDeque<Capture>>>,
+    capacity: usize,
+}
+
+impl Default for CaptureStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPTURE_CAPACITY)
+    }
+}
+
+impl CaptureStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            captures: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn push(&self, capture: Capture) {
+        let mut captures = self.captures.lock().unwrap_or_else(|e| e.into_inner());
+        if captures.len() == self.capacity {
+            captures.pop_front();
+        }
+        captures.push_back(capture);
+    }
+
+    /// Captures kept so far, newest first
+    pub fn list(&self) -> Vec<Capture> {
+        let captures = self.captures.lock().unwrap_or_else(|e| e.into_inner());
+        captures.iter().rev().cloned().collect()
+    }
+}
+
+fn wants_capture(headers: &HeaderMap) -> bool {
+    headers
+        .get(CAPTURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
+/// Create a body capture middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// Requests without the capture header pass through untouched; captured
+/// ones have both bodies buffered, so don't use it on streaming responses
+/// you want to see arrive incrementally.
+pub fn capture_layer(
+    store: CaptureStore,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        let store = store.clone();
+        Box::pin(async move {
+            if !wants_capture(req.headers()) {
+                return next.run(req).await;
+            }
+
+            let start = Instant::now();
+            let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+            let method = req.method().to_string();
+            let uri = redacted_uri(req.uri());
+
+            let (parts, body) = req.into_parts();
+            let Ok(request_body) = axum::body::to_bytes(body, DEFAULT_BODY_LIMIT).await else {
+                return AppError::PayloadTooLarge("Request body too large".to_string())
+                    .into_response();
+            };
+            let request = CapturedMessage::new(&parts.headers, &request_body);
+
+            let response = next
+                .run(Request::from_parts(parts, Body::from(request_body)))
+                .await;
+
+            let (parts, body) = response.into_parts();
+            let response_body = match axum::body::to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Response body failed while capturing");
+                    Bytes::new()
+                }
+            };
+
+            store.push(Capture {
+                request_id,
+                at: Utc::now(),
+                method,
+                uri,
+                status: parts.status.as_u16(),
+                duration_ms: start.elapsed().as_millis() as u64,
+                request,
+                response: CapturedMessage::new(&parts.headers, &response_body),
+            });
+            tracing::debug!("Request captured for /dev/captures");
+
+            let mut response = Response::from_parts(parts, Body::from(response_body));
+            response.headers_mut().remove(header::TRANSFER_ENCODING);
+            response
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_credentials_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer abc".parse().unwrap());
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        let body = json!({
+            "email": "a@example.com",
+            "password": "hunter2",
+            "nested": [{ "refreshToken": "xyz" }]
+        });
+
+        let message = CapturedMessage::new(&headers, body.to_string().as_bytes());
+
+        assert_eq!(message.headers["authorization"], REDACTED);
+        assert_eq!(message.headers["content-type"], "application/json");
+        assert_eq!(message.body["email"], "a@example.com");
+        assert_eq!(message.body["password"], REDACTED);
+        assert_eq!(message.body["nested"][0]["refreshToken"], REDACTED);
+    }
+
+    #[test]
+    fn test_large_bodies_are_redacted_then_truncated() {
+        let body = json!({ "password": "hunter2", "padding": "a".repeat(MAX_CAPTURED_BODY) });
+
+        let message = CapturedMessage::new(&HeaderMap::new(), body.to_string().as_bytes());
+
+        assert!(message.truncated);
+        let text = message.body.as_str().unwrap();
+        assert_eq!(text.len(), MAX_CAPTURED_BODY);
+        assert!(!text.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_form_bodies_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/x-www-form-urlencoded".parse().unwrap());
+
+        let message = CapturedMessage::new(&headers, b"email=a%40example.com&password=hunter2");
+
+        assert_eq!(message.body, "email=a%40example.com&password=%5BREDACTED%5D");
+    }
+
+    #[test]
+    fn test_bodies_that_cant_be_redacted_are_masked() {
+        let message = CapturedMessage::new(&HeaderMap::new(), b"password=hunter2");
+
+        let text = message.body.as_str().unwrap();
+        assert!(text.starts_with(REDACTED));
+        assert!(!text.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_query_credentials_are_redacted() {
+        let uri: Uri = "/api/v1/auth/reset?token=abc&lang=en".parse().unwrap();
+
+        assert_eq!(redacted_uri(&uri), "/api/v1/auth/reset?token=%5BREDACTED%5D&lang=en");
+        assert_eq!(redacted_uri(&"/api/v1/users".parse().unwrap()), "/api/v1/users");
+    }
+
+    #[test]
+    fn test_store_keeps_the_newest_captures() {
+        let store = CaptureStore::new(2);
+        for status in [200, 201, 202] {
+            store.push(Capture {
+                request_id: None,
+                at: Utc::now(),
+                method: "GET".to_string(),
+                uri: "/".to_string(),
+                status,
+                duration_ms: 0,
+                request: CapturedMessage::new(&HeaderMap::new(), b""),
+                response: CapturedMessage::new(&HeaderMap::new(), b""),
+            });
+        }
+
+        let statuses: Vec<u16> = store.list().iter().map(|c| c.status).collect();
+        assert_eq!(statuses, [202, 201]);
+    }
+}
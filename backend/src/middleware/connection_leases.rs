@@ -0,0 +1,145 @@
+//! Per-request count of pooled DB connections (debug builds)
+//!
+//! A handler that holds one connection while asking for another (a helper
+//! that calls `get_connection` again, a repository call inside a
+//! transaction) works fine on a large pool and hangs forever on a pool of
+//! one. [`connection_lease_layer`] makes a [`ConnectionLeases`] counter the
+//! current task's, [`crate::db::get_connection`] takes a [`Lease`] on it for
+//! as long as the connection is held, and a warning is logged the moment a
+//! request holds more than `DB_MAX_CONNECTIONS_PER_REQUEST` at once.
+//!
+//! Release builds skip the layer, so the check costs nothing in production.
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+tokio::task_local! {
+    static CURRENT: ConnectionLeases;
+}
+
+/// Connections checked out by one request
+#[derive(Debug, Clone)]
+pub struct ConnectionLeases(Arc<LeaseState>);
+
+#[derive(Debug)]
+struct LeaseState {
+    max: usize,
+    held: AtomicUsize,
+    exceeded: AtomicUsize,
+}
+
+impl ConnectionLeases {
+    /// Warn whenever more than `max` connections are held at once
+    pub fn new(max: usize) -> Self {
+        Self(Arc::new(LeaseState {
+            max,
+            held: AtomicUsize::new(0),
+            exceeded: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Connections currently held
+    pub fn held(&self) -> usize {
+        self.0.held.load(Ordering::Relaxed)
+    }
+
+    /// Checkouts that took the request over the limit
+    pub fn exceeded(&self) -> usize {
+        self.0.exceeded.load(Ordering::Relaxed)
+    }
+
+    /// Run `future` with this counter as the current task's
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    fn lease(&self) -> Lease {
+        let held = self.0.held.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.0.max > 0 && held > self.0.max {
+            self.0.exceeded.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                held = held,
+                max = self.0.max,
+                "Request holds more database connections than DB_MAX_CONNECTIONS_PER_REQUEST; \
+                 this deadlocks once the pool is exhausted"
+            );
+        }
+        Lease(self.clone())
+    }
+}
+
+/// One checked-out connection; released on drop
+#[derive(Debug)]
+pub struct Lease(ConnectionLeases);
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        let ConnectionLeases(state) = &self.0;
+        state.held.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Count a connection against the current task's request
+///
+/// `None` outside [`connection_lease_layer`] or in release builds.
+pub fn acquire() -> Option<Lease> {
+    CURRENT.try_with(ConnectionLeases::lease).ok()
+}
+
+/// Create a connection-counting middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// Requests pass straight through in release builds or when `max` is 0.
+pub fn connection_lease_layer(
+    max: usize,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            if max == 0 || !cfg!(debug_assertions) {
+                return next.run(req).await;
+            }
+            ConnectionLeases::new(max).scope(next.run(req)).await
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_two_connections_in_one_scope_exceed_a_limit_of_one() {
+        let leases = ConnectionLeases::new(1);
+        leases
+            .clone()
+            .scope(async {
+                let _first = acquire();
+                let _second = acquire();
+            })
+            .await;
+
+        assert_eq!(leases.exceeded(), 1);
+        assert_eq!(leases.held(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_released_connections_do_not_count() {
+        let leases = ConnectionLeases::new(1);
+        leases
+            .clone()
+            .scope(async {
+                drop(acquire());
+                let _second = acquire();
+            })
+            .await;
+
+        assert_eq!(leases.exceeded(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_outside_scope_is_untracked() {
+        assert!(acquire().is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Request body media type check (ALLOWED_CONTENT_TYPES)
+//!
+//! Handlers deserialize JSON, so a body sent as `text/plain` or a form would
+//! otherwise travel all the way to the extractor before failing. Requests
+//! that carry a body must declare one of the allowed media types, or they are
+//! answered with `415 Unsupported Media Type` in the standard error shape.
+//! Bodiless requests (GET, or POST without content) are not checked.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::error::AppError;
+
+/// Create a content type checking middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// Entries are media types (`application/json`) or wildcards
+/// (`multipart/*`); `application/json` also admits `application/*+json`.
+/// An empty list disables the check.
+pub fn content_type_layer(
+    allowed: Vec<String>,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    let allowed: Arc<[String]> = allowed.iter().map(|t| t.to_ascii_lowercase()).collect();
+    move |req: Request, next: Next| {
+        let allowed = allowed.clone();
+        Box::pin(async move {
+            if allowed.is_empty() || !has_body(req.headers()) {
+                return next.run(req).await;
+            }
+
+            let media_type = req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(essence);
+            match media_type {
+                Some(media_type) if is_allowed(&allowed, &media_type) => next.run(req).await,
+                Some(media_type) => AppError::UnsupportedMediaType(format!(
+                    "Unsupported Content-Type '{}', expected {}",
+                    media_type,
+                    allowed.join(" or ")
+                ))
+                .into_response(),
+                None => AppError::UnsupportedMediaType(format!(
+                    "Missing Content-Type, expected {}",
+                    allowed.join(" or ")
+                ))
+                .into_response(),
+            }
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+/// Whether the request announces a non-empty body
+fn has_body(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::TRANSFER_ENCODING) {
+        return true;
+    }
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .is_some_and(|length| length > 0)
+}
+
+/// The media type without parameters, lowercase (`application/json`)
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn is_allowed(allowed: &[String], media_type: &str) -> bool {
+    allowed.iter().any(|entry| {
+        if let Some(prefix) = entry.strip_suffix("/*") {
+            return media_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == prefix);
+        }
+        entry == media_type
+            || (entry == "application/json"
+                && media_type.starts_with("application/")
+                && media_type.ends_with("+json"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parameters_and_case_are_ignored() {
+        let allowed = allowed(&["application/json"]);
+        assert!(is_allowed(&allowed, &essence("Application/JSON; charset=utf-8")));
+        assert!(!is_allowed(&allowed, &essence("text/plain; charset=utf-8")));
+    }
+
+    #[test]
+    fn test_json_suffix_and_wildcards() {
+        let allowed = allowed(&["application/json", "multipart/*"]);
+        assert!(is_allowed(&allowed, "application/merge-patch+json"));
+        assert!(is_allowed(&allowed, "multipart/form-data"));
+        assert!(!is_allowed(&allowed, "application/x-www-form-urlencoded"));
+    }
+
+    #[test]
+    fn test_only_requests_with_a_body_are_checked() {
+        let mut headers = HeaderMap::new();
+        assert!(!has_body(&headers));
+        headers.insert(header::CONTENT_LENGTH, "0".parse().unwrap());
+        assert!(!has_body(&headers));
+        headers.insert(header::CONTENT_LENGTH, "12".parse().unwrap());
+        assert!(has_body(&headers));
+    }
+}
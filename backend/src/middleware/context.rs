@@ -0,0 +1,74 @@
+//! Request context for services
+//!
+//! [`request_context_layer`] collects who is calling (request ID, client IP,
+//! user agent) into a [`RequestContext`] extension. Handlers take it as an
+//! extractor and pass it to service calls, so services can log and audit
+//! with it without growing a parameter per field. The tenant is not part of
+//! it: services already take it explicitly, since for authenticated calls it
+//! comes from the token rather than the request.
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request},
+    http::{header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::middleware::{rate_limit::client_ip, request_id::RequestId};
+
+/// Who made the request being served
+///
+/// `Default` (all `None`) stands in for work that doesn't come from a
+/// request, such as jobs and CLI commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestContext {
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl RequestContext {
+    /// Build the context for `req`; the request ID must already be assigned
+    pub fn from_request(req: &Request, trust_proxy: bool) -> Self {
+        Self {
+            request_id: req.extensions().get::<RequestId>().map(|id| id.0.clone()),
+            ip: client_ip(req, trust_proxy),
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|ua| ua.to_str().ok())
+                .map(str::to_string),
+        }
+    }
+}
+
+/// Extract the context stored by [`request_context_layer`]
+///
+/// Outside the layer (e.g. a bare router in a test) this is an empty context
+/// rather than a rejection.
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestContext {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<RequestContext>().cloned().unwrap_or_default())
+    }
+}
+
+/// Create a request context middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// Must run inside the request ID layer. `trust_proxy` decides whether the
+/// client IP is read from X-Forwarded-For/X-Real-IP.
+pub fn request_context_layer(
+    trust_proxy: bool,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |mut req: Request, next: Next| {
+        Box::pin(async move {
+            let context = RequestContext::from_request(&req, trust_proxy);
+            req.extensions_mut().insert(context);
+            next.run(req).await
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
@@ -0,0 +1,208 @@
+//! JSON field naming convention (API_FIELD_CASE)
+//!
+//! DTOs are declared in snake_case. With `API_FIELD_CASE=camel`, JSON bodies
+//! under `/api/` are rewritten at the edge: request keys from camelCase to
+//! snake_case before extraction, response keys from snake_case to camelCase
+//! after the handler, error bodies included. Keys already in the target case
+//! are left alone, so snake_case requests keep working. Other content types
+//! (CSV, NDJSON streams) and paths outside `/api/` pass through untouched.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Map, Value};
+
+use crate::{config::FieldCase, error::AppError, routes::DEFAULT_BODY_LIMIT};
+
+/// Create a field renaming middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// `FieldCase::Snake` passes everything through.
+pub fn field_case_layer(
+    case: FieldCase,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            if case == FieldCase::Snake || !req.uri().path().starts_with("/api/") {
+                return next.run(req).await;
+            }
+
+            let req = if is_json(req.headers()) {
+                let (mut parts, body) = req.into_parts();
+                let Ok(bytes) = axum::body::to_bytes(body, DEFAULT_BODY_LIMIT).await else {
+                    return AppError::PayloadTooLarge("Request body too large".to_string())
+                        .into_response();
+                };
+                parts.headers.remove(header::CONTENT_LENGTH);
+                Request::from_parts(parts, Body::from(rename_body(bytes, camel_to_snake)))
+            } else {
+                req
+            };
+
+            let response = next.run(req).await;
+            if !is_json(response.headers()) {
+                return response;
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to buffer response for field renaming");
+                    return AppError::InternalServerError {
+                        message: "Failed to read response body".to_string(),
+                        source: None,
+                    }
+                    .into_response();
+                }
+            };
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(rename_body(bytes, snake_to_camel)))
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+/// `application/json` or an `application/*+json` type
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+        .is_some_and(|media_type| {
+            media_type == "application/json"
+                || (media_type.starts_with("application/") && media_type.ends_with("+json"))
+        })
+}
+
+/// Rename every object key in `bytes`; bodies that aren't valid JSON are
+/// returned as is, so the usual parse errors still surface
+fn rename_body(bytes: axum::body::Bytes, rename: fn(&str) -> String) -> axum::body::Bytes {
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(value) => serde_json::to_vec(&rename_keys(value, rename))
+            .map(Into::into)
+            .unwrap_or(bytes),
+        Err(_) => bytes,
+    }
+}
+
+fn rename_keys(value: Value, rename: fn(&str) -> String) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (rename(&key), rename_keys(value, rename)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|item| rename_keys(item, rename)).collect())
+        }
+        other => other,
+    }
+}
+
+/// `expires_at` → `expiresAt`; leading underscores are kept
+fn snake_to_camel(key: &str) -> String {
+    let trimmed = key.trim_start_matches('_');
+    let mut camel = key[..key.len() - trimmed.len()].to_string();
+    let mut upper = false;
+    for c in trimmed.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// `expiresAt` → `expires_at`
+fn camel_to_snake(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 4);
+    for (i, c) in key.char_indices() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::dto::ChangePasswordRequestDto;
+    use axum::{routing::post, Json, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_dto_round_trips_in_camel_case() {
+        let app = Router::new()
+            .route(
+                "/api/v1/echo",
+                post(|Json(dto): Json<ChangePasswordRequestDto>| async move {
+                    Json(json!({
+                        "current_password": dto.current_password,
+                        "new_password": dto.new_password,
+                    }))
+                }),
+            )
+            .layer(axum::middleware::from_fn(field_case_layer(FieldCase::Camel)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/echo")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({ "currentPassword": "old", "newPassword": "new-secret" }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json, json!({ "currentPassword": "old", "newPassword": "new-secret" }));
+    }
+
+    #[test]
+    fn test_key_conversions() {
+        assert_eq!(snake_to_camel("expires_at"), "expiresAt");
+        assert_eq!(snake_to_camel("email"), "email");
+        assert_eq!(snake_to_camel("_private_field"), "_privateField");
+        assert_eq!(camel_to_snake("expiresAt"), "expires_at");
+        assert_eq!(camel_to_snake("already_snake"), "already_snake");
+        assert_eq!(camel_to_snake(&snake_to_camel("last_login_at")), "last_login_at");
+    }
+
+    #[test]
+    fn test_nested_keys_are_renamed_but_values_are_not() {
+        let value = json!({
+            "error_code": "VALIDATION_ERROR",
+            "details": [{ "field_name": "user_name" }]
+        });
+
+        assert_eq!(
+            rename_keys(value, snake_to_camel),
+            json!({
+                "errorCode": "VALIDATION_ERROR",
+                "details": [{ "fieldName": "user_name" }]
+            })
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Host header validation
+//!
+//! Rejects requests whose `Host` isn't in the configured allowlist, so a
+//! spoofed header can't leak into absolute URLs (e.g. password-reset links).
+//! Handlers that need to build absolute URLs should use the [`ValidatedHost`]
+//! extension instead of reading the header themselves.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::error::AppError;
+
+/// Allowlist of accepted hosts
+#[derive(Clone, Debug)]
+pub struct AllowedHosts {
+    patterns: Arc<Vec<String>>,
+}
+
+impl AllowedHosts {
+    /// Create an allowlist from config patterns
+    ///
+    /// Supported patterns:
+    /// - `*` matches any host
+    /// - `*.example.com` matches subdomains of example.com
+    /// - `.example.com` matches example.com and its subdomains
+    /// - anything else must match exactly (case-insensitive, port ignored)
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: Arc::new(patterns.iter().map(|p| p.trim().to_lowercase()).collect()),
+        }
+    }
+
+    /// Whether every host is accepted
+    pub fn allows_any(&self) -> bool {
+        self.patterns.iter().any(|p| p == "*")
+    }
+
+    /// Check a host (with or without port) against the allowlist
+    pub fn is_allowed(&self, host: &str) -> bool {
+        if self.allows_any() {
+            return true;
+        }
+
+        let host = strip_port(host).to_lowercase();
+        self.patterns.iter().any(|pattern| {
+            if let Some(suffix) = pattern.strip_prefix("*.") {
+                host.ends_with(&format!(".{}", suffix))
+            } else if let Some(suffix) = pattern.strip_prefix('.') {
+                host == suffix || host.ends_with(pattern.as_str())
+            } else {
+                host == *pattern
+            }
+        })
+    }
+}
+
+/// Host of the current request after validation
+///
+/// Inserted into request extensions by [`host_validation_layer`].
+#[derive(Clone, Debug)]
+pub struct ValidatedHost {
+    /// Host as sent by the client, including the port if present
+    pub host: String,
+    pub scheme: String,
+}
+
+impl ValidatedHost {
+    pub fn as_str(&self) -> &str {
+        &self.host
+    }
+
+    /// Build an absolute URL for `path` on this host
+    pub fn absolute_url(&self, path: &str) -> String {
+        format!("{}://{}/{}", self.scheme, self.host, path.trim_start_matches('/'))
+    }
+}
+
+/// Remove a trailing `:port`, keeping bracketed IPv6 literals intact
+pub(crate) fn strip_port(host: &str) -> &str {
+    if let Some(end) = host.strip_prefix('[').and_then(|rest| rest.find(']')) {
+        return &host[..end + 2];
+    }
+    host.rsplit_once(':').map_or(host, |(name, _)| name)
+}
+
+/// Host from the `Host` header, or the URI authority for HTTP/2 requests
+pub(crate) fn request_host(req: &Request) -> Option<String> {
+    req.headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .or_else(|| req.uri().authority().map(|a| a.to_string()))
+}
+
+/// Create a host validation middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// `trust_proxy` lets `X-Forwarded-Proto` decide the scheme of generated URLs;
+/// otherwise it is https when the server terminates TLS itself (`tls`).
+pub fn host_validation_layer(
+    allowed: AllowedHosts,
+    trust_proxy: bool,
+    tls: bool,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |mut req: Request, next: Next| {
+        let allowed = allowed.clone();
+        Box::pin(async move {
+            let host = request_host(&req);
+
+            match &host {
+                Some(host) if allowed.is_allowed(host) => {}
+                // Missing Host is only tolerated when any host is accepted
+                None if allowed.allows_any() => {}
+                _ => {
+                    tracing::warn!(host = ?host, "Rejected request with disallowed Host header");
+                    return AppError::BadRequest("Invalid Host header".to_string()).into_response();
+                }
+            }
+
+            if let Some(host) = host {
+                let scheme = trust_proxy
+                    .then(|| req.headers().get("x-forwarded-proto"))
+                    .flatten()
+                    .and_then(|v| v.to_str().ok())
+                    .filter(|s| *s == "http" || *s == "https")
+                    .unwrap_or(if tls { "https" } else { "http" })
+                    .to_string();
+                req.extensions_mut().insert(ValidatedHost { host, scheme });
+            }
+
+            next.run(req).await
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(patterns: &[&str]) -> AllowedHosts {
+        AllowedHosts::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_wildcard_allows_anything() {
+        assert!(hosts(&["*"]).is_allowed("evil.example"));
+    }
+
+    #[test]
+    fn test_exact_match_ignores_port_and_case() {
+        let allowed = hosts(&["api.example.com"]);
+        assert!(allowed.is_allowed("API.example.com:8443"));
+        assert!(!allowed.is_allowed("api.example.com.evil.test"));
+    }
+
+    #[test]
+    fn test_subdomain_patterns() {
+        let star = hosts(&["*.example.com"]);
+        assert!(star.is_allowed("api.example.com"));
+        assert!(!star.is_allowed("example.com"));
+        assert!(!star.is_allowed("badexample.com"));
+
+        let dot = hosts(&[".example.com"]);
+        assert!(dot.is_allowed("example.com"));
+        assert!(dot.is_allowed("api.example.com"));
+    }
+
+    #[test]
+    fn test_ipv6_host() {
+        let allowed = hosts(&["[::1]"]);
+        assert!(allowed.is_allowed("[::1]:2999"));
+        assert!(allowed.is_allowed("[::1]"));
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let host = ValidatedHost {
+            host: "api.example.com".to_string(),
+            scheme: "https".to_string(),
+        };
+        assert_eq!(host.absolute_url("/reset?token=x"), "https://api.example.com/reset?token=x");
+    }
+}
@@ -0,0 +1,148 @@
+//! JSON body extractor with structural limits
+//!
+//! [`LimitedJson`] behaves like [`axum::Json`] but first scans the raw body
+//! against [`JsonLimitsConfig`], so a deeply nested document or an enormous
+//! number literal is rejected before serde ever sees it.
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{config::JsonLimitsConfig, error::AppError, AppState};
+
+/// `Json<T>` that enforces `JSON_MAX_DEPTH` and `JSON_MAX_NUMBER_LENGTH`
+///
+/// Limit violations are rejected with `AppError::BadRequest` and a missing
+/// JSON content type with `AppError::UnsupportedMediaType`; everything else
+/// (syntax, schema) is rejected exactly as `axum::Json` would.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LimitedJson<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequest<AppState> for LimitedJson<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        check_limits(&bytes, &state.config.json).map_err(IntoResponse::into_response)?;
+
+        let req = Request::from_parts(parts, Body::from(bytes));
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|rejection| match rejection {
+                JsonRejection::MissingJsonContentType(_) => AppError::UnsupportedMediaType(
+                    "Expected request with `Content-Type: application/json`".to_string(),
+                )
+                .into_response(),
+                rejection => rejection.into_response(),
+            })?;
+
+        Ok(LimitedJson(value))
+    }
+}
+
+/// Scan a JSON document for nesting and number length violations
+///
+/// This is a lexical pass, not a parser: malformed input that stays within
+/// the limits is left for serde to reject.
+pub fn check_limits(body: &[u8], limits: &JsonLimitsConfig) -> Result<(), AppError> {
+    let mut depth = 0usize;
+    let mut number_length = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in body {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        if matches!(byte, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') {
+            number_length += 1;
+            if number_length > limits.max_number_length {
+                return Err(AppError::BadRequest(format!(
+                    "JSON number exceeds {} characters",
+                    limits.max_number_length
+                )));
+            }
+            continue;
+        }
+        number_length = 0;
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > limits.max_depth {
+                    return Err(AppError::BadRequest(format!(
+                        "JSON nesting exceeds depth {}",
+                        limits.max_depth
+                    )));
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max_depth: usize, max_number_length: usize) -> JsonLimitsConfig {
+        JsonLimitsConfig {
+            max_depth,
+            max_number_length,
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let limits = limits(3, 64);
+        assert!(check_limits(br#"{"a": [{"b": 1}]}"#, &limits).is_ok());
+        assert!(matches!(
+            check_limits(br#"{"a": [{"b": [1]}]}"#, &limits),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_siblings_do_not_add_depth() {
+        assert!(check_limits(br#"[[1], [2], [3], {"a": 1}]"#, &limits(2, 64)).is_ok());
+    }
+
+    #[test]
+    fn test_brackets_inside_strings_are_ignored() {
+        let body = br#"{"text": "[[[[{{{{ \" ]]]] 123456789"}"#;
+        assert!(check_limits(body, &limits(1, 4)).is_ok());
+    }
+
+    #[test]
+    fn test_number_length_limit() {
+        let limits = limits(8, 10);
+        assert!(check_limits(br#"{"n": -1.2345e10}"#, &limits).is_ok());
+        assert!(matches!(
+            check_limits(format!(r#"{{"n": {}}}"#, "9".repeat(11)).as_bytes(), &limits),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
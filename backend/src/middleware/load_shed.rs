@@ -0,0 +1,119 @@
+//! Load shedding (MAX_IN_FLIGHT_REQUESTS)
+//!
+//! Once the configured number of requests is in flight, new ones are turned
+//! away immediately with `503` and `Retry-After` instead of queuing, so the
+//! requests already admitted keep their latency. The router skips this layer
+//! for PROBE_PATHS: an orchestrator seeing health probes fail would restart a
+//! busy instance.
+use axum::{
+    body::Body,
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::error::AppError;
+
+/// Seconds clients are asked to wait before retrying a shed request
+const RETRY_AFTER_SECS: u64 = 1;
+
+/// Create a load shedding middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// `max_in_flight` of 0 disables shedding.
+pub fn load_shed_layer(
+    max_in_flight: usize,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    let permits = (max_in_flight > 0).then(|| Arc::new(Semaphore::new(max_in_flight)));
+
+    move |req: Request, next: Next| {
+        let permits = permits.clone();
+        Box::pin(async move {
+            let Some(permits) = permits else {
+                return next.run(req).await;
+            };
+
+            // Held until the response is produced
+            let Ok(_permit) = permits.try_acquire_owned() else {
+                tracing::warn!(path = %req.uri().path(), "Shedding request, server is at capacity");
+                crate::metrics::record_load_shed();
+                return AppError::ServiceUnavailable {
+                    message: "Server is at capacity. Please try again shortly.".to_string(),
+                    retry_after_secs: RETRY_AFTER_SECS,
+                }
+                .into_response();
+            };
+
+            next.run(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::skip_paths::SkipPathsLayer;
+    use axum::{
+        http::{header, StatusCode},
+        routing::get,
+        Router,
+    };
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        "done"
+    }
+
+    fn app(max_in_flight: usize) -> Router {
+        Router::new()
+            .route("/slow", get(slow))
+            .route("/api/v1/health", get(slow))
+            .layer(SkipPathsLayer::new(
+                axum::middleware::from_fn(load_shed_layer(max_in_flight)),
+                vec!["/api/v1/health".to_string()],
+            ))
+    }
+
+    fn get_req(uri: &str) -> Request {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_over_cap_is_shed() {
+        let app = app(1);
+
+        let first = tokio::spawn(app.clone().oneshot(get_req("/slow")));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let start = std::time::Instant::now();
+        let second = app.clone().oneshot(get_req("/slow")).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200), "request was queued");
+        assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(second.headers()[header::RETRY_AFTER], "1");
+        let body = axum::body::to_bytes(second.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error_code"], "SERVICE_UNAVAILABLE");
+
+        assert_eq!(first.await.unwrap().unwrap().status(), StatusCode::OK);
+
+        // Capacity is released once the first request finishes
+        let third = app.oneshot(get_req("/slow")).await.unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_paths_are_never_shed() {
+        let app = app(1);
+
+        let first = tokio::spawn(app.clone().oneshot(get_req("/slow")));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let health = app.oneshot(get_req("/api/v1/health")).await.unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+        first.await.unwrap().unwrap();
+    }
+}
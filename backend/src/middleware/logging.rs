@@ -3,15 +3,40 @@ use axum::{
     http::{Request, Response},
     middleware::Next,
 };
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Instant;
 
+use crate::middleware::cancellation::{self, CancelOnDrop};
+
+/// Create a request logging middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// Requests to `quiet_paths` (exact matches, LOG_QUIET_PATHS) log their
+/// start and completion at debug instead of info.
+pub fn log_request_layer(
+    quiet_paths: Vec<String>,
+) -> impl Fn(Request<Body>, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    let quiet_paths: Arc<HashSet<String>> = Arc::new(quiet_paths.into_iter().collect());
+
+    move |req: Request<Body>, next: Next| {
+        let quiet = quiet_paths.contains(req.uri().path());
+        Box::pin(log_request(req, next, quiet))
+    }
+}
+
 /// Request logging middleware with tracing spans
 /// This creates a span for each request with detailed context
 ///
 /// Set VERBOSE_HTTP=1 to log request headers and body
-pub async fn log_request(
-    req: Request<Body>,
+///
+/// A request dropped before it produced a response (client disconnect,
+/// timeout) logs "Request cancelled" instead of "Request completed" and fires
+/// its [`Cancellation`](crate::middleware::cancellation::Cancellation).
+async fn log_request(
+    mut req: Request<Body>,
     next: Next,
+    quiet: bool,
 ) -> Response<Body> {
     let method = req.method().clone();
     let uri = req.uri().clone();
@@ -37,6 +62,9 @@ pub async fn log_request(
         http.user_agent = %user_agent,
         http.status_code = tracing::field::Empty,
         http.response_time_ms = tracing::field::Empty,
+        // Recorded by the `AuthUser` extractor; left out for anonymous requests
+        user.id = tracing::field::Empty,
+        tenant.id = tracing::field::Empty,
         otel.kind = "server",
         otel.status_code = tracing::field::Empty,
     );
@@ -60,18 +88,32 @@ pub async fn log_request(
         }
     }
 
-    tracing::info!("Request started");
+    if quiet {
+        tracing::debug!("Request started");
+    } else {
+        tracing::info!("Request started");
+    }
 
     let start = Instant::now();
 
     // Drop the guard before calling next to avoid holding the span across await
     drop(_guard);
 
+    let (signal, cancellation) = cancellation::channel();
+    req.extensions_mut().insert(cancellation);
+    let cancelled = CancelledLog {
+        span: span.clone(),
+        start,
+        quiet,
+        signal: Some(signal),
+    };
+
     let response = async {
         next.run(req).await
     }
     .instrument(span.clone())
     .await;
+    cancelled.completed();
 
     let elapsed = start.elapsed();
     let status = response.status();
@@ -88,14 +130,134 @@ pub async fn log_request(
     }
 
     let _guard = span.enter();
-    tracing::info!(
-        status = %status,
-        elapsed_ms = %elapsed.as_millis(),
-        "Request completed"
-    );
+    if quiet {
+        tracing::debug!(
+            status = %status,
+            elapsed_ms = %elapsed.as_millis(),
+            "Request completed"
+        );
+    } else {
+        tracing::info!(
+            status = %status,
+            elapsed_ms = %elapsed.as_millis(),
+            "Request completed"
+        );
+    }
 
     response
 }
 
+/// Logs "Request cancelled" if dropped before [`completed`](Self::completed)
+struct CancelledLog {
+    span: tracing::Span,
+    start: Instant,
+    quiet: bool,
+    signal: Option<CancelOnDrop>,
+}
+
+impl CancelledLog {
+    fn completed(mut self) {
+        if let Some(signal) = self.signal.take() {
+            signal.disarm();
+        }
+    }
+}
+
+impl Drop for CancelledLog {
+    fn drop(&mut self) {
+        // Dropping the signal fires the request's `Cancellation`
+        if self.signal.take().is_none() {
+            return;
+        }
+
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        self.span.record("http.response_time_ms", elapsed_ms);
+        self.span.record("otel.status_code", "ERROR");
+
+        let _guard = self.span.enter();
+        if self.quiet {
+            tracing::debug!(elapsed_ms, "Request cancelled");
+        } else {
+            tracing::info!(elapsed_ms, "Request cancelled");
+        }
+    }
+}
+
 // Re-export for convenience
 use tracing::Instrument;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::CapturedLogs;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_quiet_paths_skip_info_completion_event() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/metrics", get(|| async { "ok" }))
+            .route("/api/v1/things", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(log_request_layer(vec!["/metrics".to_string()])));
+
+        for uri in ["/metrics", "/api/v1/things"] {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(req).await.unwrap();
+        }
+
+        let logs = logs.contents();
+        let completed: Vec<&str> = logs
+            .lines()
+            .filter(|line| line.contains("Request completed"))
+            .collect();
+        assert_eq!(completed.len(), 1, "{}", logs);
+        assert!(completed[0].contains("/api/v1/things"), "{}", logs);
+        assert!(!logs.contains("/metrics"), "{}", logs);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_request_logs_cancelled() {
+        use crate::middleware::cancellation::Cancellation;
+
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        // The handler hands its cancellation out, then never finishes
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = std::sync::Arc::new(std::sync::Mutex::new(tx));
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(move |cancellation: Cancellation| async move {
+                    tx.lock().unwrap().send(cancellation).unwrap();
+                    std::future::pending::<&str>().await
+                }),
+            )
+            .layer(axum::middleware::from_fn(log_request_layer(vec![])));
+
+        // Dropping the request future is what hyper does when the client goes away
+        let req = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+        let result = tokio::time::timeout(std::time::Duration::from_millis(50), app.oneshot(req)).await;
+        assert!(result.is_err());
+
+        let cancellation = rx.recv().unwrap();
+        assert!(cancellation.is_cancelled());
+
+        let logs = logs.contents();
+        assert!(logs.contains("Request cancelled"), "{}", logs);
+        assert!(!logs.contains("Request completed"), "{}", logs);
+    }
+}
@@ -1,9 +1,28 @@
 pub mod auth;
+pub mod cache;
+pub mod cancellation;
+#[cfg(debug_assertions)]
+pub mod capture;
+pub mod connection_leases;
+pub mod content_type;
+pub mod context;
+pub mod field_case;
+pub mod host;
+pub mod json;
+pub mod load_shed;
 pub mod logging;
+pub mod nonce;
+#[cfg(debug_assertions)]
+pub mod openapi_validation;
+pub mod path;
 pub mod rate_limit;
 pub mod request_id;
 pub mod security;
+pub mod server_timing;
+pub mod skip_paths;
+pub mod tenant;
+pub mod uri_limit;
 
-pub use logging::log_request;
-pub use request_id::request_id_middleware;
+pub use logging::log_request_layer;
+pub use request_id::request_id_layer;
 pub use security::security_headers;
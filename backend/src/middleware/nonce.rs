@@ -0,0 +1,34 @@
+//! `X-Nonce` header extractor for replay-protected routes
+//!
+//! Adding [`Nonce`] to a handler makes the header mandatory; the handler then
+//! consumes it with `state.services.nonces.consume(..)` (see
+//! `services::nonce`).
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+
+use crate::error::AppError;
+
+/// Header carrying a nonce from `GET /api/v1/auth/nonce`
+pub const NONCE_HEADER: &str = "x-nonce";
+
+/// Nonce sent with the request
+#[derive(Debug, Clone)]
+pub struct Nonce(pub String);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Nonce
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .headers
+            .get(NONCE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| Nonce(value.to_string()))
+            .ok_or_else(|| AppError::BadRequest("Missing X-Nonce header".to_string()))
+    }
+}
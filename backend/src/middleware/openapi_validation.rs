@@ -0,0 +1,253 @@
+//! Request body validation against the generated OpenAPI document
+//!
+//! Development aid (debug builds, `OPENAPI_VALIDATION=1`): JSON request
+//! bodies are checked against the `requestBody` schema of the matching
+//! operation in `ApiDoc::openapi()`, and mismatches are logged as warnings.
+//! Requests are never rejected - the schema check is intentionally shallow
+//! (types, required fields, unknown fields) and may have false positives.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::sync::OnceLock;
+use utoipa::OpenApi;
+
+use crate::{docs::ApiDoc, error::AppError, routes::DEFAULT_BODY_LIMIT};
+
+/// OpenAPI document as JSON, generated once
+fn spec() -> &'static Value {
+    static SPEC: OnceLock<Value> = OnceLock::new();
+    SPEC.get_or_init(|| serde_json::to_value(ApiDoc::openapi()).unwrap_or(Value::Null))
+}
+
+/// Match a request path against an OpenAPI path template like `/users/{id}`
+fn path_matches(template: &str, path: &str) -> bool {
+    let mut template_segments = template.trim_end_matches('/').split('/');
+    let mut path_segments = path.trim_end_matches('/').split('/');
+
+    loop {
+        match (template_segments.next(), path_segments.next()) {
+            (None, None) => return true,
+            (Some(t), Some(p)) if t == p || (t.starts_with('{') && t.ends_with('}')) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Follow a local `$ref` into `components/schemas`
+fn resolve(schema: &Value) -> &Value {
+    match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => reference
+            .strip_prefix("#/components/schemas/")
+            .and_then(|name| spec().pointer(&format!("/components/schemas/{}", name)))
+            .unwrap_or(&Value::Null),
+        None => schema,
+    }
+}
+
+/// Validate a JSON body against the request schema of `method path`
+///
+/// Returns a list of human-readable mismatches; empty when the body matches
+/// or when the operation isn't documented / has no JSON request body.
+pub fn validate_body(method: &Method, path: &str, body: &Value) -> Vec<String> {
+    let Some(paths) = spec().get("paths").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let schema = paths
+        .iter()
+        .find(|(template, _)| path_matches(template, path))
+        .and_then(|(_, item)| item.get(method.as_str().to_lowercase()))
+        .and_then(|operation| operation.pointer("/requestBody/content/application~1json/schema"));
+
+    let mut issues = Vec::new();
+    if let Some(schema) = schema {
+        check(schema, body, "$", &mut issues);
+    }
+    issues
+}
+
+fn check(schema: &Value, value: &Value, at: &str, issues: &mut Vec<String>) {
+    let schema = resolve(schema);
+
+    if value.is_null() && schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+
+    let type_matches = match schema.get("type").and_then(Value::as_str) {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("number") => value.is_number(),
+        Some("boolean") => value.is_boolean(),
+        // Composite schemas (allOf/oneOf/...) aren't checked
+        _ => return,
+    };
+
+    if !type_matches {
+        issues.push(format!(
+            "{}: expected {}, got {}",
+            at,
+            schema["type"].as_str().unwrap_or("?"),
+            json_type(value)
+        ));
+        return;
+    }
+
+    if let (Some(object), Some(properties)) = (
+        value.as_object(),
+        schema.get("properties").and_then(Value::as_object),
+    ) {
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(required) {
+                issues.push(format!("{}.{}: missing required field", at, required));
+            }
+        }
+
+        let allows_extra = schema
+            .get("additionalProperties")
+            .is_some_and(|v| v != &Value::Bool(false));
+        for (key, field_value) in object {
+            match properties.get(key) {
+                Some(field_schema) => {
+                    check(field_schema, field_value, &format!("{}.{}", at, key), issues)
+                }
+                None if !allows_extra => issues.push(format!("{}.{}: unexpected field", at, key)),
+                None => {}
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check(item_schema, item, &format!("{}[{}]", at, i), issues);
+        }
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Middleware logging request bodies that don't match the OpenAPI schema
+pub async fn validate_request_body(req: Request, next: Next) -> Response {
+    let is_json = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+
+    if !is_json {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes = match axum::body::to_bytes(body, DEFAULT_BODY_LIMIT).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return AppError::PayloadTooLarge("Request body too large".to_string()).into_response()
+        }
+    };
+
+    // Malformed JSON is left for the handler's extractor to reject
+    if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+        for issue in validate_body(&parts.method, parts.uri.path(), &value) {
+            tracing::warn!(
+                method = %parts.method,
+                path = %parts.uri.path(),
+                issue = %issue,
+                "Request body does not match OpenAPI schema"
+            );
+        }
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::CapturedLogs;
+    use axum::{http::StatusCode, routing::post, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_valid_body_has_no_issues() {
+        let body = json!({"email": "a@example.com", "username": "abc", "password": "SecurePass123!"});
+        assert!(validate_body(&Method::POST, "/api/v1/auth/register", &body).is_empty());
+    }
+
+    #[test]
+    fn test_reports_unexpected_missing_and_mistyped_fields() {
+        let body = json!({"email": 42, "username": "abc", "nickname": "x"});
+        let issues = validate_body(&Method::POST, "/api/v1/auth/register", &body);
+
+        assert!(issues.contains(&"$.email: expected string, got number".to_string()));
+        assert!(issues.contains(&"$.password: missing required field".to_string()));
+        assert!(issues.contains(&"$.nickname: unexpected field".to_string()));
+    }
+
+    #[test]
+    fn test_undocumented_route_is_ignored() {
+        assert!(validate_body(&Method::POST, "/api/v1/unknown", &json!({"a": 1})).is_empty());
+    }
+
+    #[test]
+    fn test_path_template_matching() {
+        assert!(path_matches("/users/{id}", "/users/123"));
+        assert!(!path_matches("/users/{id}", "/users/123/posts"));
+    }
+
+    #[tokio::test]
+    async fn test_extra_field_logs_warning_without_blocking() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/api/v1/auth/login", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn(validate_request_body));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({"email": "a@example.com", "password": "x", "remember_me": true}).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let output = logs.contents();
+        assert!(output.contains("Request body does not match OpenAPI schema"), "{}", output);
+        assert!(output.contains("$.remember_me: unexpected field"), "{}", output);
+    }
+}
@@ -0,0 +1,97 @@
+//! UUID path parameter extractor
+//!
+//! `Path<Uuid>` rejects a malformed id with axum's plain-text 400. An id that
+//! can't be parsed can't name any resource, so [`UuidPath`] answers exactly as
+//! a well-formed id that doesn't exist would: `AppError::NotFound`.
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::{error::AppError, types::user_id::UserId};
+
+/// Single UUID path parameter, e.g. `/users/:id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UuidPath(pub Uuid);
+
+impl UuidPath {
+    /// The id as a [`UserId`], for user routes
+    pub fn user_id(self) -> UserId {
+        UserId::from_uuid(self.0)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for UuidPath
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Only a route mistake (no or several params) ends up here
+        let Path(raw) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Invalid path parameters: {}", e)))?;
+
+        Uuid::parse_str(&raw)
+            .map(UuidPath)
+            .map_err(|_| AppError::NotFound("Resource not found".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    const EXISTING: &str = "6f1c2b7e-3a4d-4c5e-9f80-1a2b3c4d5e6f";
+
+    async fn get_item(UuidPath(id): UuidPath) -> Result<&'static str, AppError> {
+        if id.to_string() == EXISTING {
+            Ok("found")
+        } else {
+            Err(AppError::NotFound("Resource not found".to_string()))
+        }
+    }
+
+    async fn status_and_code(uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = Router::new()
+            .route("/items/:id", get(get_item))
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+        (status, json["error_code"].clone())
+    }
+
+    #[tokio::test]
+    async fn test_malformed_id_is_not_found() {
+        let (status, code) = status_and_code("/items/not-a-uuid").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(code, "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_nonexistent_id_is_not_found() {
+        let (status, code) = status_and_code(&format!("/items/{}", Uuid::new_v4())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(code, "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_existing_id_is_extracted() {
+        let (status, _) = status_and_code(&format!("/items/{}", EXISTING)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -1,21 +1,32 @@
 //! Simple rate limiter for authentication endpoints
 //!
-//! Tracks request counts per IP address with a sliding window.
-//! Automatically cleans up old entries to prevent memory leaks.
+//! Tracks request counts per key with a sliding window. Requests are keyed
+//! by client IP unless the limiter is given another [`KeyExtractor`]: the
+//! authenticated user ([`UserKey`]), the API key ([`ApiKeyHeader`]), the route
+//! ([`RouteKey`]), a pair of them such as `(RouteKey, IpKey)`, or a closure.
+//! Automatically cleans up old entries to prevent memory leaks, and caps the
+//! number of tracked keys so a flood of unique IPs can't grow it unbounded.
+//! Allowlisted networks and bypass API keys skip the limiter entirely.
 use axum::{
     body::Body,
-    extract::{ConnectInfo, Request},
+    extract::{ConnectInfo, MatchedPath, Request},
     http::StatusCode,
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+use crate::config::RateLimitConfig;
+use crate::AppState;
+
+/// Default cap on distinct keys tracked at once
+pub const DEFAULT_MAX_KEYS: usize = 10_000;
+
 /// Rate limiter state shared across requests
 #[derive(Clone)]
 pub struct RateLimiter {
@@ -23,13 +34,23 @@ pub struct RateLimiter {
     max_requests: usize,
     window: Duration,
     trust_proxy: bool,
+    max_keys: usize,
+    /// Shared by clones so `set_bypass_keys` reaches the mounted layer
+    exempt: Arc<std::sync::RwLock<RateLimitConfig>>,
+    key: Arc<dyn KeyExtractor>,
 }
 
 struct RateLimiterState {
-    requests: HashMap<String, Vec<Instant>>,
+    requests: HashMap<String, KeyHistory>,
     last_cleanup: Instant,
 }
 
+/// Request timestamps for one key, oldest first
+struct KeyHistory {
+    timestamps: VecDeque<Instant>,
+    last_seen: Instant,
+}
+
 impl RateLimiter {
     /// Create a new rate limiter
     ///
@@ -46,6 +67,9 @@ impl RateLimiter {
             max_requests,
             window,
             trust_proxy,
+            max_keys: DEFAULT_MAX_KEYS,
+            exempt: Arc::new(std::sync::RwLock::new(RateLimitConfig::default())),
+            key: Arc::new(IpKey::new(trust_proxy)),
         }
     }
 
@@ -54,46 +78,255 @@ impl RateLimiter {
         Self::new(10, Duration::from_secs(60), trust_proxy)
     }
 
-    /// Check if a request from the given IP should be allowed
-    pub async fn check(&self, ip: &str) -> bool {
+    /// Cap the number of tracked keys (least recently seen keys are evicted)
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys.max(1);
+        self
+    }
+
+    /// Never limit callers from `allowlist` networks or presenting a bypass key
+    pub fn with_exemptions(mut self, exempt: RateLimitConfig) -> Self {
+        self.exempt = Arc::new(std::sync::RwLock::new(exempt));
+        self
+    }
+
+    /// Replace the bypass keys, e.g. after they were rotated in the secret store
+    pub fn set_bypass_keys(&self, bypass_keys: Vec<String>) {
+        self.exempt.write().unwrap_or_else(|e| e.into_inner()).bypass_keys = bypass_keys;
+    }
+
+    /// Bucket requests by `key` instead of by client IP
+    pub fn with_key(mut self, key: impl KeyExtractor) -> Self {
+        self.key = Arc::new(key);
+        self
+    }
+
+    /// Whether the caller is a trusted service that skips the limiter
+    fn is_exempt(&self, req: &Request, ip: &str) -> bool {
+        let exempt = self.exempt.read().unwrap_or_else(|e| e.into_inner());
+        if let Ok(ip) = ip.parse::<IpAddr>() {
+            if exempt.allowlist.iter().any(|cidr| cidr.contains(ip)) {
+                return true;
+            }
+        }
+
+        req.headers()
+            .get("x-api-key")
+            .and_then(|key| key.to_str().ok())
+            .is_some_and(|key| exempt.bypass_keys.iter().any(|k| k == key))
+    }
+
+    /// Number of keys currently tracked
+    pub async fn tracked_keys(&self) -> usize {
+        self.state.read().await.requests.len()
+    }
+
+    /// Drop keys whose requests have all left the window, returning how
+    /// many were removed
+    ///
+    /// `check` only cleans up every 5 minutes while requests keep arriving;
+    /// the scheduled prune job frees memory after a burst has passed.
+    pub async fn prune(&self) -> usize {
+        let mut state = self.state.write().await;
+        let before = state.requests.len();
+        self.cleanup(&mut state, Instant::now());
+        before - state.requests.len()
+    }
+
+    /// Check if another request under `key` should be allowed
+    pub async fn check(&self, key: &str) -> bool {
         let mut state = self.state.write().await;
         let now = Instant::now();
 
-        // Cleanup old entries every 5 minutes
-        if now.duration_since(state.last_cleanup) > Duration::from_secs(300) {
-            state.requests.retain(|_, timestamps| {
-                timestamps.retain(|ts| now.duration_since(*ts) < self.window);
-                !timestamps.is_empty()
-            });
-            state.last_cleanup = now;
+        // Cleanup old entries every 5 minutes, or as soon as the map is full
+        let is_full = state.requests.len() >= self.max_keys && !state.requests.contains_key(key);
+        if is_full || now.duration_since(state.last_cleanup) > Duration::from_secs(300) {
+            self.cleanup(&mut state, now);
+        }
+
+        // Still full of active keys: evict the least recently seen ones
+        if state.requests.len() >= self.max_keys && !state.requests.contains_key(key) {
+            self.evict_lru(&mut state);
         }
 
-        // Get or create request history for this IP
-        let requests = state.requests.entry(ip.to_string()).or_insert_with(Vec::new);
+        // Get or create request history for this key
+        let history = state.requests.entry(key.to_string()).or_insert_with(|| KeyHistory {
+            timestamps: VecDeque::new(),
+            last_seen: now,
+        });
+        history.last_seen = now;
 
         // Remove expired requests
-        requests.retain(|ts| now.duration_since(*ts) < self.window);
+        while history
+            .timestamps
+            .front()
+            .is_some_and(|ts| now.duration_since(*ts) >= self.window)
+        {
+            history.timestamps.pop_front();
+        }
 
         // Check if under limit
-        if requests.len() < self.max_requests {
-            requests.push(now);
+        if history.timestamps.len() < self.max_requests {
+            history.timestamps.push_back(now);
             true
         } else {
             false
         }
     }
+
+    /// Drop timestamps outside the window and keys with no recent requests
+    fn cleanup(&self, state: &mut RateLimiterState, now: Instant) {
+        state.requests.retain(|_, history| {
+            history.timestamps.retain(|ts| now.duration_since(*ts) < self.window);
+            !history.timestamps.is_empty()
+        });
+        state.last_cleanup = now;
+    }
+
+    /// Evict least recently seen keys down to 90% of the cap
+    ///
+    /// Evicting in a batch keeps the sort cost amortized under a flood of
+    /// unique keys instead of paying it on every new key.
+    fn evict_lru(&self, state: &mut RateLimiterState) {
+        let target = self.max_keys - self.max_keys.div_ceil(10);
+        let excess = state.requests.len().saturating_sub(target);
+        if excess == 0 {
+            return;
+        }
+
+        let mut by_age: Vec<(Instant, String)> = state
+            .requests
+            .iter()
+            .map(|(key, history)| (history.last_seen, key.clone()))
+            .collect();
+        by_age.sort_unstable_by_key(|(last_seen, _)| *last_seen);
+
+        for (_, key) in by_age.into_iter().take(excess) {
+            state.requests.remove(&key);
+        }
+
+        tracing::warn!(
+            evicted = excess,
+            max_keys = self.max_keys,
+            "Rate limiter key cap reached, evicted least recently seen keys"
+        );
+    }
+}
+
+/// How requests are grouped into rate-limit buckets
+///
+/// Returning `None` means the strategy can't identify the caller (e.g. no
+/// valid token for [`UserKey`]); those requests are keyed by client IP.
+/// Closures taking `&Request` implement it too.
+pub trait KeyExtractor: Send + Sync + 'static {
+    fn key(&self, req: &Request) -> Option<String>;
+}
+
+impl<F> KeyExtractor for F
+where
+    F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+{
+    fn key(&self, req: &Request) -> Option<String> {
+        self(req)
+    }
+}
+
+/// Both keys joined, e.g. `(RouteKey, IpKey)` for a separate bucket per
+/// route for each client; `None` if either part is
+impl<A: KeyExtractor, B: KeyExtractor> KeyExtractor for (A, B) {
+    fn key(&self, req: &Request) -> Option<String> {
+        Some(format!("{}|{}", self.0.key(req)?, self.1.key(req)?))
+    }
+}
+
+/// Key by client IP (the default)
+#[derive(Debug, Clone, Copy)]
+pub struct IpKey {
+    trust_proxy: bool,
+}
+
+impl IpKey {
+    /// `trust_proxy` - Whether to trust X-Forwarded-For/X-Real-IP headers
+    pub fn new(trust_proxy: bool) -> Self {
+        Self { trust_proxy }
+    }
+}
+
+impl KeyExtractor for IpKey {
+    fn key(&self, req: &Request) -> Option<String> {
+        Some(extract_ip(req, self.trust_proxy))
+    }
+}
+
+/// Key by authenticated user, so one account gets one budget across IPs
+///
+/// The bearer token (or auth cookie) is verified here since the limiter
+/// runs before any handler extracts [`AuthUser`](super::auth::AuthUser).
+#[derive(Clone)]
+pub struct UserKey {
+    state: AppState,
+}
+
+impl UserKey {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+impl KeyExtractor for UserKey {
+    fn key(&self, req: &Request) -> Option<String> {
+        let token = super::auth::extract_token(req.headers(), &self.state).ok()?;
+        let claims = self.state.jwt().verify_token(token).ok()?;
+        Some(format!("user:{}:{}", claims.tenant, claims.sub))
+    }
+}
+
+/// Key by the `X-Api-Key` header
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiKeyHeader;
+
+impl KeyExtractor for ApiKeyHeader {
+    fn key(&self, req: &Request) -> Option<String> {
+        req.headers()
+            .get("x-api-key")
+            .and_then(|key| key.to_str().ok())
+            .map(|key| format!("api-key:{}", key))
+    }
+}
+
+/// Key by route (the matched path template, so `/users/:id` is one route)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouteKey;
+
+impl KeyExtractor for RouteKey {
+    fn key(&self, req: &Request) -> Option<String> {
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str())
+            .unwrap_or_else(|| req.uri().path());
+        Some(format!("{} {}", req.method(), route))
+    }
 }
 
 /// Extract IP address from request
 ///
+/// Falls back to `"unknown"` when neither the headers nor the connection
+/// name one; see [`client_ip`].
+fn extract_ip(req: &Request, trust_proxy: bool) -> String {
+    client_ip(req, trust_proxy).unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Client IP address of a request, if known
+///
 /// # Arguments
 /// * `req` - The HTTP request
 /// * `trust_proxy` - Whether to trust X-Forwarded-For/X-Real-IP headers
-///                   Should only be true when behind a trusted reverse proxy
+///   Should only be true when behind a trusted reverse proxy
 ///
 /// # Security
 /// When `trust_proxy` is false, proxy headers are ignored to prevent IP spoofing
-fn extract_ip(req: &Request, trust_proxy: bool) -> String {
+pub fn client_ip(req: &Request, trust_proxy: bool) -> Option<String> {
     // Only trust proxy headers if explicitly configured
     if trust_proxy {
         // Try to get IP from X-Forwarded-For header (for proxies/load balancers)
@@ -101,7 +334,7 @@ fn extract_ip(req: &Request, trust_proxy: bool) -> String {
             if let Ok(forwarded_str) = forwarded_for.to_str() {
                 // Take the first IP in the chain (the client IP)
                 if let Some(first_ip) = forwarded_str.split(',').next() {
-                    return first_ip.trim().to_string();
+                    return Some(first_ip.trim().to_string());
                 }
             }
         }
@@ -109,18 +342,15 @@ fn extract_ip(req: &Request, trust_proxy: bool) -> String {
         // Try to get IP from X-Real-IP header
         if let Some(real_ip) = req.headers().get("x-real-ip") {
             if let Ok(ip_str) = real_ip.to_str() {
-                return ip_str.to_string();
+                return Some(ip_str.to_string());
             }
         }
     }
 
     // Fall back to ConnectInfo (actual connection IP)
-    if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
-        return addr.ip().to_string();
-    }
-
-    // Last resort: use unknown
-    "unknown".to_string()
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
 }
 
 /// Create a rate limiting middleware closure
@@ -136,8 +366,13 @@ pub fn rate_limit_layer(
             // Extract IP address from headers or connection info
             let ip = extract_ip(&req, trust_proxy);
 
+            if limiter.is_exempt(&req, &ip) {
+                return next.run(req).await;
+            }
+
             // Check rate limit
-            if !limiter.check(&ip).await {
+            let key = limiter.key.key(&req).unwrap_or_else(|| ip.clone());
+            if !limiter.check(&key).await {
                 tracing::warn!(ip = %ip, "Rate limit exceeded");
 
                 let error_response = serde_json::json!({
@@ -153,3 +388,125 @@ pub fn rate_limit_layer(
             as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_requests_per_key() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60), false);
+
+        assert!(limiter.check("10.0.0.1").await);
+        assert!(limiter.check("10.0.0.1").await);
+        assert!(!limiter.check("10.0.0.1").await);
+        assert!(limiter.check("10.0.0.2").await);
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_ip_is_never_limited() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let limiter = RateLimiter::new(2, Duration::from_secs(60), true).with_exemptions(
+            RateLimitConfig {
+                allowlist: vec!["10.0.0.0/8".parse().unwrap()],
+                ..Default::default()
+            },
+        );
+        let app = Router::new()
+            .route("/login", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(rate_limit_layer(limiter)));
+        let status_for = |ip: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder()
+                    .uri("/login")
+                    .header("x-forwarded-for", ip)
+                    .body(Body::empty())
+                    .unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        for _ in 0..5 {
+            assert_eq!(status_for("10.1.2.3").await, StatusCode::OK);
+        }
+        assert_eq!(status_for("203.0.113.7").await, StatusCode::OK);
+        assert_eq!(status_for("203.0.113.7").await, StatusCode::OK);
+        assert_eq!(status_for("203.0.113.7").await, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_route_and_ip_key_gives_each_route_its_own_bucket() {
+        use axum::{routing::post, Router};
+        use tower::ServiceExt;
+
+        let limiter = RateLimiter::new(1, Duration::from_secs(60), true)
+            .with_key((RouteKey, IpKey::new(true)));
+        let app = Router::new()
+            .route("/login", post(|| async { "ok" }))
+            .route("/register", post(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(rate_limit_layer(limiter)));
+        let status_for = |path: &'static str, ip: &'static str| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder()
+                    .method("POST")
+                    .uri(path)
+                    .header("x-forwarded-for", ip)
+                    .body(Body::empty())
+                    .unwrap();
+                app.oneshot(req).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status_for("/login", "203.0.113.7").await, StatusCode::OK);
+        assert_eq!(status_for("/register", "203.0.113.7").await, StatusCode::OK);
+        assert_eq!(status_for("/login", "198.51.100.1").await, StatusCode::OK);
+        assert_eq!(status_for("/login", "203.0.113.7").await, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(status_for("/register", "203.0.113.7").await, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_extractors_without_a_key_return_none() {
+        let req = Request::builder()
+            .uri("/login")
+            .header("x-forwarded-for", "203.0.113.7")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(ApiKeyHeader.key(&req), None);
+        assert_eq!((RouteKey, ApiKeyHeader).key(&req), None);
+        let by_tenant = |req: &Request| req.headers().get("x-tenant")?.to_str().ok().map(String::from);
+        assert_eq!(by_tenant.key(&req), None);
+        assert_eq!(IpKey::new(true).key(&req).as_deref(), Some("203.0.113.7"));
+    }
+
+    #[tokio::test]
+    async fn test_unique_keys_stay_bounded() {
+        let limiter = RateLimiter::new(5, Duration::from_secs(60), false).with_max_keys(100);
+
+        for i in 0..10_000 {
+            limiter.check(&format!("10.0.{}.{}", i / 256, i % 256)).await;
+            assert!(limiter.tracked_keys().await <= 100);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eviction_keeps_recent_keys() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60), false).with_max_keys(10);
+
+        for i in 0..10 {
+            limiter.check(&format!("old-{}", i)).await;
+        }
+        // Touch the newest key again so it's the most recently seen
+        assert!(!limiter.check("old-9").await);
+
+        limiter.check("new").await;
+
+        // The recent key is still limited, evicted old keys start fresh
+        assert!(!limiter.check("old-9").await);
+        assert!(limiter.check("old-0").await);
+    }
+}
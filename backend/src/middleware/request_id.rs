@@ -1,6 +1,13 @@
+//! Request IDs (REQUEST_ID_HEADER)
+//!
+//! Every request gets an ID stored as a [`RequestId`] extension and echoed in
+//! the response. It is taken from, in order: the configured header (default
+//! `x-request-id`), the trace id of a W3C `traceparent` header, so logs line
+//! up with the caller's OpenTelemetry trace, or a fresh UUID.
 use axum::{
+    body::Body,
     extract::Request,
-    http::HeaderValue,
+    http::{HeaderMap, HeaderName, HeaderValue},
     middleware::Next,
     response::Response,
 };
@@ -8,31 +15,73 @@ use uuid::Uuid;
 
 pub const REQUEST_ID_HEADER: &str = "x-request-id";
 
-/// Middleware to add request ID to all requests
-pub async fn request_id_middleware(
-    mut req: Request,
-    next: Next,
-) -> Response {
-    // Check if request already has an ID, otherwise generate one
-    let request_id = req
-        .headers()
-        .get(REQUEST_ID_HEADER)
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
-
-    // Add request ID to request extensions for use in handlers
-    req.extensions_mut().insert(RequestId(request_id.clone()));
-
-    // Call the next middleware/handler
-    let mut response = next.run(req).await;
-
-    // Add request ID to response headers
-    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
-        response.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+/// W3C Trace Context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Create a request ID middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// When `header` is `traceparent` the ID is not echoed back, since a trace
+/// id alone is not a valid `traceparent` value.
+pub fn request_id_layer(
+    header: HeaderName,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |mut req: Request, next: Next| {
+        let header = header.clone();
+        Box::pin(async move {
+            let request_id = request_id_from(req.headers(), &header)
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+            // Add request ID to request extensions for use in handlers
+            req.extensions_mut().insert(RequestId(request_id.clone()));
+
+            let mut response = next.run(req).await;
+
+            if header != TRACEPARENT_HEADER {
+                if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+                    response.headers_mut().insert(header, header_value);
+                }
+            }
+
+            response
+        })
+    }
+}
+
+/// The request ID the client supplied, if any
+fn request_id_from(headers: &HeaderMap, header: &HeaderName) -> Option<String> {
+    let value = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if header != TRACEPARENT_HEADER {
+        if let Some(id) = value(header.as_str()).filter(|id| !id.is_empty()) {
+            return Some(id.to_string());
+        }
     }
+    value(TRACEPARENT_HEADER).and_then(trace_id).map(str::to_string)
+}
+
+/// Trace id of a W3C `traceparent` value (`00-<trace-id>-<parent-id>-<flags>`)
+///
+/// `None` for malformed values and the all-zero trace id, which the spec
+/// declares invalid.
+pub fn trace_id(traceparent: &str) -> Option<&str> {
+    let mut parts = traceparent.trim().split('-');
+    let (version, trace_id, parent_id, flags) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+
+    let is_hex = |s: &str, len: usize| {
+        s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    };
+    let valid = is_hex(version, 2)
+        && version != "ff"
+        && is_hex(trace_id, 32)
+        && is_hex(parent_id, 16)
+        && is_hex(flags, 2)
+        && trace_id.bytes().any(|b| b != b'0')
+        // Version 00 has exactly four fields; later versions may append more
+        && (version != "00" || parts.next().is_none());
 
-    response
+    valid.then_some(trace_id)
 }
 
 /// Request ID extension for extracting in handlers
@@ -46,3 +95,20 @@ impl RequestId {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_id_from_traceparent() {
+        assert_eq!(
+            trace_id("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert_eq!(trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("00-4bf92f3577b34da6-00f067aa0ba902b7-01"), None);
+        assert_eq!(trace_id("garbage"), None);
+    }
+}
@@ -4,21 +4,37 @@
 //! common web vulnerabilities.
 use axum::{
     body::Body,
-    extract::Request,
+    extract::{Request, State},
     http::header,
     middleware::Next,
     response::Response,
 };
 
+use crate::{middleware::host::ValidatedHost, AppState};
+
 /// Middleware to add security headers to responses
 ///
 /// Adds the following headers:
 /// - X-Content-Type-Options: nosniff (prevents MIME sniffing)
-/// - X-Frame-Options: DENY (prevents clickjacking)
+/// - X-Frame-Options: DENY or SAMEORIGIN (prevents clickjacking)
 /// - X-XSS-Protection: 1; mode=block (XSS protection for older browsers)
-/// - Strict-Transport-Security: enforces HTTPS (in production only)
+/// - Strict-Transport-Security: enforces HTTPS (release builds unless SECURITY_HSTS says otherwise),
+///   only on responses that are actually served over HTTPS
 /// - Referrer-Policy: strict-origin-when-cross-origin (controls referrer info)
-pub async fn security_headers(request: Request, next: Next) -> Response<Body> {
+/// - Content-Security-Policy: `config::DEFAULT_CSP` unless SECURITY_CSP overrides it
+///
+/// Use with `axum::middleware::from_fn_with_state`.
+pub async fn security_headers(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    let config = &state.config.security_headers;
+    // The scheme resolved by host validation (TLS or a trusted X-Forwarded-Proto)
+    let https = request
+        .extensions()
+        .get::<ValidatedHost>()
+        .map_or(state.config.server.tls.is_some(), |host| host.scheme == "https");
     let mut response = next.run(request).await;
     let headers = response.headers_mut();
 
@@ -28,10 +44,10 @@ pub async fn security_headers(request: Request, next: Next) -> Response<Body> {
         header::HeaderValue::from_static("nosniff"),
     );
 
-    // Prevent clickjacking - don't allow embedding in iframes
+    // Prevent clickjacking - don't allow embedding in other sites' iframes
     headers.insert(
         header::HeaderName::from_static("x-frame-options"),
-        header::HeaderValue::from_static("DENY"),
+        header::HeaderValue::from_static(config.frame_options.as_str()),
     );
 
     // Enable XSS protection in older browsers
@@ -46,24 +62,22 @@ pub async fn security_headers(request: Request, next: Next) -> Response<Body> {
         header::HeaderValue::from_static("strict-origin-when-cross-origin"),
     );
 
-    // HSTS: Force HTTPS for 1 year (only in production)
-    // Note: Only enable this if you're serving over HTTPS in production!
-    #[cfg(not(debug_assertions))]
-    {
-        headers.insert(
-            header::STRICT_TRANSPORT_SECURITY,
-            header::HeaderValue::from_static("max-age=31536000; includeSubDomains"),
-        );
+    // HSTS: Force HTTPS for SECURITY_HSTS_MAX_AGE (1 year by default).
+    // Browsers ignore it over plain HTTP, and asserting it there would only
+    // mislead.
+    if config.hsts && https {
+        if let Ok(hsts) = header::HeaderValue::from_str(&config.hsts_value()) {
+            headers.insert(header::STRICT_TRANSPORT_SECURITY, hsts);
+        }
     }
 
-    // CSP: Basic content security policy (you should customize this for your needs)
-    // This is a restrictive default - adjust based on your application requirements
-    headers.insert(
-        header::HeaderName::from_static("content-security-policy"),
-        header::HeaderValue::from_static(
-            "default-src 'self'; script-src 'self'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; font-src 'self'; connect-src 'self'; frame-ancestors 'none';",
-        ),
-    );
+    // CSP: restrictive by default, overridable with SECURITY_CSP
+    // (validated as a header value when the config is loaded)
+    if !config.content_security_policy.is_empty() {
+        if let Ok(csp) = header::HeaderValue::from_str(&config.content_security_policy) {
+            headers.insert(header::CONTENT_SECURITY_POLICY, csp);
+        }
+    }
 
     response
 }
@@ -0,0 +1,118 @@
+//! `Server-Timing` response header (SERVER_TIMING=1)
+//!
+//! [`server_timing_layer`] puts a [`ServerTimings`] accumulator in the request
+//! extensions and also makes it the current task's accumulator, so code that
+//! never sees the request (the DB helpers) can still [`record`] into it. When
+//! the response comes back the collected phases are written out, e.g.
+//! `auth;dur=0.4, db;dur=3.1, handler;dur=5.2`, which browser dev tools show
+//! in the network timing panel.
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header::HeaderName, Extensions, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+static SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+tokio::task_local! {
+    static CURRENT: ServerTimings;
+}
+
+/// Per-request accumulator of phase durations
+///
+/// Repeated phases (several queries) are summed into one entry.
+#[derive(Debug, Clone, Default)]
+pub struct ServerTimings(Arc<Mutex<Vec<(&'static str, Duration)>>>);
+
+impl ServerTimings {
+    pub fn record(&self, phase: &'static str, duration: Duration) {
+        let mut phases = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += duration,
+            None => phases.push((phase, duration)),
+        }
+    }
+
+    /// Record into the request's accumulator, if timing is enabled
+    pub fn record_in(extensions: &Extensions, phase: &'static str, duration: Duration) {
+        if let Some(timings) = extensions.get::<ServerTimings>() {
+            timings.record(phase, duration);
+        }
+    }
+
+    /// `Server-Timing` header value, in milliseconds
+    pub fn header_value(&self) -> String {
+        let phases = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        phases
+            .iter()
+            .map(|(name, duration)| format!("{};dur={:.1}", name, duration.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Record a phase for the request being handled by the current task
+///
+/// A no-op outside [`server_timing_layer`] or when timing is disabled.
+pub fn record(phase: &'static str, duration: Duration) {
+    let _ = CURRENT.try_with(|timings| timings.record(phase, duration));
+}
+
+/// Create a Server-Timing middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// When `enabled` is false requests pass straight through.
+pub fn server_timing_layer(
+    enabled: bool,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |mut req: Request, next: Next| {
+        Box::pin(async move {
+            if !enabled {
+                return next.run(req).await;
+            }
+
+            let timings = ServerTimings::default();
+            req.extensions_mut().insert(timings.clone());
+
+            let start = Instant::now();
+            let mut response = CURRENT.scope(timings.clone(), next.run(req)).await;
+            timings.record("handler", start.elapsed());
+
+            if let Ok(value) = HeaderValue::from_str(&timings.header_value()) {
+                response.headers_mut().insert(SERVER_TIMING.clone(), value);
+            }
+            response
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_phases_are_summed() {
+        let timings = ServerTimings::default();
+        timings.record("db", Duration::from_millis(2));
+        timings.record("auth", Duration::from_micros(500));
+        timings.record("db", Duration::from_millis(3));
+
+        assert_eq!(timings.header_value(), "db;dur=5.0, auth;dur=0.5");
+    }
+
+    #[tokio::test]
+    async fn test_record_outside_scope_is_noop() {
+        record("db", Duration::from_millis(1));
+
+        let timings = ServerTimings::default();
+        CURRENT
+            .scope(timings.clone(), async { record("db", Duration::from_millis(1)) })
+            .await;
+        assert_eq!(timings.header_value(), "db;dur=1.0");
+    }
+}
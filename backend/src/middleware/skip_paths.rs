@@ -0,0 +1,120 @@
+//! Bypass a layer for selected paths (PROBE_PATHS)
+//!
+//! Health probes and metric scrapes arrive every few seconds and are never
+//! read by a browser, so CSP, HSTS and CORS handling on them is wasted work.
+//! [`SkipPathsLayer`] wraps another layer and sends requests for the listed
+//! paths (exact matches) straight to the inner service, leaving the layer's
+//! position in the stack unchanged for every other request.
+use axum::http::Request;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service, ServiceExt};
+
+/// Apply `layer` to every request except those for `paths`
+#[derive(Clone)]
+pub struct SkipPathsLayer<L> {
+    layer: L,
+    paths: Arc<HashSet<String>>,
+}
+
+impl<L> SkipPathsLayer<L> {
+    pub fn new(layer: L, paths: Vec<String>) -> Self {
+        Self {
+            layer,
+            paths: Arc::new(paths.into_iter().collect()),
+        }
+    }
+}
+
+impl<L, S> Layer<S> for SkipPathsLayer<L>
+where
+    L: Layer<S>,
+    S: Clone,
+{
+    type Service = SkipPaths<L::Service, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SkipPaths {
+            layered: self.layer.layer(inner.clone()),
+            inner,
+            paths: self.paths.clone(),
+        }
+    }
+}
+
+/// Service built by [`SkipPathsLayer`]
+#[derive(Clone)]
+pub struct SkipPaths<A, S> {
+    layered: A,
+    inner: S,
+    paths: Arc<HashSet<String>>,
+}
+
+impl<A, S, B> Service<Request<B>> for SkipPaths<A, S>
+where
+    A: Service<Request<B>, Response = S::Response, Error = S::Error> + Clone + Send + 'static,
+    A::Future: Send + 'static,
+    S: Service<Request<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Readiness is awaited on whichever service ends up handling the call
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        if self.paths.contains(req.uri().path()) {
+            Box::pin(self.inner.clone().oneshot(req))
+        } else {
+            Box::pin(self.layered.clone().oneshot(req))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::HeaderValue,
+        middleware::{from_fn, Next},
+        routing::get,
+        Router,
+    };
+
+    async fn mark(req: axum::extract::Request, next: Next) -> axum::response::Response {
+        let mut response = next.run(req).await;
+        response.headers_mut().insert("x-marked", HeaderValue::from_static("1"));
+        response
+    }
+
+    #[tokio::test]
+    async fn test_listed_paths_skip_the_layer() {
+        let marker = from_fn(mark);
+        let app = Router::new()
+            .route("/probe", get(|| async { "ok" }))
+            .route("/api", get(|| async { "ok" }))
+            .layer(SkipPathsLayer::new(marker, vec!["/probe".to_string()]));
+
+        let probe = app
+            .clone()
+            .oneshot(Request::get("/probe").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(probe.headers().get("x-marked").is_none());
+
+        let api = app
+            .oneshot(Request::get("/api").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(api.headers()["x-marked"], "1");
+    }
+}
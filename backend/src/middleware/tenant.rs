@@ -0,0 +1,184 @@
+//! Tenant resolution
+//!
+//! Every request runs in exactly one tenant. [`tenant_layer`] resolves a tenant
+//! named by the request itself (the tenant header, then a subdomain of
+//! `TENANT_BASE_DOMAIN`) and stores it as a [`RequestTenant`] extension.
+//! Handlers take the [`TenantId`] extractor, which additionally falls back to
+//! the bearer token's `tenant` claim and finally the default tenant.
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderMap, HeaderName},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::{
+    config::TenantConfig,
+    error::AppError,
+    middleware::host::{request_host, strip_port},
+    types::TenantId,
+    AppState,
+};
+
+/// Tenant named explicitly by the request (header or subdomain)
+///
+/// Inserted into request extensions by [`tenant_layer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestTenant(pub TenantId);
+
+/// Resolves the tenant a request names, per [`TenantConfig`]
+#[derive(Clone, Debug)]
+pub struct TenantResolver {
+    header: HeaderName,
+    base_domain: Option<Arc<str>>,
+}
+
+impl TenantResolver {
+    pub fn new(config: &TenantConfig) -> Self {
+        Self {
+            // Validated when the config is loaded
+            header: HeaderName::from_bytes(config.header.as_bytes())
+                .unwrap_or_else(|_| HeaderName::from_static("x-tenant-id")),
+            base_domain: config.base_domain.as_deref().map(Arc::from),
+        }
+    }
+
+    /// Tenant named by the header or the host, if any
+    ///
+    /// A malformed header is an error; a host that isn't a single-label
+    /// subdomain of the base domain simply names no tenant.
+    pub fn resolve(
+        &self,
+        headers: &HeaderMap,
+        host: Option<&str>,
+    ) -> Result<Option<TenantId>, AppError> {
+        if let Some(value) = headers.get(&self.header) {
+            let value = value
+                .to_str()
+                .map_err(|_| AppError::BadRequest("Invalid tenant header".to_string()))?;
+            return TenantId::parse(value)
+                .map(Some)
+                .map_err(|e| AppError::BadRequest(format!("Invalid tenant id: {}", e)));
+        }
+
+        let (Some(base_domain), Some(host)) = (self.base_domain.as_deref(), host) else {
+            return Ok(None);
+        };
+
+        let host = strip_port(host).to_lowercase();
+        Ok(host
+            .strip_suffix(base_domain)
+            .and_then(|prefix| prefix.strip_suffix('.'))
+            .filter(|label| !label.contains('.'))
+            .and_then(|label| TenantId::parse(label).ok()))
+    }
+}
+
+/// Create a tenant resolution middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+pub fn tenant_layer(
+    resolver: TenantResolver,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |mut req: Request, next: Next| {
+        let resolver = resolver.clone();
+        Box::pin(async move {
+            let host = request_host(&req);
+
+            match resolver.resolve(req.headers(), host.as_deref()) {
+                Ok(Some(tenant)) => {
+                    req.extensions_mut().insert(RequestTenant(tenant));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(error = %e, "Rejected request with invalid tenant");
+                    return e.into_response();
+                }
+            }
+
+            next.run(req).await
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+/// Extractor for the tenant a request runs in
+///
+/// Resolution order: [`RequestTenant`] (header/subdomain), the `tenant` claim
+/// of a valid bearer token, then the default tenant.
+#[axum::async_trait]
+impl FromRequestParts<AppState> for TenantId {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(RequestTenant(tenant)) = parts.extensions.get::<RequestTenant>() {
+            return Ok(tenant.clone());
+        }
+
+        let claimed = parts
+            .headers
+            .get("authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .and_then(|token| state.jwt().verify_token(token).ok())
+            .map(|claims| claims.tenant);
+
+        Ok(claimed.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(base_domain: Option<&str>) -> TenantResolver {
+        TenantResolver::new(&TenantConfig {
+            header: "x-tenant-id".to_string(),
+            base_domain: base_domain.map(str::to_string),
+        })
+    }
+
+    fn headers(tenant: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-tenant-id", tenant.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_header_takes_precedence_over_subdomain() {
+        let tenant = resolver(Some("app.example.com"))
+            .resolve(&headers("Acme"), Some("globex.app.example.com"))
+            .unwrap();
+        assert_eq!(tenant, Some(TenantId::parse("acme").unwrap()));
+    }
+
+    #[test]
+    fn test_subdomain_of_base_domain() {
+        let resolver = resolver(Some("app.example.com"));
+        let empty = HeaderMap::new();
+
+        let tenant = resolver.resolve(&empty, Some("globex.app.example.com:8443")).unwrap();
+        assert_eq!(tenant, Some(TenantId::parse("globex").unwrap()));
+
+        assert_eq!(resolver.resolve(&empty, Some("app.example.com")).unwrap(), None);
+        assert_eq!(resolver.resolve(&empty, Some("a.b.app.example.com")).unwrap(), None);
+        assert_eq!(resolver.resolve(&empty, Some("evilapp.example.com")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_header_is_rejected() {
+        let result = resolver(None).resolve(&headers("not/valid"), None);
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_no_tenant_without_header_or_base_domain() {
+        let tenant = resolver(None).resolve(&HeaderMap::new(), Some("acme.example.com")).unwrap();
+        assert_eq!(tenant, None);
+    }
+}
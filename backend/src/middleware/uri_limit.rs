@@ -0,0 +1,75 @@
+//! Request target length limit (MAX_URI_LENGTH)
+//!
+//! The body limit does nothing for GET requests, whose whole payload is the
+//! query string. Requests whose path plus query is longer than the configured
+//! limit are answered with `414 URI Too Long` before any routing, parsing or
+//! logging of the query happens.
+use axum::{
+    body::Body,
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::AppError;
+
+/// Create a URI length limiting middleware closure
+///
+/// Returns a closure that can be used with axum::middleware::from_fn.
+/// `max_length` of 0 disables the check.
+pub fn uri_limit_layer(
+    max_length: usize,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            let length = req
+                .uri()
+                .path_and_query()
+                .map_or(0, |target| target.as_str().len());
+            if max_length > 0 && length > max_length {
+                tracing::warn!(length = length, max_length = max_length, "Rejected over-long request URI");
+                let message = if cfg!(debug_assertions) {
+                    format!("Request URI too long (limit: {} bytes)", max_length)
+                } else {
+                    "Request URI too long".to_string()
+                };
+                return AppError::UriTooLong(message).into_response();
+            }
+
+            next.run(req).await
+        })
+            as std::pin::Pin<Box<dyn std::future::Future<Output = Response<Body>> + Send>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn status_for(max_length: usize, uri: &str) -> StatusCode {
+        Router::new()
+            .route("/search", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(uri_limit_layer(max_length)))
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_over_long_query_is_rejected() {
+        let uri = format!("/search?q={}", "a".repeat(200));
+
+        assert_eq!(status_for(100, &uri).await, StatusCode::URI_TOO_LONG);
+        assert_eq!(status_for(100, "/search?q=rust").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_zero_disables_the_limit() {
+        let uri = format!("/search?q={}", "a".repeat(10_000));
+
+        assert_eq!(status_for(0, &uri).await, StatusCode::OK);
+    }
+}
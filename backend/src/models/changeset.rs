@@ -0,0 +1,106 @@
+//! Partial-update changesets
+//!
+//! A patch changeset wraps every column in `Option`: `None` leaves the column
+//! as it is, so a PATCH that sends one field can't overwrite the others.
+//! Declare one with [`patch_changeset!`](crate::patch_changeset) and pass it
+//! to `diesel::update(..).set(..)` (usually through `Timestamped::touched`):
+//!
+//! ```no_run
+//! # use backend::patch_changeset;
+//! # diesel::table! {
+//! #     posts (id) {
+//! #         id -> Int4,
+//! #         title -> Text,
+//! #         summary -> Nullable<Text>,
+//! #     }
+//! # }
+//! patch_changeset! {
+//!     /// Editable post columns
+//!     pub struct PostChangeset for posts {
+//!         title: String,
+//!         // Nullable column: `Some(None)` writes NULL
+//!         summary: Option<String>,
+//!     }
+//! }
+//! ```
+
+/// A set of column updates that may be empty
+pub trait Changeset {
+    /// Whether no column would change
+    ///
+    /// Diesel rejects an empty `SET`, so callers skip the update instead.
+    fn is_empty(&self) -> bool;
+}
+
+/// Declare a patch changeset struct for a Diesel table
+///
+/// Each field is declared with its column's Rust type and becomes an
+/// `Option` of it; only `Some` fields end up in the `UPDATE`. The struct
+/// derives `AsChangeset` and `Default`, and implements [`Changeset`].
+#[macro_export]
+macro_rules! patch_changeset {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident for $table:ident {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, diesel::AsChangeset)]
+        #[diesel(table_name = $table)]
+        $vis struct $name {
+            $($(#[$field_meta])* pub $field: Option<$ty>,)*
+        }
+
+        impl $crate::models::changeset::Changeset for $name {
+            fn is_empty(&self) -> bool {
+                true $(&& self.$field.is_none())*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::{pg::Pg, prelude::*};
+
+    diesel::table! {
+        posts (id) {
+            id -> Int4,
+            title -> Text,
+            summary -> Nullable<Text>,
+        }
+    }
+
+    patch_changeset! {
+        struct PostChangeset for posts {
+            title: String,
+            summary: Option<String>,
+        }
+    }
+
+    #[test]
+    fn test_only_set_fields_are_updated() {
+        let changes = PostChangeset {
+            summary: Some(None),
+            ..Default::default()
+        };
+        let query = diesel::update(posts::table.find(1)).set(&changes);
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+
+        assert!(sql.contains(r#""summary" = $1"#), "{}", sql);
+        assert!(!sql.contains(r#""title""#), "{}", sql);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        assert!(PostChangeset::default().is_empty());
+
+        let changes = PostChangeset {
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        };
+        assert!(!changes.is_empty());
+    }
+}
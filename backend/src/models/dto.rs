@@ -1,15 +1,21 @@
 // DTOs (Data Transfer Objects) - Request and Response types
 // These are separate from domain models to allow independent evolution
+//
+// Serialization policy for responses: an `Option` field that is `None` is
+// omitted, never sent as `null`, and enums use SCREAMING_SNAKE_CASE like
+// `error::ErrorCode`. Clients treat a missing field as "not set".
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidateEmail, ValidateLength, ValidationError, ValidationErrors};
+
+use crate::types::Patch;
 
 // ===== Auth DTOs =====
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct RegisterRequestDto {
     #[validate(email(message = "Invalid email address"))]
     #[schema(example = "user@example.com")]
@@ -24,7 +30,7 @@ pub struct RegisterRequestDto {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct LoginRequestDto {
     #[validate(email(message = "Invalid email address"))]
     #[schema(example = "user@example.com")]
@@ -48,27 +54,105 @@ pub struct UserResponseDto {
 
     #[schema(example = "2024-01-15T10:30:00")]
     pub created_at: NaiveDateTime,
+
+    /// Most recent successful sign-in; omitted until the first login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "2024-01-16T08:00:00")]
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+impl UserResponseDto {
+    /// Names accepted in a `fields` selection
+    pub const FIELDS: &'static [&'static str] =
+        &["id", "email", "username", "created_at", "last_login_at"];
+}
+
+/// Optional `fields` projection for single-resource responses
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQueryDto {
+    /// Comma-separated response fields to return, e.g. `id,email`
+    /// (default: all fields)
+    pub fields: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AuthResponseDto {
     pub user: UserResponseDto,
 
-    #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
+    /// Omitted when AUTH_TOKEN_TRANSPORT=cookie; the token is then only in the cookie
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[schema(required = false, example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
     pub token: String,
+
+    /// When the token expires (RFC 3339), so clients can refresh without decoding it
+    #[schema(example = "2024-01-16T10:30:00Z")]
+    pub expires_at: DateTime<Utc>,
+
+    /// Seconds until the token expires
+    #[schema(example = 86400)]
+    pub expires_in: i64,
+}
+
+/// Single-use nonce for a replay-protected request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NonceResponseDto {
+    #[schema(example = "Zm9vYmFyYmF6cXV4cXV1eA")]
+    pub nonce: String,
+
+    /// Seconds until the nonce expires
+    #[schema(example = 300)]
+    pub expires_in: u64,
 }
 
 // ===== User DTOs =====
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
+/// Partial profile update: omitted fields are left unchanged
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct UpdateUserRequestDto {
-    #[validate(email(message = "Invalid email address"))]
-    #[schema(example = "newemail@example.com")]
-    pub email: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "newemail@example.com")]
+    pub email: Patch<String>,
 
-    #[validate(length(min = 3, max = 100, message = "Username must be between 3 and 100 characters"))]
-    #[schema(example = "newusername")]
-    pub username: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "newusername")]
+    pub username: Patch<String>,
+}
+
+impl Validate for UpdateUserRequestDto {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        // Neither column is nullable, so an explicit null is rejected
+        match &self.email {
+            Patch::Null => errors.add("email", field_error("null", "Email cannot be null")),
+            Patch::Value(email) if !email.validate_email() => {
+                errors.add("email", field_error("email", "Invalid email address"))
+            }
+            _ => {}
+        }
+
+        match &self.username {
+            Patch::Null => errors.add("username", field_error("null", "Username cannot be null")),
+            Patch::Value(username) if !username.validate_length(Some(3), Some(100), None) => {
+                errors.add(
+                    "username",
+                    field_error("length", "Username must be between 3 and 100 characters"),
+                )
+            }
+            _ => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn field_error(code: &'static str, message: &'static str) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -80,9 +164,32 @@ pub struct ChangePasswordRequestDto {
     pub new_password: String,
 }
 
-// ===== List/Pagination DTOs =====
+/// Most users one bulk delete may remove
+pub const MAX_BULK_DELETE: u64 = 1000;
 
+/// Admin request to delete several users at once
 #[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct BulkDeleteUsersRequestDto {
+    #[validate(length(min = 1, max = "MAX_BULK_DELETE", message = "Provide between 1 and 1000 user ids"))]
+    pub ids: Vec<Uuid>,
+
+    /// Must be `true`; guards against deleting users by accident
+    #[serde(default)]
+    #[schema(example = true)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkDeleteUsersResponseDto {
+    /// Users actually removed; ids that didn't exist are not counted
+    #[schema(example = 3)]
+    pub deleted: usize,
+}
+
+// ===== List/Pagination DTOs =====
+
+#[derive(Debug, Deserialize, Validate, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListUsersRequestDto {
     #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
     #[schema(example = 20)]
@@ -91,6 +198,16 @@ pub struct ListUsersRequestDto {
     #[validate(range(min = 0, message = "Offset must be non-negative"))]
     #[schema(example = 0)]
     pub offset: Option<i64>,
+
+    /// `created_at` or `username`, prefixed with `-` for descending order or
+    /// followed by `:asc` or `:desc` (default: `-created_at`)
+    #[schema(example = "-created_at")]
+    pub sort: Option<String>,
+
+    /// Comma-separated user fields to return, e.g. `id,email`
+    /// (default: all fields)
+    #[schema(example = "id,email")]
+    pub fields: Option<String>,
 }
 
 impl Default for ListUsersRequestDto {
@@ -98,6 +215,8 @@ impl Default for ListUsersRequestDto {
         Self {
             limit: Some(20),
             offset: Some(0),
+            sort: None,
+            fields: None,
         }
     }
 }
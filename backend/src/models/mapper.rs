@@ -2,8 +2,11 @@
 // This keeps the API contract separate from internal domain logic
 
 use crate::models::{
-    dto::{AuthResponseDto, LoginRequestDto, RegisterRequestDto, UserResponseDto},
-    user::{AuthResponse, LoginRequest, RegisterRequest, User, UserResponse},
+    dto::{
+        AuthResponseDto, LoginRequestDto, RegisterRequestDto, UpdateUserRequestDto,
+        UserResponseDto,
+    },
+    user::{AuthResponse, LoginRequest, RegisterRequest, User, UserChangeset, UserResponse},
 };
 
 // ===== User Mappers =====
@@ -15,6 +18,7 @@ impl From<User> for UserResponseDto {
             email: user.email,
             username: user.username,
             created_at: user.created_at,
+            last_login_at: user.last_login_at,
         }
     }
 }
@@ -26,6 +30,17 @@ impl From<UserResponse> for UserResponseDto {
             email: response.email,
             username: response.username,
             created_at: response.created_at,
+            last_login_at: response.last_login_at,
+        }
+    }
+}
+
+impl From<UpdateUserRequestDto> for UserChangeset {
+    /// Expects a validated DTO: explicit nulls were already rejected
+    fn from(dto: UpdateUserRequestDto) -> Self {
+        UserChangeset {
+            email: dto.email.into_option().flatten(),
+            username: dto.username.into_option().flatten(),
         }
     }
 }
@@ -56,6 +71,8 @@ impl From<AuthResponse> for AuthResponseDto {
         AuthResponseDto {
             user: response.user.into(),
             token: response.token,
+            expires_at: response.expires_at,
+            expires_in: (response.expires_at - chrono::Utc::now()).num_seconds().max(0),
         }
     }
 }
@@ -1,10 +1,19 @@
+#[cfg(feature = "server")]
+pub mod changeset;
 pub mod dto;
+#[cfg(feature = "server")]
 pub mod mapper;
+#[cfg(feature = "server")]
+pub mod timestamps;
+#[cfg(feature = "server")]
 pub mod user;
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+#[cfg(feature = "server")]
+use crate::config::PaginationConfig;
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
@@ -16,6 +25,9 @@ pub struct HealthResponse {
 pub struct HealthChecks {
     pub database: SubsystemHealth,
     pub memory: SubsystemHealth,
+    /// Read replica lag, only when a replica is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica: Option<SubsystemHealth>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -27,38 +39,122 @@ pub struct SubsystemHealth {
     pub details: Option<serde_json::Value>,
 }
 
+/// Build information reported by `/api/v1/version`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VersionResponse {
+    #[schema(example = "backend")]
+    pub name: String,
+    #[schema(example = "0.1.0")]
+    pub version: String,
+}
+
+/// Liveness reported by `/api/v1/health/live` to JSON clients
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LivenessResponse {
+    /// Always "alive"; a dead process doesn't answer
+    #[schema(example = "alive")]
+    pub status: String,
+    #[schema(example = "0.1.0")]
+    pub version: String,
+}
+
+/// Startup progress reported by the readiness probe
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// "ready" or "not ready"
+    pub status: String,
+    /// Embedded migrations have been applied
+    pub migrations: bool,
+    /// Pool warmup finished (always true when DB_WARMUP is off)
+    pub warmup: bool,
+    /// A pooled connection answered DB_PROBE_QUERY
+    pub database: bool,
+}
+
+/// Runtime statistics for internal admin tooling
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminStatsResponse {
+    pub version: String,
+    pub uptime_seconds: u64,
+    /// Resident memory, when the platform exposes it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_usage_mb: Option<u64>,
+    pub pool: PoolStatsResponse,
+    pub requests: RequestStatsResponse,
+}
+
+/// Outcome of `POST /api/v1/admin/secrets/reload`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SecretReloadResponse {
+    /// Whether the JWT signing secret changed
+    pub jwt_secret_rotated: bool,
+    /// Retired JWT secrets still accepted for verification
+    pub jwt_previous_secrets: usize,
+    pub rate_limit_bypass_keys: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolStatsResponse {
+    pub size: usize,
+    pub available: usize,
+    pub max_size: usize,
+    /// Connections currently checked out of the pool
+    pub active_connections: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RequestStatsResponse {
+    /// Requests handled since startup
+    pub total: u64,
+    pub in_flight: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PaginationParams {
     #[serde(default = "default_page")]
     pub page: i64,
-    #[serde(default = "default_per_page")]
-    pub per_page: i64,
+    /// Falls back to `PAGINATION_DEFAULT` when omitted
+    #[serde(default)]
+    pub per_page: Option<i64>,
 }
 
 fn default_page() -> i64 {
     1
 }
 
-fn default_per_page() -> i64 {
-    20
-}
-
 impl Default for PaginationParams {
     fn default() -> Self {
         Self {
             page: 1,
-            per_page: 20,
+            per_page: None,
         }
     }
 }
 
+#[cfg(feature = "server")]
 impl PaginationParams {
+    /// Apply the configured default and bounds
+    ///
+    /// `page` is raised to at least 1 and `per_page` is clamped to
+    /// `1..=max_per_page`; call this before `offset()`/`limit()`.
+    pub fn clamped(&self, config: &PaginationConfig) -> Self {
+        Self {
+            page: self.page.max(1),
+            per_page: Some(
+                self.per_page
+                    .unwrap_or(config.default_per_page)
+                    .clamp(1, config.max_per_page),
+            ),
+        }
+    }
+
     pub fn offset(&self) -> i64 {
-        (self.page - 1).saturating_mul(self.per_page)
+        (self.page - 1).saturating_mul(self.limit())
     }
 
     pub fn limit(&self) -> i64 {
         self.per_page
+            .unwrap_or(crate::db::pagination::DEFAULT_PER_PAGE)
     }
 }
 
@@ -0,0 +1,104 @@
+//! `created_at` / `updated_at` audit columns
+//!
+//! Every table gets both columns, defaulting to the insert time:
+//!
+//! ```sql
+//! CREATE TABLE posts (
+//!     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+//!     -- ...
+//!     created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+//!     updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+//! );
+//!
+//! -- Optional safety net for updates that bypass `Timestamped::touched`
+//! SELECT diesel_manage_updated_at('posts');
+//! ```
+//!
+//! Inserts leave both columns to the database. Updates go through
+//! [`Timestamped::touched`], which adds `updated_at = CURRENT_TIMESTAMP` to
+//! the changeset, so no repository has to remember to bump it.
+use chrono::NaiveDateTime;
+use diesel::{
+    dsl::{now, Eq},
+    sql_types::Timestamp,
+    Column, ExpressionMethods,
+};
+
+/// An entity backed by a table with audit timestamps
+pub trait Timestamped {
+    /// The table's `updated_at` column, e.g. `users::updated_at`
+    type UpdatedAt: Column<SqlType = Timestamp> + Default;
+
+    fn created_at(&self) -> NaiveDateTime;
+
+    fn updated_at(&self) -> NaiveDateTime;
+
+    /// Whether the row changed after it was inserted
+    fn was_updated(&self) -> bool {
+        self.updated_at() > self.created_at()
+    }
+
+    /// `changes` plus `updated_at = CURRENT_TIMESTAMP`, for `diesel::update(..).set(..)`
+    fn touched<C>(changes: C) -> (C, Eq<Self::UpdatedAt, now>) {
+        (changes, Self::UpdatedAt::default().eq(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::{pg::Pg, prelude::*};
+
+    diesel::table! {
+        posts (id) {
+            id -> Int4,
+            title -> Text,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
+        }
+    }
+
+    /// A second entity, to show the trait isn't tied to `User`
+    struct Post {
+        created_at: NaiveDateTime,
+        updated_at: NaiveDateTime,
+    }
+
+    impl Timestamped for Post {
+        type UpdatedAt = posts::updated_at;
+
+        fn created_at(&self) -> NaiveDateTime {
+            self.created_at
+        }
+
+        fn updated_at(&self) -> NaiveDateTime {
+            self.updated_at
+        }
+    }
+
+    #[test]
+    fn test_touched_changeset_sets_updated_at() {
+        let query = diesel::update(posts::table.find(1))
+            .set(Post::touched(posts::title.eq("Renamed")));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+
+        assert!(sql.contains(r#""title" = $1"#), "{}", sql);
+        assert!(sql.contains(r#""updated_at" = CURRENT_TIMESTAMP"#), "{}", sql);
+    }
+
+    #[test]
+    fn test_was_updated() {
+        let created = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().naive_utc();
+        let post = Post {
+            created_at: created,
+            updated_at: created,
+        };
+        assert!(!post.was_updated());
+
+        let post = Post {
+            updated_at: created + chrono::Duration::seconds(5),
+            ..post
+        };
+        assert!(post.was_updated());
+    }
+}
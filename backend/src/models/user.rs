@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::db::schema::users;
+use crate::{db::schema::users, models::timestamps::Timestamped};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = users)]
@@ -16,6 +16,40 @@ pub struct User {
     pub password_hash: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub role: String,
+    pub tenant_id: String,
+    /// OAuth provider the account signs in with (`None` for password accounts)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The user's id at `provider`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// Most recent successful sign-in (`None` until the first login)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+/// Default role for registered users
+pub const ROLE_USER: &str = "user";
+/// Role allowed to use `/api/v1/admin/*` endpoints
+pub const ROLE_ADMIN: &str = "admin";
+
+impl User {
+    pub fn is_admin(&self) -> bool {
+        self.role == ROLE_ADMIN
+    }
+}
+
+impl Timestamped for User {
+    type UpdatedAt = users::updated_at;
+
+    fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> NaiveDateTime {
+        self.updated_at
+    }
 }
 
 #[derive(Debug, Insertable)]
@@ -24,6 +58,16 @@ pub struct NewUser {
     pub email: String,
     pub username: String,
     pub password_hash: String,
+    pub provider: Option<String>,
+    pub provider_id: Option<String>,
+}
+
+crate::patch_changeset! {
+    /// Partial update of profile columns; `None` fields are left unchanged
+    pub struct UserChangeset for users {
+        email: String,
+        username: String,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,6 +76,8 @@ pub struct UserResponse {
     pub email: String,
     pub username: String,
     pub created_at: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<NaiveDateTime>,
 }
 
 impl From<User> for UserResponse {
@@ -41,6 +87,7 @@ impl From<User> for UserResponse {
             email: user.email,
             username: user.username,
             created_at: user.created_at,
+            last_login_at: user.last_login_at,
         }
     }
 }
@@ -66,8 +113,23 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+/// A user as identified by an external sign-in provider
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    /// Provider name stored in `users.provider`, e.g. "google"
+    pub provider: String,
+    /// Stable user id at the provider (`users.provider_id`)
+    pub provider_id: String,
+    /// Email the provider has verified
+    pub email: String,
+    /// Username to use if the account has to be created
+    pub username: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub user: UserResponse,
     pub token: String,
+    /// When `token` expires
+    pub expires_at: chrono::DateTime<chrono::Utc>,
 }
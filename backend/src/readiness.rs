@@ -0,0 +1,43 @@
+//! Startup milestones gating `GET /api/v1/ready`
+//!
+//! The listener is bound before migrations and pool warmup finish, so a
+//! load balancer polling readiness must not route traffic until both are done.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Default)]
+pub struct Readiness {
+    migrations: AtomicBool,
+    warmup: AtomicBool,
+}
+
+impl Readiness {
+    /// Start not ready; `warmup_required` is false when DB_WARMUP is off
+    pub fn new(warmup_required: bool) -> Self {
+        Self {
+            migrations: AtomicBool::new(false),
+            warmup: AtomicBool::new(!warmup_required),
+        }
+    }
+
+    pub fn mark_migrations_done(&self) {
+        self.migrations.store(true, Ordering::Release);
+    }
+
+    pub fn mark_warmup_done(&self) {
+        self.warmup.store(true, Ordering::Release);
+    }
+
+    pub fn migrations_done(&self) -> bool {
+        self.migrations.load(Ordering::Acquire)
+    }
+
+    pub fn warmup_done(&self) -> bool {
+        self.warmup.load(Ordering::Acquire)
+    }
+
+    /// Every milestone has been reached
+    pub fn is_ready(&self) -> bool {
+        self.migrations_done() && self.warmup_done()
+    }
+}
@@ -1,3 +1,8 @@
+pub mod query;
 pub mod user_repository;
 
-pub use user_repository::{UserRepository, UserRepositoryTrait};
+pub use query::{FilterOp, RangeFilter, RawFilter, TextFilter};
+pub use user_repository::{
+    CollectionVersion, SortDirection, UserFilter, UserRepository, UserRepositoryTrait, UserSort,
+    UserSortField,
+};
@@ -0,0 +1,205 @@
+//! Filter parameters for list endpoints
+//!
+//! List endpoints accept `filter[<field>]=<value>` for equality and
+//! `filter[<field>][<op>]=<value>` for other comparisons, e.g.
+//! `filter[username][contains]=ann&filter[created_at][gte]=2024-01-01`.
+//! This module only splits those parameters into [`RawFilter`]s; each
+//! repository maps them onto its own allowlist of columns and operators
+//! (see `UserFilter`), so nothing a client sends is ever spliced into SQL.
+use std::fmt;
+
+/// Comparison a filter applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    /// Case-insensitive substring match
+    Contains,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl FilterOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eq => "eq",
+            Self::Contains => "contains",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+        }
+    }
+}
+
+impl fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for FilterOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eq" => Ok(Self::Eq),
+            "contains" => Ok(Self::Contains),
+            "gt" => Ok(Self::Gt),
+            "gte" => Ok(Self::Gte),
+            "lt" => Ok(Self::Lt),
+            "lte" => Ok(Self::Lte),
+            _ => Err(format!(
+                "Invalid filter operator '{}'. Expected one of: eq, contains, gt, gte, lt, lte",
+                s
+            )),
+        }
+    }
+}
+
+/// One `filter[...]` parameter, not yet checked against a column allowlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawFilter {
+    pub field: String,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+/// Filter on a text column: `eq` or `contains`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextFilter {
+    Eq(String),
+    Contains(String),
+}
+
+impl TextFilter {
+    pub fn new(field: &str, op: FilterOp, value: String) -> Result<Self, String> {
+        match op {
+            FilterOp::Eq => Ok(Self::Eq(value)),
+            FilterOp::Contains => Ok(Self::Contains(value)),
+            _ => Err(unsupported(field, op)),
+        }
+    }
+}
+
+/// Filter on an ordered column: `gt`, `gte`, `lt` or `lte`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeFilter<T> {
+    Gt(T),
+    Gte(T),
+    Lt(T),
+    Lte(T),
+}
+
+impl<T> RangeFilter<T> {
+    pub fn new(field: &str, op: FilterOp, value: T) -> Result<Self, String> {
+        match op {
+            FilterOp::Gt => Ok(Self::Gt(value)),
+            FilterOp::Gte => Ok(Self::Gte(value)),
+            FilterOp::Lt => Ok(Self::Lt(value)),
+            FilterOp::Lte => Ok(Self::Lte(value)),
+            _ => Err(unsupported(field, op)),
+        }
+    }
+}
+
+/// Error for an operator the field's allowlist doesn't include
+pub fn unsupported(field: &str, op: FilterOp) -> String {
+    format!("Filter operator '{}' is not allowed on '{}'", op, field)
+}
+
+/// Collect the `filter[...]` entries of a decoded query string
+///
+/// Other parameters are ignored. A malformed key such as `filter[a` or
+/// `filter[a][b][c]`, or an unknown operator, is an error.
+pub fn parse_filters(params: &[(String, String)]) -> Result<Vec<RawFilter>, String> {
+    params
+        .iter()
+        .filter(|(key, _)| key.starts_with("filter["))
+        .map(|(key, value)| {
+            let invalid = || format!("Invalid filter parameter '{}'", key);
+            let inner = key
+                .strip_prefix("filter[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or_else(invalid)?;
+            let (field, op) = match inner.split_once("][") {
+                Some((field, op)) => (field, op.parse()?),
+                None => (inner, FilterOp::Eq),
+            };
+            if field.is_empty() || field.contains(['[', ']']) {
+                return Err(invalid());
+            }
+            Ok(RawFilter {
+                field: field.to_string(),
+                op,
+                value: value.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Escape `%`, `_` and `\` so a value matches literally inside a LIKE pattern
+pub fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_filters_with_and_without_operator() {
+        let filters = parse_filters(&params(&[
+            ("limit", "10"),
+            ("filter[username]", "ann"),
+            ("filter[created_at][gte]", "2024-01-01"),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            filters,
+            vec![
+                RawFilter { field: "username".into(), op: FilterOp::Eq, value: "ann".into() },
+                RawFilter { field: "created_at".into(), op: FilterOp::Gte, value: "2024-01-01".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_filters_rejects_malformed_keys_and_operators() {
+        for key in ["filter[username", "filter[]", "filter[a][eq][b]", "filter[username][like]"] {
+            assert!(parse_filters(&params(&[(key, "x")])).is_err(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_typed_filters_only_accept_their_operators() {
+        assert_eq!(
+            TextFilter::new("username", FilterOp::Contains, "an".into()),
+            Ok(TextFilter::Contains("an".into()))
+        );
+        assert!(TextFilter::new("username", FilterOp::Gt, "an".into()).is_err());
+        assert_eq!(RangeFilter::new("n", FilterOp::Lte, 3), Ok(RangeFilter::Lte(3)));
+        assert!(RangeFilter::new("n", FilterOp::Contains, 3).is_err());
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+    }
+}
@@ -1,31 +1,242 @@
 use async_trait::async_trait;
-use diesel::prelude::*;
+use diesel::{pg::Pg, prelude::*};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
+use super::query::{escape_like, unsupported, FilterOp, RangeFilter, RawFilter, TextFilter};
 use crate::{
     db::{schema::users, DbPool},
     error::{AppError, DatabaseResultExt},
     logged_query,
-    models::user::{NewUser, User},
+    models::{
+        timestamps::Timestamped,
+        user::{NewUser, User, UserChangeset},
+    },
+    retry_read,
+    types::TenantId,
 };
 
+/// Column a user listing can be sorted by
+///
+/// Only these columns are accepted from clients, so a `sort` parameter can
+/// never name an arbitrary column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSortField {
+    CreatedAt,
+    Username,
+}
+
+impl UserSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::Username => "username",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Ordering of a user listing
+///
+/// Parsed from `<field>`, `-<field>` or `<field>:asc|desc`, e.g. `username`,
+/// `-created_at` or `created_at:desc`; a leading `-` means descending and the
+/// direction otherwise defaults to ascending. The default ordering is
+/// `created_at:desc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserSort {
+    pub field: UserSortField,
+    pub direction: SortDirection,
+}
+
+impl Default for UserSort {
+    fn default() -> Self {
+        Self {
+            field: UserSortField::CreatedAt,
+            direction: SortDirection::Desc,
+        }
+    }
+}
+
+impl std::str::FromStr for UserSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = match s.strip_prefix('-') {
+            Some(field) => (field, "desc"),
+            None => s.split_once(':').unwrap_or((s, "asc")),
+        };
+        let field = match field {
+            "created_at" => UserSortField::CreatedAt,
+            "username" => UserSortField::Username,
+            _ => {
+                return Err(format!(
+                    "Invalid sort field '{}'. Expected one of: created_at, username",
+                    field
+                ))
+            }
+        };
+        let direction = match direction {
+            "asc" => SortDirection::Asc,
+            "desc" => SortDirection::Desc,
+            _ => {
+                return Err(format!(
+                    "Invalid sort direction '{}'. Expected asc or desc",
+                    direction
+                ))
+            }
+        };
+        Ok(Self { field, direction })
+    }
+}
+
+/// A `filter[...]` condition on a user listing
+///
+/// Only these columns and operators are accepted from clients:
+/// `username` and `email` (`eq`, `contains`), `role` (`eq`) and
+/// `created_at` (`gt`, `gte`, `lt`, `lte`, as a date or timestamp).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserFilter {
+    Username(TextFilter),
+    Email(TextFilter),
+    Role(String),
+    CreatedAt(RangeFilter<chrono::NaiveDateTime>),
+}
+
+impl TryFrom<RawFilter> for UserFilter {
+    type Error = String;
+
+    fn try_from(raw: RawFilter) -> Result<Self, Self::Error> {
+        let field = raw.field.as_str();
+        match field {
+            "username" => Ok(Self::Username(TextFilter::new(field, raw.op, raw.value)?)),
+            "email" => Ok(Self::Email(TextFilter::new(field, raw.op, raw.value)?)),
+            "role" if raw.op == FilterOp::Eq => Ok(Self::Role(raw.value)),
+            "role" => Err(unsupported(field, raw.op)),
+            "created_at" => {
+                let at = parse_timestamp(&raw.value).ok_or_else(|| {
+                    format!(
+                        "Invalid created_at '{}'. Expected YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS",
+                        raw.value
+                    )
+                })?;
+                Ok(Self::CreatedAt(RangeFilter::new(field, raw.op, at)?))
+            }
+            _ => Err(format!(
+                "Invalid filter field '{}'. Expected one of: username, email, role, created_at",
+                field
+            )),
+        }
+    }
+}
+
+/// `YYYY-MM-DD` (midnight) or `YYYY-MM-DDTHH:MM:SS[.fff]`
+fn parse_timestamp(value: &str) -> Option<chrono::NaiveDateTime> {
+    value
+        .parse::<chrono::NaiveDateTime>()
+        .ok()
+        .or_else(|| value.parse::<chrono::NaiveDate>().ok()?.and_hms_opt(0, 0, 0))
+}
+
+/// Size and last change of a tenant's user list, for collection ETags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionVersion {
+    pub count: i64,
+    /// Latest `updated_at`, `None` when there are no users
+    pub last_updated: Option<chrono::NaiveDateTime>,
+    /// Latest `last_login_at`; logins don't touch `updated_at`
+    pub last_login: Option<chrono::NaiveDateTime>,
+}
+
+impl CollectionVersion {
+    /// Latest change to anything a listing shows, edits and logins alike
+    pub fn last_changed(&self) -> Option<chrono::NaiveDateTime> {
+        self.last_updated.max(self.last_login)
+    }
+}
+
 /// Repository trait for user data access operations
 /// Allows for easy mocking and testing
+///
+/// Every method takes the tenant it operates in; rows belonging to other
+/// tenants are never read or modified.
 #[async_trait]
 pub trait UserRepositoryTrait: Send + Sync {
-    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, AppError>;
-    async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError>;
-    async fn find_by_username(&self, username: &str) -> Result<Option<User>, AppError>;
+    async fn find_by_id(&self, tenant: &TenantId, id: Uuid) -> Result<Option<User>, AppError>;
+    async fn find_by_email(&self, tenant: &TenantId, email: &str) -> Result<Option<User>, AppError>;
+    async fn find_by_username(&self, tenant: &TenantId, username: &str) -> Result<Option<User>, AppError>;
     async fn find_by_email_or_username(
         &self,
+        tenant: &TenantId,
         email: &str,
         username: &str,
     ) -> Result<Option<User>, AppError>;
-    async fn create(&self, new_user: NewUser) -> Result<User, AppError>;
-    async fn update_password(&self, id: Uuid, password_hash: String) -> Result<User, AppError>;
-    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
-    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<User>, AppError>;
+    /// Whether a user with `email` or `username` exists, without loading it
+    async fn exists_by_email_or_username(
+        &self,
+        tenant: &TenantId,
+        email: &str,
+        username: &str,
+    ) -> Result<bool, AppError>;
+    async fn create(&self, tenant: &TenantId, new_user: NewUser) -> Result<User, AppError>;
+    /// Insert every user in one statement, returning how many were inserted
+    ///
+    /// Users whose email or username is already taken in the tenant are
+    /// skipped rather than failing the batch.
+    async fn create_many(&self, tenant: &TenantId, new_users: &[NewUser]) -> Result<usize, AppError>;
+    /// Return the user with `new_user.email`, creating it if absent
+    ///
+    /// The flag is `true` when the user was created by this call. Concurrent
+    /// callers with the same email all get the same row.
+    async fn find_or_create(
+        &self,
+        tenant: &TenantId,
+        new_user: NewUser,
+    ) -> Result<(User, bool), AppError>;
+    async fn update_password(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        password_hash: String,
+    ) -> Result<User, AppError>;
+    async fn update_profile(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        changes: UserChangeset,
+    ) -> Result<User, AppError>;
+    async fn update_role(&self, tenant: &TenantId, id: Uuid, role: &str) -> Result<User, AppError>;
+    /// Set `last_login_at` to now
+    async fn record_login(&self, tenant: &TenantId, id: Uuid) -> Result<User, AppError>;
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<(), AppError>;
+    /// Delete every listed user in the tenant, returning how many were removed
+    async fn delete_many(&self, tenant: &TenantId, ids: &[Uuid]) -> Result<usize, AppError>;
+    /// A page of users in the default order (newest first)
+    async fn list(&self, tenant: &TenantId, limit: i64, offset: i64) -> Result<Vec<User>, AppError> {
+        self.list_sorted(tenant, UserSort::default(), &[], limit, offset).await
+    }
+    /// A page of the users matching every filter, in `sort` order; ties are
+    /// broken by id, so pages never overlap
+    async fn list_sorted(
+        &self,
+        tenant: &TenantId,
+        sort: UserSort,
+        filters: &[UserFilter],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>, AppError>;
+    /// Number of users in the tenant
+    async fn count(&self, tenant: &TenantId) -> Result<i64, AppError>;
+    /// Number of users in the tenant matching every filter
+    async fn count_matching(&self, tenant: &TenantId, filters: &[UserFilter]) -> Result<i64, AppError>;
+    /// Number of users and their latest `updated_at`, in one query
+    async fn collection_version(&self, tenant: &TenantId) -> Result<CollectionVersion, AppError>;
+    /// Up to `limit` users ordered by id, starting after `after` (keyset pagination)
+    async fn list_after(&self, tenant: &TenantId, after: Option<Uuid>, limit: i64) -> Result<Vec<User>, AppError>;
 }
 
 /// Concrete implementation of UserRepository
@@ -44,102 +255,377 @@ impl UserRepository {
     }
 }
 
+/// Users visible to `tenant`; every query starts from here
+fn in_tenant(tenant: &TenantId) -> users::BoxedQuery<'_, Pg> {
+    users::table
+        .filter(users::tenant_id.eq(tenant.as_str()))
+        .into_boxed()
+}
+
+/// Narrow `query` to rows matching every filter
+fn filtered<'a>(query: users::BoxedQuery<'a, Pg>, filters: &[UserFilter]) -> users::BoxedQuery<'a, Pg> {
+    let contains = |value: &str| format!("%{}%", escape_like(value));
+    filters.iter().fold(query, |query, filter| match filter {
+        UserFilter::Username(TextFilter::Eq(value)) => query.filter(users::username.eq(value.clone())),
+        UserFilter::Username(TextFilter::Contains(value)) => {
+            query.filter(users::username.ilike(contains(value)))
+        }
+        UserFilter::Email(TextFilter::Eq(value)) => query.filter(users::email.eq(value.clone())),
+        UserFilter::Email(TextFilter::Contains(value)) => query.filter(users::email.ilike(contains(value))),
+        UserFilter::Role(role) => query.filter(users::role.eq(role.clone())),
+        UserFilter::CreatedAt(RangeFilter::Gt(at)) => query.filter(users::created_at.gt(*at)),
+        UserFilter::CreatedAt(RangeFilter::Gte(at)) => query.filter(users::created_at.ge(*at)),
+        UserFilter::CreatedAt(RangeFilter::Lt(at)) => query.filter(users::created_at.lt(*at)),
+        UserFilter::CreatedAt(RangeFilter::Lte(at)) => query.filter(users::created_at.le(*at)),
+    })
+}
+
+type UserInTenant<'a> = diesel::dsl::Filter<
+    diesel::dsl::Filter<users::table, diesel::dsl::Eq<users::tenant_id, &'a str>>,
+    diesel::dsl::Eq<users::id, Uuid>,
+>;
+
+/// A single user row in `tenant`, as an update/delete target
+fn user_in_tenant(tenant: &TenantId, id: Uuid) -> UserInTenant<'_> {
+    users::table
+        .filter(users::tenant_id.eq(tenant.as_str()))
+        .filter(users::id.eq(id))
+}
+
 #[async_trait]
 impl UserRepositoryTrait for UserRepository {
-    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, AppError> {
-        let mut conn = self.get_connection().await?;
-
-        logged_query!(
-            "SELECT * FROM users WHERE id = $1",
-            users::table
-                .find(id)
+    async fn find_by_id(&self, tenant: &TenantId, id: Uuid) -> Result<Option<User>, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND id = $2",
+            in_tenant(tenant)
+                .filter(users::id.eq(id))
                 .first::<User>(&mut conn)
                 .await
-        )
+        ))
         .optional()
         .with_db_context(|| format!("Failed to query user by id: {}", id))
     }
 
-    async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
-        let mut conn = self.get_connection().await?;
-
-        logged_query!(
-            "SELECT * FROM users WHERE email = $1",
-            users::table
+    async fn find_by_email(&self, tenant: &TenantId, email: &str) -> Result<Option<User>, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND email = $2",
+            in_tenant(tenant)
                 .filter(users::email.eq(email))
                 .first::<User>(&mut conn)
                 .await
-        )
+        ))
         .optional()
         .with_db_context(|| format!("Failed to query user by email: {}", email))
     }
 
-    async fn find_by_username(&self, username: &str) -> Result<Option<User>, AppError> {
-        let mut conn = self.get_connection().await?;
-
-        users::table
-            .filter(users::username.eq(username))
-            .first::<User>(&mut conn)
-            .await
-            .optional()
-            .with_db_context(|| format!("Failed to query user by username: {}", username))
+    async fn find_by_username(&self, tenant: &TenantId, username: &str) -> Result<Option<User>, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND username = $2",
+            in_tenant(tenant)
+                .filter(users::username.eq(username))
+                .first::<User>(&mut conn)
+                .await
+        ))
+        .optional()
+        .with_db_context(|| format!("Failed to query user by username: {}", username))
     }
 
     async fn find_by_email_or_username(
         &self,
+        tenant: &TenantId,
         email: &str,
         username: &str,
     ) -> Result<Option<User>, AppError> {
-        let mut conn = self.get_connection().await?;
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND (email = $2 OR username = $3)",
+            in_tenant(tenant)
+                .filter(users::email.eq(email).or(users::username.eq(username)))
+                .first::<User>(&mut conn)
+                .await
+        ))
+        .optional()
+        .with_db_context(|| format!("Failed to query user by email '{}' or username '{}'", email, username))
+    }
 
-        users::table
-            .filter(users::email.eq(email).or(users::username.eq(username)))
-            .first::<User>(&mut conn)
+    async fn exists_by_email_or_username(
+        &self,
+        tenant: &TenantId,
+        email: &str,
+        username: &str,
+    ) -> Result<bool, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE tenant_id = $1 AND (email = $2 OR username = $3))",
+            diesel::select(diesel::dsl::exists(
+                in_tenant(tenant).filter(users::email.eq(email).or(users::username.eq(username)))
+            ))
+            .get_result::<bool>(&mut conn)
             .await
-            .optional()
-            .with_db_context(|| format!("Failed to query user by email '{}' or username '{}'", email, username))
+        ))
+        .with_db_context(|| format!("Failed to check for user with email '{}' or username '{}'", email, username))
     }
 
-    async fn create(&self, new_user: NewUser) -> Result<User, AppError> {
+    async fn create(&self, tenant: &TenantId, new_user: NewUser) -> Result<User, AppError> {
         let mut conn = self.get_connection().await?;
 
-        diesel::insert_into(users::table)
-            .values(&new_user)
-            .get_result::<User>(&mut conn)
-            .await
-            .with_db_context(|| format!("Failed to create user with email: {}", new_user.email))
+        logged_query!(
+            "INSERT INTO users (...) VALUES (...) RETURNING *",
+            diesel::insert_into(users::table)
+                .values((&new_user, users::tenant_id.eq(tenant.as_str())))
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to create user with email: {}", new_user.email))
     }
 
-    async fn update_password(&self, id: Uuid, password_hash: String) -> Result<User, AppError> {
+    async fn create_many(&self, tenant: &TenantId, new_users: &[NewUser]) -> Result<usize, AppError> {
+        if new_users.is_empty() {
+            return Ok(0);
+        }
         let mut conn = self.get_connection().await?;
+        let rows: Vec<_> = new_users
+            .iter()
+            .map(|new_user| (new_user, users::tenant_id.eq(tenant.as_str())))
+            .collect();
 
-        diesel::update(users::table.find(id))
-            .set(users::password_hash.eq(password_hash))
-            .get_result::<User>(&mut conn)
-            .await
-            .with_db_context(|| format!("Failed to update password for user id: {}", id))
+        logged_query!(
+            "INSERT INTO users (...) VALUES (...), ... ON CONFLICT DO NOTHING",
+            diesel::insert_into(users::table)
+                .values(rows)
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to create {} users", new_users.len()))
     }
 
-    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+    async fn find_or_create(
+        &self,
+        tenant: &TenantId,
+        new_user: NewUser,
+    ) -> Result<(User, bool), AppError> {
         let mut conn = self.get_connection().await?;
 
-        diesel::delete(users::table.find(id))
-            .execute(&mut conn)
-            .await
-            .with_db_context(|| format!("Failed to delete user id: {}", id))?;
+        // The insert and the conflict check are one statement, so there's no
+        // window for another request to create the user in between
+        let created = logged_query!(
+            "INSERT INTO users ... ON CONFLICT (tenant_id, email) DO NOTHING RETURNING *",
+            diesel::insert_into(users::table)
+                .values((&new_user, users::tenant_id.eq(tenant.as_str())))
+                .on_conflict((users::tenant_id, users::email))
+                .do_nothing()
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .optional()
+        .with_db_context(|| format!("Failed to find or create user with email: {}", new_user.email))?;
+
+        if let Some(user) = created {
+            return Ok((user, true));
+        }
+
+        let existing = logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND email = $2",
+            in_tenant(tenant)
+                .filter(users::email.eq(&new_user.email))
+                .first::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to query user by email: {}", new_user.email))?;
+
+        Ok((existing, false))
+    }
+
+    async fn update_password(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        password_hash: String,
+    ) -> Result<User, AppError> {
+        let mut conn = self.get_connection().await?;
+
+        logged_query!(
+            "UPDATE users SET password_hash = $3, updated_at = now() WHERE tenant_id = $1 AND id = $2 RETURNING *",
+            diesel::update(user_in_tenant(tenant, id))
+                .set(User::touched(users::password_hash.eq(password_hash)))
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to update password for user id: {}", id))
+    }
+
+    async fn update_profile(
+        &self,
+        tenant: &TenantId,
+        id: Uuid,
+        changes: UserChangeset,
+    ) -> Result<User, AppError> {
+        let mut conn = self.get_connection().await?;
+
+        logged_query!(
+            "UPDATE users SET ..., updated_at = now() WHERE tenant_id = $1 AND id = $2 RETURNING *",
+            diesel::update(user_in_tenant(tenant, id))
+                .set(User::touched(&changes))
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to update profile for user id: {}", id))
+    }
+
+    async fn update_role(&self, tenant: &TenantId, id: Uuid, role: &str) -> Result<User, AppError> {
+        let mut conn = self.get_connection().await?;
+
+        logged_query!(
+            "UPDATE users SET role = $3, updated_at = now() WHERE tenant_id = $1 AND id = $2 RETURNING *",
+            diesel::update(user_in_tenant(tenant, id))
+                .set(User::touched(users::role.eq(role)))
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to update role for user id: {}", id))
+    }
+
+    async fn record_login(&self, tenant: &TenantId, id: Uuid) -> Result<User, AppError> {
+        let mut conn = self.get_connection().await?;
+
+        // A sign-in isn't an edit of the user, so updated_at stays as it is
+        logged_query!(
+            "UPDATE users SET last_login_at = now() WHERE tenant_id = $1 AND id = $2 RETURNING *",
+            diesel::update(user_in_tenant(tenant, id))
+                .set(users::last_login_at.eq(diesel::dsl::now.nullable()))
+                .get_result::<User>(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to record login for user id: {}", id))
+    }
+
+    async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<(), AppError> {
+        let mut conn = self.get_connection().await?;
+
+        logged_query!(
+            "DELETE FROM users WHERE tenant_id = $1 AND id = $2",
+            diesel::delete(user_in_tenant(tenant, id))
+                .execute(&mut conn)
+                .await
+        )
+        .with_db_context(|| format!("Failed to delete user id: {}", id))?;
 
         Ok(())
     }
 
-    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<User>, AppError> {
+    async fn delete_many(&self, tenant: &TenantId, ids: &[Uuid]) -> Result<usize, AppError> {
         let mut conn = self.get_connection().await?;
 
-        users::table
+        logged_query!(
+            "DELETE FROM users WHERE tenant_id = $1 AND id = ANY($2)",
+            diesel::delete(
+                users::table
+                    .filter(users::tenant_id.eq(tenant.as_str()))
+                    .filter(users::id.eq_any(ids)),
+            )
+            .execute(&mut conn)
+            .await
+        )
+        .with_db_context(|| format!("Failed to delete {} users", ids.len()))
+    }
+
+    async fn list_sorted(
+        &self,
+        tenant: &TenantId,
+        sort: UserSort,
+        filters: &[UserFilter],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>, AppError> {
+        // Boxed queries can't be cloned, so build a fresh one per attempt
+        let sorted = || {
+            let query = filtered(in_tenant(tenant), filters);
+            match (sort.field, sort.direction) {
+                (UserSortField::CreatedAt, SortDirection::Asc) => {
+                    query.order((users::created_at.asc(), users::id.asc()))
+                }
+                (UserSortField::CreatedAt, SortDirection::Desc) => {
+                    query.order((users::created_at.desc(), users::id.desc()))
+                }
+                (UserSortField::Username, SortDirection::Asc) => {
+                    query.order((users::username.asc(), users::id.asc()))
+                }
+                (UserSortField::Username, SortDirection::Desc) => {
+                    query.order((users::username.desc(), users::id.desc()))
+                }
+            }
             .limit(limit)
             .offset(offset)
-            .load::<User>(&mut conn)
-            .await
-            .with_db_context(|| format!("Failed to list users (limit: {}, offset: {})", limit, offset))
+        };
+
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND <filters> ORDER BY <sort>, id LIMIT $2 OFFSET $3",
+            sorted().load::<User>(&mut conn).await
+        ))
+        .with_db_context(|| {
+            format!(
+                "Failed to list users (sort: {}, filters: {}, limit: {}, offset: {})",
+                sort.field.as_str(),
+                filters.len(),
+                limit,
+                offset
+            )
+        })
+    }
+
+    async fn count(&self, tenant: &TenantId) -> Result<i64, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT COUNT(*) FROM users WHERE tenant_id = $1",
+            in_tenant(tenant).count().get_result::<i64>(&mut conn).await
+        ))
+        .with_db_context(|| "Failed to count users".to_string())
+    }
+
+    async fn count_matching(&self, tenant: &TenantId, filters: &[UserFilter]) -> Result<i64, AppError> {
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND <filters>",
+            filtered(in_tenant(tenant), filters)
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await
+        ))
+        .with_db_context(|| format!("Failed to count users ({} filters)", filters.len()))
+    }
+
+    async fn collection_version(&self, tenant: &TenantId) -> Result<CollectionVersion, AppError> {
+        let (count, last_updated, last_login) = retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT COUNT(*), MAX(updated_at), MAX(last_login_at) FROM users WHERE tenant_id = $1",
+            in_tenant(tenant)
+                .select((
+                    diesel::dsl::count_star(),
+                    diesel::dsl::max(users::updated_at),
+                    diesel::dsl::max(users::last_login_at),
+                ))
+                .get_result::<(i64, Option<chrono::NaiveDateTime>, Option<chrono::NaiveDateTime>)>(&mut conn)
+                .await
+        ))
+        .with_db_context(|| "Failed to read user list version".to_string())?;
+        Ok(CollectionVersion {
+            count,
+            last_updated,
+            last_login,
+        })
+    }
+
+    async fn list_after(&self, tenant: &TenantId, after: Option<Uuid>, limit: i64) -> Result<Vec<User>, AppError> {
+        // Boxed queries can't be cloned, so build a fresh one per attempt
+        let page = || {
+            let query = in_tenant(tenant).order(users::id.asc()).limit(limit);
+            match after {
+                Some(after) => query.filter(users::id.gt(after)),
+                None => query,
+            }
+        };
+
+        retry_read!(&self.db_pool, |conn| logged_query!(
+            "SELECT * FROM users WHERE tenant_id = $1 AND id > $2 ORDER BY id LIMIT $3",
+            page().load::<User>(&mut conn).await
+        ))
+        .with_db_context(|| format!("Failed to list users after {:?} (limit: {})", after, limit))
     }
 }
 
@@ -166,36 +652,88 @@ mod tests {
         }
     }
 
+    /// In-memory equivalent of `filtered`
+    fn matches(filter: &UserFilter, user: &User) -> bool {
+        let text = |filter: &TextFilter, column: &str| match filter {
+            TextFilter::Eq(value) => column == value,
+            TextFilter::Contains(value) => column.to_lowercase().contains(&value.to_lowercase()),
+        };
+        match filter {
+            UserFilter::Username(filter) => text(filter, &user.username),
+            UserFilter::Email(filter) => text(filter, &user.email),
+            UserFilter::Role(role) => &user.role == role,
+            UserFilter::CreatedAt(RangeFilter::Gt(at)) => user.created_at > *at,
+            UserFilter::CreatedAt(RangeFilter::Gte(at)) => user.created_at >= *at,
+            UserFilter::CreatedAt(RangeFilter::Lt(at)) => user.created_at < *at,
+            UserFilter::CreatedAt(RangeFilter::Lte(at)) => user.created_at <= *at,
+        }
+    }
+
+    fn find_mut<'a>(
+        users: &'a mut [User],
+        tenant: &TenantId,
+        id: Uuid,
+    ) -> Result<&'a mut User, AppError> {
+        users
+            .iter_mut()
+            .find(|u| u.id == id && u.tenant_id == tenant.as_str())
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
     #[async_trait]
     impl UserRepositoryTrait for MockUserRepository {
-        async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, AppError> {
+        async fn find_by_id(&self, tenant: &TenantId, id: Uuid) -> Result<Option<User>, AppError> {
             let users = self.users.lock().await;
-            Ok(users.iter().find(|u| u.id == id).cloned())
+            Ok(users
+                .iter()
+                .find(|u| u.tenant_id == tenant.as_str() && u.id == id)
+                .cloned())
         }
 
-        async fn find_by_email(&self, email: &str) -> Result<Option<User>, AppError> {
+        async fn find_by_email(&self, tenant: &TenantId, email: &str) -> Result<Option<User>, AppError> {
             let users = self.users.lock().await;
-            Ok(users.iter().find(|u| u.email == email).cloned())
+            Ok(users
+                .iter()
+                .find(|u| u.tenant_id == tenant.as_str() && u.email == email)
+                .cloned())
         }
 
-        async fn find_by_username(&self, username: &str) -> Result<Option<User>, AppError> {
+        async fn find_by_username(&self, tenant: &TenantId, username: &str) -> Result<Option<User>, AppError> {
             let users = self.users.lock().await;
-            Ok(users.iter().find(|u| u.username == username).cloned())
+            Ok(users
+                .iter()
+                .find(|u| u.tenant_id == tenant.as_str() && u.username == username)
+                .cloned())
         }
 
         async fn find_by_email_or_username(
             &self,
+            tenant: &TenantId,
             email: &str,
             username: &str,
         ) -> Result<Option<User>, AppError> {
             let users = self.users.lock().await;
             Ok(users
                 .iter()
-                .find(|u| u.email == email || u.username == username)
+                .find(|u| {
+                    u.tenant_id == tenant.as_str() && (u.email == email || u.username == username)
+                })
                 .cloned())
         }
 
-        async fn create(&self, new_user: NewUser) -> Result<User, AppError> {
+        async fn exists_by_email_or_username(
+            &self,
+            tenant: &TenantId,
+            email: &str,
+            username: &str,
+        ) -> Result<bool, AppError> {
+            Ok(self
+                .find_by_email_or_username(tenant, email, username)
+                .await?
+                .is_some())
+        }
+
+        async fn create(&self, tenant: &TenantId, new_user: NewUser) -> Result<User, AppError> {
             let user = User {
                 id: Uuid::new_v4(),
                 email: new_user.email,
@@ -203,35 +741,214 @@ mod tests {
                 password_hash: new_user.password_hash,
                 created_at: chrono::Utc::now().naive_utc(),
                 updated_at: chrono::Utc::now().naive_utc(),
+                role: crate::models::user::ROLE_USER.to_string(),
+                tenant_id: tenant.to_string(),
+                provider: None,
+                provider_id: None,
+                last_login_at: None,
             };
             self.users.lock().await.push(user.clone());
             Ok(user)
         }
 
-        async fn update_password(&self, id: Uuid, password_hash: String) -> Result<User, AppError> {
+        async fn create_many(&self, tenant: &TenantId, new_users: &[NewUser]) -> Result<usize, AppError> {
+            let mut users = self.users.lock().await;
+            let mut created = 0;
+            for new_user in new_users {
+                let taken = users.iter().any(|u| {
+                    u.tenant_id == tenant.as_str()
+                        && (u.email == new_user.email || u.username == new_user.username)
+                });
+                if taken {
+                    continue;
+                }
+                let now = chrono::Utc::now().naive_utc();
+                users.push(User {
+                    id: Uuid::new_v4(),
+                    email: new_user.email.clone(),
+                    username: new_user.username.clone(),
+                    password_hash: new_user.password_hash.clone(),
+                    created_at: now,
+                    updated_at: now,
+                    role: crate::models::user::ROLE_USER.to_string(),
+                    tenant_id: tenant.to_string(),
+                    provider: new_user.provider.clone(),
+                    provider_id: new_user.provider_id.clone(),
+                    last_login_at: None,
+                });
+                created += 1;
+            }
+            Ok(created)
+        }
+
+        async fn find_or_create(
+            &self,
+            tenant: &TenantId,
+            new_user: NewUser,
+        ) -> Result<(User, bool), AppError> {
+            if let Some(user) = self.find_by_email(tenant, &new_user.email).await? {
+                return Ok((user, false));
+            }
+            Ok((self.create(tenant, new_user).await?, true))
+        }
+
+        async fn update_password(
+            &self,
+            tenant: &TenantId,
+            id: Uuid,
+            password_hash: String,
+        ) -> Result<User, AppError> {
             let mut users = self.users.lock().await;
-            let user = users
-                .iter_mut()
-                .find(|u| u.id == id)
-                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            let user = find_mut(&mut users, tenant, id)?;
             user.password_hash = password_hash;
             Ok(user.clone())
         }
 
-        async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        async fn update_profile(
+            &self,
+            tenant: &TenantId,
+            id: Uuid,
+            changes: UserChangeset,
+        ) -> Result<User, AppError> {
+            let mut users = self.users.lock().await;
+            let user = find_mut(&mut users, tenant, id)?;
+            if let Some(email) = changes.email {
+                user.email = email;
+            }
+            if let Some(username) = changes.username {
+                user.username = username;
+            }
+            Ok(user.clone())
+        }
+
+        async fn update_role(&self, tenant: &TenantId, id: Uuid, role: &str) -> Result<User, AppError> {
+            let mut users = self.users.lock().await;
+            let user = find_mut(&mut users, tenant, id)?;
+            user.role = role.to_string();
+            Ok(user.clone())
+        }
+
+        async fn record_login(&self, tenant: &TenantId, id: Uuid) -> Result<User, AppError> {
             let mut users = self.users.lock().await;
-            users.retain(|u| u.id != id);
+            let user = find_mut(&mut users, tenant, id)?;
+            user.last_login_at = Some(chrono::Utc::now().naive_utc());
+            Ok(user.clone())
+        }
+
+        async fn delete(&self, tenant: &TenantId, id: Uuid) -> Result<(), AppError> {
+            let mut users = self.users.lock().await;
+            users.retain(|u| u.id != id || u.tenant_id != tenant.as_str());
             Ok(())
         }
 
-        async fn list(&self, limit: i64, offset: i64) -> Result<Vec<User>, AppError> {
+        async fn delete_many(&self, tenant: &TenantId, ids: &[Uuid]) -> Result<usize, AppError> {
+            let mut users = self.users.lock().await;
+            let before = users.len();
+            users.retain(|u| !ids.contains(&u.id) || u.tenant_id != tenant.as_str());
+            Ok(before - users.len())
+        }
+
+        async fn list_sorted(
+            &self,
+            tenant: &TenantId,
+            sort: UserSort,
+            filters: &[UserFilter],
+            limit: i64,
+            offset: i64,
+        ) -> Result<Vec<User>, AppError> {
             let users = self.users.lock().await;
-            Ok(users
+            let mut matching: Vec<User> = users
                 .iter()
+                .filter(|u| u.tenant_id == tenant.as_str() && filters.iter().all(|f| matches(f, u)))
+                .cloned()
+                .collect();
+            matching.sort_by(|a, b| {
+                let ordering = match sort.field {
+                    UserSortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                    UserSortField::Username => a.username.cmp(&b.username),
+                }
+                .then(a.id.cmp(&b.id));
+                match sort.direction {
+                    SortDirection::Asc => ordering,
+                    SortDirection::Desc => ordering.reverse(),
+                }
+            });
+            Ok(matching
+                .into_iter()
                 .skip(offset as usize)
                 .take(limit as usize)
-                .cloned()
                 .collect())
         }
+
+        async fn count(&self, tenant: &TenantId) -> Result<i64, AppError> {
+            let users = self.users.lock().await;
+            Ok(users.iter().filter(|u| u.tenant_id == tenant.as_str()).count() as i64)
+        }
+
+        async fn count_matching(&self, tenant: &TenantId, filters: &[UserFilter]) -> Result<i64, AppError> {
+            let users = self.users.lock().await;
+            Ok(users
+                .iter()
+                .filter(|u| u.tenant_id == tenant.as_str() && filters.iter().all(|f| matches(f, u)))
+                .count() as i64)
+        }
+
+        async fn collection_version(&self, tenant: &TenantId) -> Result<CollectionVersion, AppError> {
+            let users = self.users.lock().await;
+            let in_tenant = || users.iter().filter(|u| u.tenant_id == tenant.as_str());
+            Ok(CollectionVersion {
+                count: in_tenant().count() as i64,
+                last_updated: in_tenant().map(|u| u.updated_at).max(),
+                last_login: in_tenant().filter_map(|u| u.last_login_at).max(),
+            })
+        }
+
+        async fn list_after(&self, tenant: &TenantId, after: Option<Uuid>, limit: i64) -> Result<Vec<User>, AppError> {
+            let users = self.users.lock().await;
+            let mut page: Vec<User> = users
+                .iter()
+                .filter(|u| u.tenant_id == tenant.as_str() && after.map_or(true, |a| u.id > a))
+                .cloned()
+                .collect();
+            page.sort_by_key(|u| u.id);
+            page.truncate(limit as usize);
+            Ok(page)
+        }
+    }
+
+    #[test]
+    fn test_user_sort_parsing() {
+        assert_eq!(
+            "username".parse::<UserSort>().unwrap(),
+            UserSort { field: UserSortField::Username, direction: SortDirection::Asc }
+        );
+        assert_eq!("created_at:desc".parse::<UserSort>().unwrap(), UserSort::default());
+        assert_eq!("-created_at".parse::<UserSort>().unwrap(), UserSort::default());
+        assert!("-password_hash".parse::<UserSort>().is_err());
+        assert!("password_hash".parse::<UserSort>().is_err());
+        assert!("username:sideways".parse::<UserSort>().is_err());
+    }
+
+    fn raw(field: &str, op: FilterOp, value: &str) -> RawFilter {
+        RawFilter { field: field.into(), op, value: value.into() }
+    }
+
+    #[test]
+    fn test_user_filter_allowlist() {
+        assert_eq!(
+            UserFilter::try_from(raw("username", FilterOp::Contains, "ann")),
+            Ok(UserFilter::Username(TextFilter::Contains("ann".into())))
+        );
+        assert_eq!(
+            UserFilter::try_from(raw("created_at", FilterOp::Gte, "2024-01-15")),
+            Ok(UserFilter::CreatedAt(RangeFilter::Gte(
+                chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().and_hms_opt(0, 0, 0).unwrap()
+            )))
+        );
+
+        assert!(UserFilter::try_from(raw("password_hash", FilterOp::Eq, "x")).is_err());
+        assert!(UserFilter::try_from(raw("role", FilterOp::Contains, "adm")).is_err());
+        assert!(UserFilter::try_from(raw("username", FilterOp::Gt, "a")).is_err());
+        assert!(UserFilter::try_from(raw("created_at", FilterOp::Lt, "yesterday")).is_err());
     }
 }
@@ -1,47 +1,63 @@
 use axum::{
     extract::DefaultBodyLimit,
+    http::HeaderName,
     routing::get,
     Router,
 };
 use tower_http::{
     compression::CompressionLayer,
-    cors::CorsLayer,
+    cors::{AllowHeaders, AllowMethods, CorsLayer},
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
 use std::time::Duration;
-use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::{docs::ApiDoc, handlers, metrics, middleware, AppState};
+use crate::{
+    config::TokenTransport, docs, handlers, metrics, middleware, middleware::skip_paths::SkipPathsLayer,
+    AppState,
+};
 
 /// Default request body size limit: 2MB
 /// This prevents memory exhaustion attacks and oversized uploads
-const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024; // 2MB
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024; // 2MB
 
 pub fn create_router(state: AppState) -> Router {
+    // Parsed and validated when the config is loaded
     let cors_origins: Vec<_> = state
         .config
         .cors
         .allowed_origins
         .iter()
-        .filter_map(|origin| {
-            origin.parse().map_err(|e| {
-                tracing::warn!("Invalid CORS origin '{}': {}", origin, e);
-                e
-            }).ok()
-        })
+        .map(|origin| origin.header_value().clone())
         .collect();
 
     if cors_origins.is_empty() {
         tracing::warn!("No valid CORS origins configured, CORS will be restrictive");
     }
 
-    let cors = CorsLayer::new()
-        .allow_origin(cors_origins)
-        .allow_methods(tower_http::cors::Any)
-        .allow_headers(tower_http::cors::Any)
-        .allow_credentials(false); // Explicitly set - JWT in headers, not cookies
+    // In cookie mode the browser only hands a cross-origin response to the page
+    // if it allows credentials, and `*` isn't allowed alongside them, so the
+    // preflight's own method and headers are echoed back instead
+    let cookie_auth = state.config.jwt.transport == TokenTransport::Cookie;
+    let cors = CorsLayer::new().allow_origin(cors_origins);
+    let cors = if cookie_auth {
+        cors.allow_methods(AllowMethods::mirror_request())
+            .allow_headers(AllowHeaders::mirror_request())
+    } else {
+        cors.allow_methods(tower_http::cors::Any)
+            .allow_headers(tower_http::cors::Any)
+    }
+    .allow_credentials(cookie_auth);
+
+    let allowed_hosts = middleware::host::AllowedHosts::new(&state.config.server.allowed_hosts);
+    if allowed_hosts.allows_any() && state.config.is_production() {
+        tracing::warn!("ALLOWED_HOSTS accepts any host in production");
+    }
+
+    // Validated when the config is loaded
+    let request_id_header = HeaderName::from_bytes(state.config.server.request_id_header.as_bytes())
+        .unwrap_or_else(|_| HeaderName::from_static(middleware::request_id::REQUEST_ID_HEADER));
 
     // Rate limiter for auth endpoints: 10 requests per minute per IP
     // Protects against brute force attacks on login/register
@@ -49,68 +65,183 @@ pub fn create_router(state: AppState) -> Router {
     let auth_routes = Router::new()
         .route("/register", axum::routing::post(handlers::auth::register))
         .route("/login", axum::routing::post(handlers::auth::login))
-        .route("/me", get(handlers::auth::me));
+        .route("/logout", axum::routing::post(handlers::auth::logout))
+        .route(
+            "/me",
+            get(handlers::auth::me)
+                .patch(handlers::auth::update_me)
+                .delete(handlers::auth::delete_me),
+        )
+        .route("/nonce", get(handlers::auth::nonce));
+
+    #[cfg(feature = "oauth")]
+    let auth_routes = auth_routes
+        .route("/oauth/google", get(handlers::oauth::google_login))
+        .route("/oauth/google/callback", get(handlers::oauth::google_callback));
 
     // Only apply rate limiting in production builds
     #[cfg(not(debug_assertions))]
-    let auth_routes = {
-        let auth_rate_limiter = middleware::rate_limit::RateLimiter::auth(state.config.server.trust_proxy);
-        auth_routes.layer(axum::middleware::from_fn(
-            middleware::rate_limit::rate_limit_layer(auth_rate_limiter)
-        ))
-    };
+    let auth_routes = auth_routes.layer(axum::middleware::from_fn(
+        middleware::rate_limit::rate_limit_layer(state.services.auth_rate_limiter.clone())
+    ));
 
     let api_routes = Router::new()
         .route("/health", get(handlers::health_check))
-        .nest("/auth", auth_routes);
+        .route("/health/live", get(handlers::liveness))
+        .route("/ready", get(handlers::readiness_check))
+        .route("/version", get(handlers::version))
+        .nest("/auth", auth_routes)
+        .route("/admin/stats", get(handlers::admin::stats))
+        .route("/admin/secrets/reload", axum::routing::post(handlers::admin::reload_secrets))
+        .route("/users", get(handlers::user::list_users).delete(handlers::user::bulk_delete))
+        .route("/users/export.csv", get(handlers::user::export_csv));
         // Add more routes here
-        // .route("/users", get(handlers::user::list_users).post(handlers::user::create_user))
+        // .route("/users", post(handlers::user::create_user))
+        // (take `:id` with middleware::path::UuidPath so malformed ids are a JSON 404)
         // .route("/users/:id", get(handlers::user::get_user).put(handlers::user::update_user).delete(handlers::user::delete_user))
 
+    // With `otlp` and METRICS_EXEMPLARS, scrapers that negotiate OpenMetrics also get exemplars
+    #[cfg(not(feature = "otlp"))]
+    let metrics_route = get(metrics::metrics_handler);
+    #[cfg(feature = "otlp")]
+    let metrics_route = if state.config.server.metrics_exemplars {
+        get(metrics::openmetrics_handler)
+    } else {
+        get(metrics::metrics_handler)
+    };
+
     let router = Router::new()
         .merge(SwaggerUi::new("/swagger-ui")
-            .url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .route("/metrics", get(metrics::metrics_handler))
+            .url("/api-docs/openapi.json", docs::openapi(&state.config)))
+        .route("/metrics", metrics_route)
         .nest("/api/v1", api_routes);
 
-    // Add dev routes only in debug builds
+    // Add dev routes only in debug builds, unless DEV_ENDPOINTS_ENABLED=0
     #[cfg(debug_assertions)]
-    let router = {
+    let router = if !state.config.server.dev_endpoints {
+        tracing::info!("Development endpoints disabled (DEV_ENDPOINTS_ENABLED=0)");
+        router
+    } else {
         let dev_routes = Router::new()
             .route("/", get(handlers::dev::dashboard))
             .route("/state", get(handlers::dev::debug_state))
+            .route("/config", get(handlers::dev::config))
+            .route("/whoami", get(handlers::dev::whoami))
             .route("/health", get(handlers::dev::dev_health))
             .route("/echo", axum::routing::post(handlers::dev::echo))
             .route("/error/:type", get(handlers::dev::simulate_error))
             .route("/token", axum::routing::post(handlers::dev::generate_test_token))
-            .route("/db-info", get(handlers::dev::db_info));
+            .route("/db-info", get(handlers::dev::db_info))
+            .route("/seed", axum::routing::post(handlers::dev::seed))
+            .route("/slow-query", get(handlers::dev::slow_query))
+            .route("/captures", get(handlers::dev::captures));
+        #[cfg(feature = "dev-tools")]
+        let dev_routes = dev_routes.route("/generate", axum::routing::post(handlers::dev::generate));
 
-        tracing::info!("Development endpoints enabled at /dev/* (visit /dev for dashboard)");
-        router.nest("/dev", dev_routes)
+        tracing::warn!(
+            "Development endpoints enabled at /dev/* (visit /dev for dashboard). \
+             They expose config and mint tokens: set DEV_ENDPOINTS_ENABLED=0 before sharing this build"
+        );
+        // Record requests sent with X-Debug-Capture: 1 for /dev/captures
+        router
+            .nest("/dev", dev_routes)
+            .layer(axum::middleware::from_fn(middleware::capture::capture_layer(
+                state.services.captures.clone(),
+            )))
+    };
+
+    // Warn about request bodies that drift from the OpenAPI schema (dev only, opt-in)
+    #[cfg(debug_assertions)]
+    let router = if state.config.server.openapi_validation {
+        tracing::info!("OpenAPI request body validation enabled (warnings only)");
+        router.layer(axum::middleware::from_fn(
+            middleware::openapi_validation::validate_request_body,
+        ))
+    } else {
+        router
     };
 
     router
+        .fallback(handlers::fallback::not_found)
         .layer(
             tower::ServiceBuilder::new()
                 // Middleware execution order (outer → inner):
                 // 1. TraceLayer - Creates spans for distributed tracing
-                // 2. RequestID - Adds unique request ID to headers & logs
-                // 3. SecurityHeaders - Adds security headers to responses
-                // 4. Metrics - Tracks request counts and latencies
-                // 5. Compression - Compresses response bodies (gzip)
-                // 6. CORS - Handles cross-origin requests
-                // 7. Timeout - Enforces request timeout limits
-                // 8. Logging - Logs request/response details
-                // 9. BodyLimit - Enforces max body size (prevents DoS)
+                // 2. RequestID - Reads/assigns the request ID (REQUEST_ID_HEADER, traceparent)
+                // 3. UriLimit - Rejects request targets over MAX_URI_LENGTH with 414
+                // 4. LoadShed - Rejects requests over MAX_IN_FLIGHT_REQUESTS with 503
+                // 5. HostValidation - Rejects Host headers not in ALLOWED_HOSTS
+                // 6. Tenant - Resolves the tenant from the header or subdomain
+                // 7. RequestContext - Collects request ID, client IP and user agent for services
+                // 8. ServerTiming - Adds Server-Timing phase durations (SERVER_TIMING=1)
+                // 9. ConnectionLeases - Warns when a request holds > DB_MAX_CONNECTIONS_PER_REQUEST (debug)
+                // 10. SecurityHeaders - Adds security headers to responses (not on PROBE_PATHS)
+                // 11. Metrics - Tracks request counts and latencies
+                // 12. Compression - Compresses response bodies (gzip)
+                // 13. FieldCase - Renames JSON body fields to/from camelCase (API_FIELD_CASE=camel)
+                // 14. CORS - Handles cross-origin requests (not on PROBE_PATHS)
+                // 15. Timeout - Enforces request timeout limits
+                // 16. CacheControl - Adds Cache-Control/ETag, answers If-None-Match
+                // 17. Logging - Logs request/response details (quieter for LOG_QUIET_PATHS)
+                // 18. ContentType - Rejects bodies not sent as ALLOWED_CONTENT_TYPES with 415
+                // 19. MethodNotAllowed - Rewrites empty 405s into JSON errors
+                // 20. PayloadTooLarge - Rewrites body-limit 413s into JSON errors
+                // 21. BodyLimit - Enforces max body size (prevents DoS)
                 // → Handler executes here
                 .layer(TraceLayer::new_for_http())
-                .layer(axum::middleware::from_fn(middleware::request_id_middleware))
-                .layer(axum::middleware::from_fn(middleware::security_headers))
+                .layer(axum::middleware::from_fn(middleware::request_id_layer(
+                    request_id_header,
+                )))
+                .layer(axum::middleware::from_fn(middleware::uri_limit::uri_limit_layer(
+                    state.config.server.max_uri_length,
+                )))
+                .layer(SkipPathsLayer::new(
+                    axum::middleware::from_fn(middleware::load_shed::load_shed_layer(
+                        state.config.server.max_in_flight,
+                    )),
+                    state.config.server.probe_paths.clone(),
+                ))
+                .layer(axum::middleware::from_fn(middleware::host::host_validation_layer(
+                    allowed_hosts,
+                    state.config.server.trust_proxy,
+                    state.config.server.tls.is_some(),
+                )))
+                .layer(axum::middleware::from_fn(middleware::tenant::tenant_layer(
+                    middleware::tenant::TenantResolver::new(&state.config.tenant),
+                )))
+                .layer(axum::middleware::from_fn(middleware::context::request_context_layer(
+                    state.config.server.trust_proxy,
+                )))
+                .layer(axum::middleware::from_fn(
+                    middleware::server_timing::server_timing_layer(state.config.server.server_timing),
+                ))
+                .layer(axum::middleware::from_fn(
+                    middleware::connection_leases::connection_lease_layer(
+                        state.config.database.max_connections_per_request,
+                    ),
+                ))
+                .layer(SkipPathsLayer::new(
+                    axum::middleware::from_fn_with_state(state.clone(), middleware::security_headers),
+                    state.config.server.probe_paths.clone(),
+                ))
                 .layer(axum::middleware::from_fn(metrics::track_metrics))
                 .layer(CompressionLayer::new())
-                .layer(cors)
+                .layer(axum::middleware::from_fn(middleware::field_case::field_case_layer(
+                    state.config.server.field_case,
+                )))
+                .layer(SkipPathsLayer::new(cors, state.config.server.probe_paths.clone()))
                 .layer(TimeoutLayer::new(Duration::from_secs(state.config.server.request_timeout)))
-                .layer(axum::middleware::from_fn(middleware::log_request))
+                .layer(axum::middleware::from_fn(middleware::cache::cache_control_layer(
+                    state.config.server.cache_max_age,
+                )))
+                .layer(axum::middleware::from_fn(middleware::log_request_layer(
+                    state.config.server.log_quiet_paths.clone(),
+                )))
+                .layer(axum::middleware::from_fn(middleware::content_type::content_type_layer(
+                    state.config.server.allowed_content_types.clone(),
+                )))
+                .layer(axum::middleware::from_fn(handlers::fallback::method_not_allowed))
+                .layer(axum::middleware::from_fn(handlers::fallback::payload_too_large))
                 .layer(DefaultBodyLimit::max(DEFAULT_BODY_LIMIT))
         )
         .with_state(state)
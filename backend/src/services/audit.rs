@@ -0,0 +1,93 @@
+//! Audit trail of account changes
+//!
+//! Services record an [`AuditEntry`] for each security-relevant action,
+//! stamped with the [`RequestContext`] of the request that caused it. The
+//! default [`TracingAuditLog`] writes entries as `audit` target log events;
+//! implement [`AuditLog`] to ship them somewhere durable instead.
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{middleware::context::RequestContext, types::TenantId};
+
+/// What happened to the account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Register,
+    Login,
+    ExternalLogin,
+    ProfileUpdate,
+    AccountDelete,
+}
+
+/// One audited action
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub action: AuditAction,
+    pub tenant: TenantId,
+    pub user_id: Uuid,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(ctx: &RequestContext, action: AuditAction, tenant: &TenantId, user_id: Uuid) -> Self {
+        Self {
+            at: Utc::now(),
+            action,
+            tenant: tenant.clone(),
+            user_id,
+            request_id: ctx.request_id.clone(),
+            ip: ctx.ip.clone(),
+            user_agent: ctx.user_agent.clone(),
+        }
+    }
+}
+
+/// Where audit entries go
+///
+/// Recording must not fail the action being audited, so sinks handle their
+/// own errors.
+pub trait AuditLog: Send + Sync {
+    fn record(&self, entry: AuditEntry);
+}
+
+/// Log each entry as an `audit` target event
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingAuditLog;
+
+impl AuditLog for TracingAuditLog {
+    fn record(&self, entry: AuditEntry) {
+        tracing::info!(
+            target: "audit",
+            action = ?entry.action,
+            tenant = %entry.tenant,
+            user_id = %entry.user_id,
+            request_id = entry.request_id.as_deref(),
+            ip = entry.ip.as_deref(),
+            user_agent = entry.user_agent.as_deref(),
+            "Audit event"
+        );
+    }
+}
+
+/// Keep entries in memory, for tests and the dev tools
+#[derive(Debug, Default)]
+pub struct MemoryAuditLog(Mutex<Vec<AuditEntry>>);
+
+impl MemoryAuditLog {
+    /// Entries recorded so far, oldest first
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl AuditLog for MemoryAuditLog {
+    fn record(&self, entry: AuditEntry) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).push(entry);
+    }
+}
@@ -1,19 +1,39 @@
-use argon2::{
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
-};
+use argon2::password_hash::SaltString;
 use password_hash::rand_core::OsRng;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 
 use crate::{
+    config::PasswordConfig,
     error::AppError,
-    models::user::{AuthResponse, LoginRequest, NewUser, RegisterRequest, UserResponse},
+    middleware::context::RequestContext,
+    models::{
+        changeset::Changeset,
+        user::{
+            AuthResponse, ExternalIdentity, LoginRequest, NewUser, RegisterRequest, User,
+            UserChangeset, UserResponse,
+        },
+    },
     repositories::user_repository::{UserRepository, UserRepositoryTrait},
-    services::jwt::JwtService,
+    services::{
+        audit::{AuditAction, AuditEntry, AuditLog, TracingAuditLog},
+        events::{DomainEvent, EventBus},
+        jwt::{JwtService, TokenOptions},
+        password::PasswordPolicy,
+    },
+    types::TenantId,
 };
 
 pub struct AuthService<R: UserRepositoryTrait = UserRepository> {
     user_repository: R,
     jwt_service: JwtService,
+    passwords: Arc<PasswordPolicy>,
+    rehash_on_login: bool,
+    equalize_login_timing: bool,
+    /// Caps concurrent hashes on the blocking pool; shared between clones
+    hash_permits: Arc<Semaphore>,
+    audit: Arc<dyn AuditLog>,
+    events: EventBus,
 }
 
 impl<R: UserRepositoryTrait + Clone> Clone for AuthService<R> {
@@ -21,6 +41,12 @@ impl<R: UserRepositoryTrait + Clone> Clone for AuthService<R> {
         Self {
             user_repository: self.user_repository.clone(),
             jwt_service: self.jwt_service.clone(),
+            passwords: self.passwords.clone(),
+            rehash_on_login: self.rehash_on_login,
+            equalize_login_timing: self.equalize_login_timing,
+            hash_permits: self.hash_permits.clone(),
+            audit: self.audit.clone(),
+            events: self.events.clone(),
         }
     }
 }
@@ -30,20 +56,53 @@ impl<R: UserRepositoryTrait> AuthService<R> {
         Self {
             user_repository,
             jwt_service,
+            passwords: Arc::new(PasswordPolicy::default()),
+            rehash_on_login: true,
+            equalize_login_timing: true,
+            hash_permits: Arc::new(Semaphore::new(PasswordConfig::default().max_concurrent_hashes)),
+            audit: Arc::new(TracingAuditLog),
+            events: EventBus::default(),
         }
     }
 
-    #[tracing::instrument(name = "auth_register", skip(self, req), fields(email = %req.email, username = %req.username))]
-    pub async fn register(&self, req: RegisterRequest) -> Result<AuthResponse, AppError> {
+    /// Publish domain events on `events` (normally the one in `Services`)
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Send audit entries to `audit` instead of the log
+    pub fn with_audit_log(mut self, audit: Arc<dyn AuditLog>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Apply the configured password hashing policy
+    pub fn with_password_config(mut self, config: &PasswordConfig) -> Self {
+        self.passwords = Arc::new(PasswordPolicy::from_config(config));
+        self.rehash_on_login = config.rehash_on_login;
+        self.equalize_login_timing = config.equalize_login_timing;
+        self.hash_permits = Arc::new(Semaphore::new(config.max_concurrent_hashes.max(1)));
+        self
+    }
+
+    #[tracing::instrument(name = "auth_register", skip(self, ctx, req, fingerprint), fields(tenant = %tenant, email = %req.email, username = %req.username, request_id = ctx.request_id.as_deref(), ip = ctx.ip.as_deref()))]
+    pub async fn register(
+        &self,
+        ctx: &RequestContext,
+        tenant: &TenantId,
+        req: RegisterRequest,
+        fingerprint: Option<&str>,
+    ) -> Result<AuthResponse, AppError> {
         tracing::debug!("Starting user registration");
 
         // Check if user already exists
-        let existing_user = self
+        let user_exists = self
             .user_repository
-            .find_by_email_or_username(&req.email, &req.username)
+            .exists_by_email_or_username(tenant, &req.email, &req.username)
             .await?;
 
-        if existing_user.is_some() {
+        if user_exists {
             tracing::warn!("Registration failed: user already exists");
             return Err(AppError::BadRequest(
                 "User with this email or username already exists".to_string(),
@@ -53,7 +112,7 @@ impl<R: UserRepositoryTrait> AuthService<R> {
         tracing::debug!("User does not exist, proceeding with registration");
 
         // Hash password
-        let password_hash = self.hash_password(&req.password)?;
+        let password_hash = self.hash_password(&req.password).await?;
         tracing::trace!("Password hashed successfully");
 
         // Create new user
@@ -61,59 +120,182 @@ impl<R: UserRepositoryTrait> AuthService<R> {
             email: req.email,
             username: req.username,
             password_hash,
+            provider: None,
+            provider_id: None,
         };
 
-        let user = self.user_repository.create(new_user).await?;
+        let user = self.user_repository.create(tenant, new_user).await?;
         tracing::info!(user_id = %user.id, "User created successfully");
+        self.audit.record(AuditEntry::new(ctx, AuditAction::Register, tenant, user.id));
+        self.events.publish(DomainEvent::UserRegistered {
+            tenant: tenant.clone(),
+            user_id: user.id,
+            email: user.email.clone(),
+            username: user.username.clone(),
+            at: chrono::Utc::now(),
+        });
 
         // Generate JWT token
-        let token = self
-            .jwt_service
-            .generate_token(user.id, user.email.clone(), user.username.clone())?;
+        let issued = self.jwt_service.issue_token(
+            user.id,
+            user.email.clone(),
+            user.username.clone(),
+            tenant.clone(),
+            TokenOptions {
+                fingerprint,
+                ..TokenOptions::default()
+            },
+        )?;
         tracing::debug!("JWT token generated");
 
         Ok(AuthResponse {
             user: user.into(),
-            token,
+            token: issued.token,
+            expires_at: issued.expires_at,
         })
     }
 
-    #[tracing::instrument(name = "auth_login", skip(self, req), fields(email = %req.email))]
-    pub async fn login(&self, req: LoginRequest) -> Result<AuthResponse, AppError> {
+    #[tracing::instrument(name = "auth_login", skip(self, ctx, req, fingerprint), fields(tenant = %tenant, email = %req.email, request_id = ctx.request_id.as_deref(), ip = ctx.ip.as_deref()))]
+    pub async fn login(
+        &self,
+        ctx: &RequestContext,
+        tenant: &TenantId,
+        req: LoginRequest,
+        fingerprint: Option<&str>,
+    ) -> Result<AuthResponse, AppError> {
         tracing::debug!("Starting user login");
 
         // Find user by email
-        let user = self
-            .user_repository
-            .find_by_email(&req.email)
-            .await?
-            .ok_or_else(|| {
-                tracing::warn!("Login failed: user not found");
-                AppError::Unauthorized("Invalid email or password".to_string())
-            })?;
+        let Some(user) = self.user_repository.find_by_email(tenant, &req.email).await? else {
+            tracing::warn!("Login failed: user not found");
+            // Spend as long as a wrong password would
+            if self.equalize_login_timing {
+                self.verify_dummy_password(&req.password).await?;
+            }
+            return Err(AppError::Unauthorized("Invalid email or password".to_string()));
+        };
 
         tracing::debug!(user_id = %user.id, "User found");
 
         // Verify password
-        self.verify_password(&req.password, &user.password_hash)?;
+        self.verify_password(&req.password, &user.password_hash).await?;
         tracing::trace!("Password verified successfully");
 
+        // Upgrade hashes created under an older, weaker policy
+        if self.rehash_on_login && self.passwords.needs_rehash(&user.password_hash) {
+            self.rehash_password(tenant, user.id, &req.password).await;
+        }
+
+        let user = self.record_login(tenant, user).await;
+
         // Generate JWT token
-        let token = self
-            .jwt_service
-            .generate_token(user.id, user.email.clone(), user.username.clone())?;
+        let issued = self.jwt_service.issue_token(
+            user.id,
+            user.email.clone(),
+            user.username.clone(),
+            tenant.clone(),
+            TokenOptions {
+                fingerprint,
+                ..TokenOptions::default()
+            },
+        )?;
         tracing::debug!("JWT token generated");
 
         tracing::info!(user_id = %user.id, "User logged in successfully");
+        self.audit.record(AuditEntry::new(ctx, AuditAction::Login, tenant, user.id));
+        self.events.publish(DomainEvent::UserLoggedIn {
+            tenant: tenant.clone(),
+            user_id: user.id,
+            provider: None,
+            at: chrono::Utc::now(),
+        });
 
         Ok(AuthResponse {
             user: user.into(),
-            token,
+            token: issued.token,
+            expires_at: issued.expires_at,
         })
     }
 
-    #[tracing::instrument(name = "auth_get_user_by_id", skip(self), fields(user_id = %user_id))]
-    pub async fn get_user_by_id(&self, user_id: &str) -> Result<UserResponse, AppError> {
+    /// Sign in a user authenticated by an external provider
+    ///
+    /// The account is matched on email and created on first sign-in, with an
+    /// unguessable password so it can't be used for password login. An email
+    /// already linked to a different provider identity is refused.
+    #[tracing::instrument(name = "auth_login_external", skip(self, ctx, identity, fingerprint), fields(tenant = %tenant, provider = %identity.provider, request_id = ctx.request_id.as_deref(), ip = ctx.ip.as_deref()))]
+    pub async fn login_external(
+        &self,
+        ctx: &RequestContext,
+        tenant: &TenantId,
+        identity: ExternalIdentity,
+        fingerprint: Option<&str>,
+    ) -> Result<AuthResponse, AppError> {
+        let user = match self.user_repository.find_by_email(tenant, &identity.email).await? {
+            Some(user) => user,
+            None => {
+                // Random password nobody knows; the account signs in via the provider
+                let unusable_password = SaltString::generate(&mut OsRng).to_string();
+                let password_hash = self.hash_password(&unusable_password).await?;
+                let new_user = NewUser {
+                    email: identity.email.clone(),
+                    username: identity.username.clone(),
+                    password_hash,
+                    provider: Some(identity.provider.clone()),
+                    provider_id: Some(identity.provider_id.clone()),
+                };
+                let (user, created) = self.user_repository.find_or_create(tenant, new_user).await?;
+                if created {
+                    tracing::info!(user_id = %user.id, "User provisioned from external identity");
+                }
+                user
+            }
+        };
+
+        let linked_elsewhere = user.provider.is_some()
+            && (user.provider.as_deref() != Some(identity.provider.as_str())
+                || user.provider_id.as_deref() != Some(identity.provider_id.as_str()));
+        if linked_elsewhere {
+            tracing::warn!(user_id = %user.id, "External identity does not match linked account");
+            return Err(AppError::Unauthorized(
+                "Account is linked to a different sign-in".to_string(),
+            ));
+        }
+
+        let user = self.record_login(tenant, user).await;
+
+        let issued = self.jwt_service.issue_token(
+            user.id,
+            user.email.clone(),
+            user.username.clone(),
+            tenant.clone(),
+            TokenOptions {
+                fingerprint,
+                ..TokenOptions::default()
+            },
+        )?;
+
+        tracing::info!(user_id = %user.id, "User logged in with external identity");
+        self.audit.record(AuditEntry::new(ctx, AuditAction::ExternalLogin, tenant, user.id));
+        self.events.publish(DomainEvent::UserLoggedIn {
+            tenant: tenant.clone(),
+            user_id: user.id,
+            provider: Some(identity.provider),
+            at: chrono::Utc::now(),
+        });
+
+        Ok(AuthResponse {
+            user: user.into(),
+            token: issued.token,
+            expires_at: issued.expires_at,
+        })
+    }
+
+    #[tracing::instrument(name = "auth_get_user_by_id", skip(self), fields(tenant = %tenant, user_id = %user_id))]
+    pub async fn get_user_by_id(
+        &self,
+        tenant: &TenantId,
+        user_id: &str,
+    ) -> Result<UserResponse, AppError> {
         tracing::debug!("Fetching user by ID");
 
         let uuid = uuid::Uuid::parse_str(user_id)
@@ -124,7 +306,7 @@ impl<R: UserRepositoryTrait> AuthService<R> {
 
         let user = self
             .user_repository
-            .find_by_id(uuid)
+            .find_by_id(tenant, uuid)
             .await?
             .ok_or_else(|| {
                 tracing::warn!("User not found");
@@ -135,31 +317,156 @@ impl<R: UserRepositoryTrait> AuthService<R> {
         Ok(user.into())
     }
 
-    fn hash_password(&self, password: &str) -> Result<String, AppError> {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-
-        argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map(|hash| hash.to_string())
-            .map_err(|e| AppError::InternalServerError {
-                message: "Failed to hash password".to_string(),
-                source: Some(Box::new(std::io::Error::other(
-                    e.to_string(),
-                ))),
-            })
-    }
-
-    fn verify_password(&self, password: &str, hash: &str) -> Result<(), AppError> {
-        let parsed_hash = PasswordHash::new(hash).map_err(|e| AppError::InternalServerError {
-            message: "Invalid password hash".to_string(),
-            source: Some(Box::new(std::io::Error::other(
-                e.to_string(),
-            ))),
-        })?;
-
-        Argon2::default()
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .map_err(|_| AppError::Unauthorized("Invalid email or password".to_string()))
+    #[tracing::instrument(name = "auth_update_profile", skip(self, ctx, changes), fields(tenant = %tenant, user_id = %user_id, request_id = ctx.request_id.as_deref(), ip = ctx.ip.as_deref()))]
+    pub async fn update_profile(
+        &self,
+        ctx: &RequestContext,
+        tenant: &TenantId,
+        user_id: &str,
+        changes: UserChangeset,
+        unmodified_since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<UserResponse, AppError> {
+        let uuid = uuid::Uuid::parse_str(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        // If-Unmodified-Since: refuse to overwrite changes the client hasn't seen
+        if let Some(since) = unmodified_since {
+            let current = self
+                .user_repository
+                .find_by_id(tenant, uuid)
+                .await?
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            if crate::middleware::cache::modified_since(current.updated_at, since) {
+                tracing::info!(updated_at = %current.updated_at, "Profile changed since If-Unmodified-Since");
+                return Err(AppError::PreconditionFailed(
+                    "The profile was modified after If-Unmodified-Since".to_string(),
+                ));
+            }
+        }
+
+        if changes.is_empty() {
+            tracing::debug!("No profile fields provided, returning current user");
+            return self.get_user_by_id(tenant, user_id).await;
+        }
+
+        // Reject values already used by another account
+        if let Some(email) = &changes.email {
+            if let Some(other) = self.user_repository.find_by_email(tenant, email).await? {
+                if other.id != uuid {
+                    return Err(AppError::BadRequest("Email is already taken".to_string()));
+                }
+            }
+        }
+        if let Some(username) = &changes.username {
+            if let Some(other) = self.user_repository.find_by_username(tenant, username).await? {
+                if other.id != uuid {
+                    return Err(AppError::BadRequest("Username is already taken".to_string()));
+                }
+            }
+        }
+
+        let user = self.user_repository.update_profile(tenant, uuid, changes).await?;
+        tracing::info!("User profile updated");
+        self.audit.record(AuditEntry::new(ctx, AuditAction::ProfileUpdate, tenant, uuid));
+        Ok(user.into())
+    }
+
+    #[tracing::instrument(name = "auth_delete_account", skip(self, ctx), fields(tenant = %tenant, user_id = %user_id, request_id = ctx.request_id.as_deref(), ip = ctx.ip.as_deref()))]
+    pub async fn delete_account(
+        &self,
+        ctx: &RequestContext,
+        tenant: &TenantId,
+        user_id: &str,
+    ) -> Result<(), AppError> {
+        let uuid = uuid::Uuid::parse_str(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user ID".to_string()))?;
+
+        self.user_repository.delete(tenant, uuid).await?;
+        self.audit.record(AuditEntry::new(ctx, AuditAction::AccountDelete, tenant, uuid));
+        Ok(())
+    }
+
+    /// Re-hash a verified password with the current policy
+    ///
+    /// Failures are logged and ignored; the user is already authenticated and
+    /// the upgrade will be retried on their next login.
+    async fn rehash_password(&self, tenant: &TenantId, user_id: uuid::Uuid, password: &str) {
+        let password_hash = match self.hash_password(password).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, error = %e, "Failed to rehash password");
+                return;
+            }
+        };
+
+        match self.user_repository.update_password(tenant, user_id, password_hash).await {
+            Ok(_) => {
+                tracing::info!(user_id = %user_id, "Password hash upgraded to current policy");
+                self.events.publish(DomainEvent::PasswordChanged {
+                    tenant: tenant.clone(),
+                    user_id,
+                    at: chrono::Utc::now(),
+                });
+            }
+            Err(e) => tracing::warn!(user_id = %user_id, error = %e, "Failed to store rehashed password"),
+        }
+    }
+
+    /// Stamp `last_login_at`, returning the updated user
+    ///
+    /// Failures are logged and the user returned unchanged; a missed
+    /// timestamp isn't worth failing a successful login over.
+    async fn record_login(&self, tenant: &TenantId, user: User) -> User {
+        match self.user_repository.record_login(tenant, user.id).await {
+            Ok(updated) => updated,
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "Failed to record login time");
+                user
+            }
+        }
+    }
+
+    /// Run a CPU-bound hashing operation on the blocking thread pool
+    ///
+    /// Hashing takes tens of milliseconds of pure CPU; running it inline would
+    /// stall the async worker threads. The semaphore bounds how many run at
+    /// once so a burst of logins can't exhaust the blocking pool either.
+    async fn run_blocking<T, F>(&self, task: F) -> Result<T, AppError>
+    where
+        F: FnOnce() -> Result<T, AppError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let _permit = self
+            .hash_permits
+            .acquire()
+            .await
+            .map_err(|e| AppError::internal("Password hashing pool closed", e))?;
+
+        tokio::task::spawn_blocking(task)
+            .await
+            .map_err(|e| AppError::internal("Password hashing task failed", e))?
+    }
+
+    async fn hash_password(&self, password: &str) -> Result<String, AppError> {
+        let passwords = self.passwords.clone();
+        let password = password.to_string();
+        self.run_blocking(move || passwords.hash(&password)).await
+    }
+
+    async fn verify_dummy_password(&self, password: &str) -> Result<(), AppError> {
+        let passwords = self.passwords.clone();
+        let password = password.to_string();
+        self.run_blocking(move || {
+            passwords.verify_dummy(&password);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn verify_password(&self, password: &str, hash: &str) -> Result<(), AppError> {
+        let passwords = self.passwords.clone();
+        let password = password.to_string();
+        let hash = hash.to_string();
+        self.run_blocking(move || passwords.verify(&password, &hash)).await
     }
 }
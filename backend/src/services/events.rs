@@ -0,0 +1,125 @@
+//! In-process domain events
+//!
+//! Services publish a [`DomainEvent`] on the shared [`EventBus`] after a
+//! change is committed; integrations (webhooks, analytics, CRM sync)
+//! subscribe instead of being called from the auth flow. Delivery is
+//! best-effort: events published with nobody subscribed are dropped, and a
+//! subscriber that falls more than the bus capacity behind misses the oldest
+//! ones (`RecvError::Lagged`).
+//!
+//! ```no_run
+//! # async fn send_webhook(body: &str) {}
+//! # fn example(state: backend::AppState) {
+//! let mut events = state.events().subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         if let Ok(body) = serde_json::to_string(&event) {
+//!             send_webhook(&body).await;
+//!         }
+//!     }
+//! });
+//! # }
+//! ```
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::types::TenantId;
+
+/// Events kept for subscribers that fall behind
+pub const DEFAULT_EVENT_BUS_CAPACITY: usize = 256;
+
+/// Something that happened to a user, serialized as `{"type": "user_registered", ...}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    UserRegistered {
+        tenant: TenantId,
+        user_id: Uuid,
+        email: String,
+        username: String,
+        at: DateTime<Utc>,
+    },
+    UserLoggedIn {
+        tenant: TenantId,
+        user_id: Uuid,
+        /// External identity provider, `None` for password logins
+        provider: Option<String>,
+        at: DateTime<Utc>,
+    },
+    /// The stored password hash was replaced; today that's only the upgrade
+    /// to the current hashing policy on login (ARGON2_REHASH_ON_LOGIN)
+    PasswordChanged {
+        tenant: TenantId,
+        user_id: Uuid,
+        at: DateTime<Utc>,
+    },
+}
+
+/// Broadcast channel of [`DomainEvent`]s; clones share the channel
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUS_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Send `event` to current subscribers; never fails the caller
+    pub fn publish(&self, event: DomainEvent) {
+        if self.sender.send(event).is_err() {
+            tracing::trace!("Domain event dropped: no subscribers");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_published_after_subscribing() {
+        let bus = EventBus::default();
+        let event = DomainEvent::PasswordChanged {
+            tenant: TenantId::default(),
+            user_id: Uuid::nil(),
+            at: Utc::now(),
+        };
+
+        bus.publish(event.clone());
+        let mut events = bus.subscribe();
+        bus.publish(event.clone());
+
+        assert_eq!(events.recv().await.unwrap(), event);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_event_serializes_with_type_tag() {
+        let json = serde_json::to_value(DomainEvent::UserLoggedIn {
+            tenant: TenantId::default(),
+            user_id: Uuid::nil(),
+            provider: None,
+            at: Utc::now(),
+        })
+        .unwrap();
+
+        assert_eq!(json["type"], "user_logged_in");
+        assert_eq!(json["user_id"], Uuid::nil().to_string());
+    }
+}
@@ -0,0 +1,215 @@
+//! Shared outbound HTTP client with retries
+//!
+//! Calls to external services go through [`HttpClient::send`], which retries
+//! transient failures (connection errors, timeouts and the statuses in
+//! HTTP_RETRY_STATUSES) with exponential backoff. Only idempotent methods
+//! (HTTP_RETRY_METHODS) are retried, since a POST that timed out may still
+//! have been applied upstream. A `Retry-After` on the response replaces the
+//! computed delay; when it asks for longer than HTTP_RETRY_MAX_BACKOFF_MS the
+//! response is returned as is.
+//!
+//! ```no_run
+//! # async fn example(state: backend::AppState) -> Result<(), reqwest::Error> {
+//! let response = state
+//!     .http()
+//!     .send(state.http().get("https://api.example.com/status"))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+use password_hash::rand_core::{OsRng, RngCore};
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::HttpClientConfig;
+
+/// `reqwest::Client` plus the retry policy; clones share both
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    policy: Arc<HttpClientConfig>,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(HttpClientConfig::default())
+    }
+}
+
+impl HttpClient {
+    pub fn new(policy: HttpClientConfig) -> Self {
+        Self::with_client(reqwest::Client::new(), policy)
+    }
+
+    /// Apply `policy` to a preconfigured `reqwest::Client`
+    pub fn with_client(client: reqwest::Client, policy: HttpClientConfig) -> Self {
+        Self {
+            client,
+            policy: Arc::new(policy),
+        }
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    /// Send `request`, retrying transient failures as the policy allows
+    ///
+    /// Returns the last response even when its status is an error; only
+    /// transport failures are `Err`. Requests with a streaming body can't be
+    /// replayed and are sent once.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let mut request = request.build()?;
+        let retry_method = self.retries_method(request.method());
+
+        let mut attempt = 1;
+        loop {
+            let next = if retry_method && attempt < self.policy.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = self.client.execute(request).await;
+            let Some(next) = next else {
+                return result;
+            };
+
+            let delay = match &result {
+                Ok(response) if self.retries_status(response.status()) => {
+                    match retry_after(response.headers()) {
+                        Some(wait) if wait > self.max_backoff() => return result,
+                        Some(wait) => wait,
+                        None => self.backoff(attempt),
+                    }
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => self.backoff(attempt),
+                _ => return result,
+            };
+            tracing::debug!(
+                url = %next.url(),
+                attempt = attempt,
+                status = result.as_ref().ok().map(|r| r.status().as_u16()),
+                delay_ms = delay.as_millis() as u64,
+                "Retrying outbound request"
+            );
+
+            tokio::time::sleep(delay).await;
+            request = next;
+            attempt += 1;
+        }
+    }
+
+    fn retries_method(&self, method: &Method) -> bool {
+        self.policy
+            .retry_methods
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method.as_str()))
+    }
+
+    fn retries_status(&self, status: StatusCode) -> bool {
+        self.policy.retry_statuses.contains(&status.as_u16())
+    }
+
+    fn max_backoff(&self) -> Duration {
+        Duration::from_millis(self.policy.max_backoff_ms)
+    }
+
+    /// Delay after failed attempt `attempt` (1-based): base doubled per
+    /// attempt, capped, then jittered into its upper half
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .policy
+            .base_backoff_ms
+            .saturating_mul(1u64 << (attempt - 1).min(32));
+        let capped = exponential.min(self.policy.max_backoff_ms);
+        let millis = if self.policy.jitter && capped > 1 {
+            capped / 2 + OsRng.next_u64() % (capped - capped / 2 + 1)
+        } else {
+            capped
+        };
+        Duration::from_millis(millis)
+    }
+}
+
+/// `Retry-After` as either delay seconds or an HTTP date
+fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(policy: HttpClientConfig) -> HttpClient {
+        HttpClient::new(policy)
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let client = client(HttpClientConfig {
+            base_backoff_ms: 100,
+            max_backoff_ms: 300,
+            jitter: false,
+            ..HttpClientConfig::default()
+        });
+
+        assert_eq!(client.backoff(1), Duration::from_millis(100));
+        assert_eq!(client.backoff(2), Duration::from_millis(200));
+        assert_eq!(client.backoff(3), Duration::from_millis(300));
+        assert_eq!(client.backoff(40), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_the_upper_half() {
+        let client = client(HttpClientConfig {
+            base_backoff_ms: 1000,
+            ..HttpClientConfig::default()
+        });
+
+        for _ in 0..100 {
+            let delay = client.backoff(1);
+            assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_seconds_and_dates() {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, "2".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+
+        headers.insert(header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_only_idempotent_methods_are_retried() {
+        let client = HttpClient::default();
+
+        assert!(client.retries_method(&Method::GET));
+        assert!(client.retries_method(&Method::PUT));
+        assert!(!client.retries_method(&Method::POST));
+        assert!(!client.retries_method(&Method::PATCH));
+    }
+}
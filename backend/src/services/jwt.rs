@@ -1,72 +1,200 @@
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, encode, errors::ErrorKind, DecodingKey, EncodingKey, Header, Validation,
+};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
-use crate::error::AppError;
+use crate::{error::AppError, types::TenantId};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,  // subject (user id)
     pub email: String,
     pub username: String,
+    /// Tenant the user belongs to; tokens issued before tenancy get the default
+    #[serde(default)]
+    pub tenant: TenantId,
     pub exp: i64,     // expiration time
     pub iat: i64,     // issued at
+    /// Not valid before this time; absent for tokens valid on issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    /// Fingerprint of the client the token was issued to (JWT_BIND_FINGERPRINT)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpt: Option<String>,
 }
 
+/// A signed token and the moment it stops being accepted
+#[derive(Debug, Clone)]
+pub struct IssuedToken {
+    pub token: String,
+    /// Same instant as the token's `exp` claim
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Optional restrictions on an issued token; the default has none
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenOptions<'a> {
+    /// Bind the token to this client fingerprint (JWT_BIND_FINGERPRINT)
+    pub fingerprint: Option<&'a str>,
+    /// Valid only once this has passed, carried in the `nbf` claim
+    pub activation_delay: Option<Duration>,
+}
+
+/// Source of the current time, replaceable in tests
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Seconds of clock skew tolerated on `exp` and `nbf`, as `jsonwebtoken` does
+const CLOCK_LEEWAY_SECS: i64 = 60;
+
+/// Signing secret and the retired secrets still accepted for verification
+struct JwtKeys {
+    secret: String,
+    /// Verification-only secrets, kept while rotating away from them
+    previous_secrets: Vec<String>,
+}
+
+/// Clones share their keys, so [`rotate`](JwtService::rotate) reaches every
+/// copy (e.g. the one inside `AuthService`)
 #[derive(Clone)]
 pub struct JwtService {
-    secret: String,
+    keys: Arc<RwLock<JwtKeys>>,
     expiration_hours: i64,
+    clock: Clock,
 }
 
 impl JwtService {
     pub fn new(secret: String, expiration_hours: i64) -> Self {
         Self {
-            secret,
+            keys: Arc::new(RwLock::new(JwtKeys {
+                secret,
+                previous_secrets: Vec::new(),
+            })),
             expiration_hours,
+            clock: Arc::new(Utc::now),
         }
     }
 
-    pub fn generate_token(
+    /// Read the time from `clock` when issuing and verifying tokens
+    pub fn with_clock(mut self, clock: impl Fn() -> DateTime<Utc> + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Also accept tokens signed with these secrets (never used for signing)
+    pub fn with_previous_secrets(mut self, previous_secrets: Vec<String>) -> Self {
+        // A builder call configures this service alone, not its earlier clones
+        let secret = self.keys().secret.clone();
+        self.keys = Arc::new(RwLock::new(JwtKeys {
+            secret,
+            previous_secrets,
+        }));
+        self
+    }
+
+    /// Replace the signing secret and the verification-only secrets
+    ///
+    /// Takes effect for every clone of this service. Returns whether the
+    /// signing secret changed.
+    pub fn rotate(&self, secret: String, previous_secrets: Vec<String>) -> bool {
+        let mut keys = self.keys.write().unwrap_or_else(|e| e.into_inner());
+        let changed = keys.secret != secret;
+        *keys = JwtKeys {
+            secret,
+            previous_secrets,
+        };
+        changed
+    }
+
+    fn keys(&self) -> std::sync::RwLockReadGuard<'_, JwtKeys> {
+        self.keys.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Issue a token and report when it expires
+    ///
+    /// It expires `expiration_hours` after issue, whatever `options` say.
+    pub fn issue_token(
         &self,
         user_id: Uuid,
         email: String,
         username: String,
-    ) -> Result<String, AppError> {
+        tenant: TenantId,
+        options: TokenOptions<'_>,
+    ) -> Result<IssuedToken, AppError> {
         if self.expiration_hours <= 0 {
             return Err(AppError::ConfigError(
                 "JWT expiration hours must be positive".to_string(),
             ));
         }
 
-        let now = Utc::now();
-        let expires_at = now + Duration::hours(self.expiration_hours);
+        let now = (self.clock)();
+        // Whole seconds, like the `exp` claim
+        let expires_at = DateTime::from_timestamp(
+            (now + Duration::hours(self.expiration_hours)).timestamp(),
+            0,
+        )
+        .unwrap_or(now);
 
         let claims = Claims {
             sub: user_id.to_string(),
             email,
             username,
+            tenant,
             exp: expires_at.timestamp(),
             iat: now.timestamp(),
+            nbf: options.activation_delay.map(|delay| (now + delay).timestamp()),
+            fpt: options.fingerprint.map(str::to_string),
         };
 
-        encode(
+        let token = encode(
             &Header::default(),
             &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
+            &EncodingKey::from_secret(self.keys().secret.as_bytes()),
         )
-        .map_err(|e| AppError::internal("Failed to generate JWT token", e))
+        .map_err(|e| AppError::internal("Failed to generate JWT token", e))?;
+
+        Ok(IssuedToken { token, expires_at })
     }
 
+    /// Verify a token against the primary secret, then any previous secrets
+    ///
+    /// Only a signature mismatch falls through to the next secret; any other
+    /// failure (e.g. expiry) means the signing key was found and is final.
+    /// `exp` and `nbf` are checked against the service's clock.
     pub fn verify_token(&self, token: &str) -> Result<Claims, AppError> {
-        decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map(|data| data.claims)
-        .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))
+        let mut last_error = None;
+
+        // Timing is checked below so it follows `self.clock`
+        let mut validation = Validation::default();
+        validation.validate_exp = false;
+        validation.validate_nbf = false;
+
+        let keys = self.keys();
+        for secret in std::iter::once(&keys.secret).chain(&keys.previous_secrets) {
+            match decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation) {
+                Ok(data) => return self.check_validity_window(data.claims),
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => last_error = Some(e),
+                // Expired vs malformed map to TOKEN_EXPIRED / TOKEN_INVALID
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let e = last_error.expect("at least the primary secret is tried");
+        Err(e.into())
+    }
+
+    /// Reject tokens that have expired or aren't active yet
+    fn check_validity_window(&self, claims: Claims) -> Result<Claims, AppError> {
+        let now = (self.clock)().timestamp();
+        if claims.exp < now - CLOCK_LEEWAY_SECS {
+            return Err(jsonwebtoken::errors::Error::from(ErrorKind::ExpiredSignature).into());
+        }
+        if claims.nbf.is_some_and(|nbf| nbf > now + CLOCK_LEEWAY_SECS) {
+            return Err(AppError::TokenInvalid("Token is not valid yet".to_string()));
+        }
+        Ok(claims)
     }
 }
 
@@ -80,16 +208,19 @@ mod tests {
         let user_id = Uuid::new_v4();
         let email = "test@example.com".to_string();
         let username = "testuser".to_string();
+        let tenant = TenantId::parse("acme").unwrap();
 
         let token = jwt_service
-            .generate_token(user_id, email.clone(), username.clone())
-            .unwrap();
+            .issue_token(user_id, email.clone(), username.clone(), tenant, TokenOptions::default())
+            .unwrap()
+            .token;
 
         let claims = jwt_service.verify_token(&token).unwrap();
 
         assert_eq!(claims.sub, user_id.to_string());
         assert_eq!(claims.email, email);
         assert_eq!(claims.username, username);
+        assert_eq!(claims.tenant.as_str(), "acme");
     }
 
     #[test]
@@ -99,4 +230,194 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_rotation_accepts_tokens_signed_with_previous_secret() {
+        let user_id = Uuid::new_v4();
+        let old_service = JwtService::new("old_secret_key".to_string(), 24);
+        let token = old_service
+            .issue_token(
+                user_id,
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions::default(),
+            )
+            .unwrap()
+            .token;
+
+        // Rotate: new primary, old secret kept for verification only
+        let rotated = JwtService::new("new_secret_key".to_string(), 24)
+            .with_previous_secrets(vec!["old_secret_key".to_string()]);
+
+        let claims = rotated.verify_token(&token).unwrap();
+        assert_eq!(claims.sub, user_id.to_string());
+
+        // New tokens are signed with the new primary only
+        let new_token = rotated
+            .issue_token(
+                user_id,
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions::default(),
+            )
+            .unwrap()
+            .token;
+        assert!(old_service.verify_token(&new_token).is_err());
+
+        // Once the old secret is dropped, its tokens are rejected
+        let fully_rotated = JwtService::new("new_secret_key".to_string(), 24);
+        assert!(fully_rotated.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_rotate_updates_every_clone() {
+        let service = JwtService::new("old_secret_key".to_string(), 24);
+        let clone = service.clone();
+        let token = service
+            .issue_token(
+                Uuid::new_v4(),
+                "a@example.com".to_string(),
+                "a".to_string(),
+                TenantId::default(),
+                TokenOptions::default(),
+            )
+            .unwrap()
+            .token;
+
+        assert!(service.rotate("new_secret_key".to_string(), vec!["old_secret_key".to_string()]));
+        assert!(clone.verify_token(&token).is_ok());
+
+        // Dropping the old secret ends its tokens' validity everywhere
+        assert!(!service.rotate("new_secret_key".to_string(), Vec::new()));
+        assert!(clone.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_expired_and_malformed_tokens_are_distinguished() {
+        let jwt_service = JwtService::new("test_secret_key".to_string(), 24);
+        let now = Utc::now();
+        let claims = Claims {
+            sub: Uuid::new_v4().to_string(),
+            email: "test@example.com".to_string(),
+            username: "testuser".to_string(),
+            tenant: TenantId::default(),
+            exp: (now - Duration::hours(1)).timestamp(),
+            iat: (now - Duration::hours(2)).timestamp(),
+            nbf: None,
+            fpt: None,
+        };
+        let expired = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"test_secret_key"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            jwt_service.verify_token(&expired),
+            Err(AppError::TokenExpired(_))
+        ));
+        assert!(matches!(
+            jwt_service.verify_token("garbage"),
+            Err(AppError::TokenInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_token_with_future_nbf_is_rejected_until_active() {
+        let issued_at = Utc::now();
+        let now = Arc::new(std::sync::Mutex::new(issued_at));
+        let clock = now.clone();
+        let jwt_service = JwtService::new("test_secret_key".to_string(), 24)
+            .with_clock(move || *clock.lock().unwrap());
+
+        let token = jwt_service
+            .issue_token(
+                Uuid::new_v4(),
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions {
+                    activation_delay: Some(Duration::hours(1)),
+                    ..TokenOptions::default()
+                },
+            )
+            .unwrap()
+            .token;
+
+        assert!(matches!(
+            jwt_service.verify_token(&token),
+            Err(AppError::TokenInvalid(_))
+        ));
+
+        *now.lock().unwrap() = issued_at + Duration::minutes(59);
+        assert!(jwt_service.verify_token(&token).is_ok(), "accepted within the clock leeway");
+
+        *now.lock().unwrap() = issued_at + Duration::hours(2);
+        let claims = jwt_service.verify_token(&token).unwrap();
+        assert_eq!(claims.nbf, Some((issued_at + Duration::hours(1)).timestamp()));
+    }
+
+    #[test]
+    fn test_tokens_without_delay_are_valid_immediately() {
+        let jwt_service = JwtService::new("test_secret_key".to_string(), 24);
+        let token = jwt_service
+            .issue_token(
+                Uuid::new_v4(),
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions::default(),
+            )
+            .unwrap()
+            .token;
+
+        let claims = jwt_service.verify_token(&token).unwrap();
+        assert_eq!(claims.nbf, None);
+    }
+
+    #[test]
+    fn test_delayed_token_can_be_bound_to_a_fingerprint() {
+        let jwt_service = JwtService::new("test_secret_key".to_string(), 24);
+        let issued = jwt_service
+            .issue_token(
+                Uuid::new_v4(),
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions {
+                    fingerprint: Some("client-a"),
+                    activation_delay: Some(Duration::hours(1)),
+                },
+            )
+            .unwrap();
+
+        let later = JwtService::new("test_secret_key".to_string(), 24)
+            .with_clock(move || Utc::now() + Duration::hours(2));
+        let claims = later.verify_token(&issued.token).unwrap();
+        assert!(claims.nbf.is_some());
+        assert_eq!(claims.fpt.as_deref(), Some("client-a"));
+    }
+
+    #[test]
+    fn test_expiry_follows_the_injected_clock() {
+        let issued_at = Utc::now();
+        let token = JwtService::new("test_secret_key".to_string(), 1)
+            .with_clock(move || issued_at)
+            .issue_token(
+                Uuid::new_v4(),
+                "test@example.com".to_string(),
+                "testuser".to_string(),
+                TenantId::default(),
+                TokenOptions::default(),
+            )
+            .unwrap()
+            .token;
+
+        let later = JwtService::new("test_secret_key".to_string(), 1)
+            .with_clock(move || issued_at + Duration::hours(2));
+        assert!(matches!(later.verify_token(&token), Err(AppError::TokenExpired(_))));
+    }
 }
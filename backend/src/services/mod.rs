@@ -1,5 +1,14 @@
+pub mod audit;
 pub mod auth;
+pub mod events;
+pub mod http;
 pub mod jwt;
+pub mod nonce;
+#[cfg(feature = "oauth")]
+pub mod oauth;
+pub mod password;
+pub mod rotation;
+pub mod throttle;
 
 use crate::db::DbPool;
 
@@ -0,0 +1,158 @@
+//! Single-use nonces for replay protection on sensitive mutations
+//!
+//! A client fetches a nonce from `GET /api/v1/auth/nonce` and sends it back in
+//! the `X-Nonce` header of the mutation. Each nonce is bound to the user it
+//! was issued to, expires after a short TTL and can be consumed once; a
+//! replayed request is answered with 409 Conflict.
+use password_hash::{rand_core::OsRng, SaltString};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+
+/// How long an issued nonce stays valid
+pub const DEFAULT_NONCE_TTL: Duration = Duration::from_secs(300);
+
+/// In-memory store of issued nonces
+#[derive(Clone)]
+pub struct NonceStore {
+    nonces: Arc<Mutex<HashMap<String, IssuedNonce>>>,
+    ttl: Duration,
+}
+
+struct IssuedNonce {
+    owner: String,
+    expires_at: Instant,
+    used: bool,
+}
+
+impl NonceStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            nonces: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Issue a fresh nonce for `owner` (e.g. "tenant:user_id")
+    pub fn issue(&self, owner: &str) -> String {
+        let nonce = SaltString::generate(&mut OsRng).to_string();
+        let now = Instant::now();
+
+        let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        // Expired entries are no longer needed to detect replays
+        nonces.retain(|_, issued| issued.expires_at > now);
+        nonces.insert(
+            nonce.clone(),
+            IssuedNonce {
+                owner: owner.to_string(),
+                expires_at: now + self.ttl,
+                used: false,
+            },
+        );
+        nonce
+    }
+
+    /// Drop expired nonces, returning how many were removed
+    ///
+    /// `issue` already does this on every call; the scheduled prune job
+    /// covers quiet periods when nothing is being issued.
+    pub fn prune(&self) -> usize {
+        let now = Instant::now();
+        let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        let before = nonces.len();
+        nonces.retain(|_, issued| issued.expires_at > now);
+        before - nonces.len()
+    }
+
+    /// Number of nonces currently held, expired or not
+    pub fn len(&self) -> usize {
+        self.nonces.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Validate and consume a nonce issued to `owner`
+    ///
+    /// Unknown, expired or foreign nonces are a bad request; a nonce that was
+    /// already consumed is a conflict.
+    pub fn consume(&self, nonce: &str, owner: &str) -> Result<(), AppError> {
+        let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        match nonces.get_mut(nonce) {
+            Some(issued) if issued.owner == owner && issued.expires_at > Instant::now() => {
+                if issued.used {
+                    return Err(AppError::Conflict("Nonce has already been used".to_string()));
+                }
+                issued.used = true;
+                Ok(())
+            }
+            _ => Err(AppError::BadRequest("Invalid or expired nonce".to_string())),
+        }
+    }
+}
+
+impl Default for NonceStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_NONCE_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonce_is_single_use() {
+        let store = NonceStore::default();
+        let nonce = store.issue("default:alice");
+
+        assert!(store.consume(&nonce, "default:alice").is_ok());
+        assert!(matches!(
+            store.consume(&nonce, "default:alice"),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_nonce_is_bound_to_owner() {
+        let store = NonceStore::default();
+        let nonce = store.issue("default:alice");
+
+        assert!(matches!(
+            store.consume(&nonce, "default:bob"),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(store.consume(&nonce, "default:alice").is_ok());
+    }
+
+    #[test]
+    fn test_prune_removes_only_expired_nonces() {
+        let expired = NonceStore::new(Duration::ZERO);
+        expired.issue("default:alice");
+        assert_eq!(expired.prune(), 1);
+        assert!(expired.is_empty());
+
+        let live = NonceStore::default();
+        let nonce = live.issue("default:alice");
+        assert_eq!(live.prune(), 0);
+        assert!(live.consume(&nonce, "default:alice").is_ok());
+    }
+
+    #[test]
+    fn test_expired_nonce_is_rejected() {
+        let store = NonceStore::new(Duration::ZERO);
+        let nonce = store.issue("default:alice");
+
+        assert!(matches!(
+            store.consume(&nonce, "default:alice"),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
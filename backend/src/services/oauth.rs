@@ -0,0 +1,189 @@
+//! OAuth2 / OpenID Connect sign-in (authorization-code flow)
+//!
+//! Enabled with the `oauth` feature. The provider only proves who the user is;
+//! the account and the JWT returned to the client are this API's own (see
+//! `AuthService::login_external`).
+//!
+//! ```no_run
+//! # use backend::{config::Config, error::AppError, services::oauth::OAuthProvider, AppState};
+//! # async fn example(config: Config, app: AppState, state: String, code: String) -> Result<(), AppError> {
+//! let provider = OAuthProvider::google(config.oauth.google.clone().unwrap(), app.http().clone());
+//! let url = provider.authorize_url(&state)?;          // redirect the browser here
+//! let identity = provider.exchange_code(&code).await?; // in the callback
+//! # Ok(())
+//! # }
+//! ```
+
+use password_hash::{rand_core::OsRng, SaltString};
+use serde::Deserialize;
+
+use crate::{
+    config::OAuthProviderConfig, error::AppError, models::user::ExternalIdentity,
+    services::http::HttpClient,
+};
+
+/// Scopes needed for a stable subject id and a verified email
+const SCOPES: &str = "openid email profile";
+
+/// One configured OAuth2 provider
+#[derive(Debug, Clone)]
+pub struct OAuthProvider {
+    name: &'static str,
+    config: OAuthProviderConfig,
+    http: HttpClient,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Standard OIDC userinfo claims
+#[derive(Deserialize)]
+struct UserInfo {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+}
+
+impl OAuthProvider {
+    /// Google sign-in
+    pub fn google(config: OAuthProviderConfig, http: HttpClient) -> Self {
+        Self {
+            name: "google",
+            config,
+            http,
+        }
+    }
+
+    /// Provider name, as stored in `users.provider`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// URL to send the browser to, carrying the anti-CSRF `state`
+    pub fn authorize_url(&self, state: &str) -> Result<String, AppError> {
+        reqwest::Url::parse_with_params(
+            &self.config.auth_url,
+            &[
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("response_type", "code"),
+                ("scope", SCOPES),
+                ("state", state),
+            ],
+        )
+        .map(String::from)
+        .map_err(|e| AppError::ConfigError(format!("Invalid {} auth URL: {}", self.name, e)))
+    }
+
+    /// Exchange an authorization code for the user's verified identity
+    pub async fn exchange_code(&self, code: &str) -> Result<ExternalIdentity, AppError> {
+        // Codes are single-use, so the POST is never retried
+        let token: TokenResponse = self
+            .http
+            .send(self.http.post(&self.config.token_url).form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+            ]))
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AppError::external_service(self.name, e))?
+            .json()
+            .await
+            .map_err(|e| AppError::external_service(self.name, e))?;
+
+        let info: UserInfo = self
+            .http
+            .send(
+                self.http
+                    .get(&self.config.userinfo_url)
+                    .bearer_auth(&token.access_token),
+            )
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AppError::external_service(self.name, e))?
+            .json()
+            .await
+            .map_err(|e| AppError::external_service(self.name, e))?;
+
+        // An unverified email could claim someone else's account
+        let email = info
+            .email
+            .filter(|_| info.email_verified)
+            .ok_or_else(|| {
+                AppError::Unauthorized(format!("{} account has no verified email", self.name))
+            })?;
+
+        Ok(ExternalIdentity {
+            provider: self.name.to_string(),
+            username: username_for(&email, &info.sub),
+            provider_id: info.sub,
+            email,
+        })
+    }
+}
+
+/// Random value tying a callback to the browser that started the flow
+pub fn generate_state() -> String {
+    SaltString::generate(&mut OsRng).to_string()
+}
+
+/// Username for a new account: the email's local part plus a slice of the
+/// provider id, so two `jane@...` addresses don't collide
+fn username_for(email: &str, provider_id: &str) -> String {
+    let local: String = email
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(64)
+        .collect();
+    let suffix = provider_id
+        .char_indices()
+        .rev()
+        .nth(7)
+        .map_or(provider_id, |(i, _)| &provider_id[i..]);
+
+    format!("{}_{}", if local.is_empty() { "user" } else { &local }, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> OAuthProvider {
+        OAuthProvider::google(
+            OAuthProviderConfig::google(
+                "client-id".to_string(),
+                "client-secret".to_string(),
+                "https://api.example.com/api/v1/auth/oauth/google/callback".to_string(),
+            ),
+            HttpClient::default(),
+        )
+    }
+
+    #[test]
+    fn test_authorize_url_carries_client_and_state() {
+        let url = provider().authorize_url("abc123").unwrap();
+
+        assert!(url.starts_with("https://accounts.google.com/o/oauth2/v2/auth?"));
+        assert!(url.contains("client_id=client-id"));
+        assert!(url.contains("state=abc123"));
+        assert!(url.contains("response_type=code"));
+        assert!(url.contains(
+            "redirect_uri=https%3A%2F%2Fapi.example.com%2Fapi%2Fv1%2Fauth%2Foauth%2Fgoogle%2Fcallback"
+        ));
+    }
+
+    #[test]
+    fn test_username_from_email_and_provider_id() {
+        assert_eq!(username_for("jane.doe+x@example.com", "1234567890123"), "jane.doex_67890123");
+        assert_eq!(username_for("@example.com", "42"), "user_42");
+    }
+}
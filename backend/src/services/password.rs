@@ -0,0 +1,371 @@
+//! Password hashing algorithms
+//!
+//! [`PasswordPolicy`] hashes new passwords with the preferred algorithm
+//! (PASSWORD_HASH_ALGORITHM) and verifies a stored hash with whichever
+//! compiled-in [`PasswordHasher`] recognizes it. Hashes imported from a
+//! legacy bcrypt store therefore keep working, and `AuthService` rehashes
+//! them to the preferred algorithm on the next successful login.
+//!
+//! Everything here is CPU-bound and synchronous; `AuthService` runs it on the
+//! blocking thread pool.
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use password_hash::rand_core::OsRng;
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    config::{PasswordAlgorithm, PasswordConfig},
+    error::AppError,
+};
+
+/// One password hashing algorithm
+pub trait PasswordHasher: Send + Sync {
+    fn algorithm(&self) -> PasswordAlgorithm;
+
+    /// Whether `hash` was produced by this algorithm
+    fn recognizes(&self, hash: &str) -> bool;
+
+    fn hash(&self, password: &str) -> Result<String, AppError>;
+
+    /// `Unauthorized` when the password doesn't match
+    fn verify(&self, password: &str, hash: &str) -> Result<(), AppError>;
+
+    /// Whether `hash` was created with weaker settings than this hasher's
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
+
+fn invalid_hash(e: impl std::fmt::Display) -> AppError {
+    AppError::InternalServerError {
+        message: "Invalid password hash".to_string(),
+        source: Some(Box::new(std::io::Error::other(e.to_string()))),
+    }
+}
+
+fn mismatch() -> AppError {
+    AppError::Unauthorized("Invalid email or password".to_string())
+}
+
+/// Argon2id, keyed with the pepper when one is configured
+///
+/// The pepper is argon2's built-in secret input, so it never appears in the
+/// stored PHC string; verification must use the same pepper.
+pub struct Argon2Hasher {
+    params: Params,
+    pepper: Option<Arc<[u8]>>,
+}
+
+impl Argon2Hasher {
+    pub fn new(params: Params, pepper: Option<&[u8]>) -> Self {
+        Self {
+            params,
+            pepper: pepper.map(Arc::from),
+        }
+    }
+
+    fn argon2(&self, params: Params) -> Result<Argon2<'_>, AppError> {
+        match self.pepper.as_deref() {
+            Some(pepper) => {
+                Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params)
+                    .map_err(|e| AppError::ConfigError(format!("Invalid ARGON2_PEPPER: {}", e)))
+            }
+            None => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        }
+    }
+}
+
+impl PasswordHasher for Argon2Hasher {
+    fn algorithm(&self) -> PasswordAlgorithm {
+        PasswordAlgorithm::Argon2
+    }
+
+    fn recognizes(&self, hash: &str) -> bool {
+        hash.starts_with("$argon2")
+    }
+
+    fn hash(&self, password: &str) -> Result<String, AppError> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        self.argon2(self.params.clone())?
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AppError::InternalServerError {
+                message: "Failed to hash password".to_string(),
+                source: Some(Box::new(std::io::Error::other(e.to_string()))),
+            })
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<(), AppError> {
+        let parsed_hash = PasswordHash::new(hash).map_err(invalid_hash)?;
+
+        // Cost parameters come from the stored hash, not the current policy
+        self.argon2(Params::default())?
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .map_err(|_| mismatch())
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(hash) else {
+            return false;
+        };
+
+        if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+
+        match Params::try_from(&parsed_hash) {
+            Ok(params) => {
+                params.m_cost() < self.params.m_cost()
+                    || params.t_cost() < self.params.t_cost()
+                    || params.p_cost() < self.params.p_cost()
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// bcrypt, for hashes imported from legacy systems
+///
+/// The pepper is not applied: imported hashes were made without it. bcrypt
+/// only looks at the first 72 bytes of a password.
+#[cfg(feature = "bcrypt")]
+pub struct BcryptHasher {
+    cost: u32,
+}
+
+#[cfg(feature = "bcrypt")]
+impl BcryptHasher {
+    pub fn new(cost: u32) -> Self {
+        Self { cost }
+    }
+}
+
+#[cfg(feature = "bcrypt")]
+impl Default for BcryptHasher {
+    fn default() -> Self {
+        Self::new(bcrypt::DEFAULT_COST)
+    }
+}
+
+#[cfg(feature = "bcrypt")]
+impl PasswordHasher for BcryptHasher {
+    fn algorithm(&self) -> PasswordAlgorithm {
+        PasswordAlgorithm::Bcrypt
+    }
+
+    fn recognizes(&self, hash: &str) -> bool {
+        ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+    }
+
+    fn hash(&self, password: &str) -> Result<String, AppError> {
+        bcrypt::hash(password, self.cost)
+            .map_err(|e| AppError::internal("Failed to hash password", e))
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<(), AppError> {
+        match bcrypt::verify(password, hash) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(mismatch()),
+            Err(e) => Err(invalid_hash(e)),
+        }
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        hash.parse::<bcrypt::HashParts>()
+            .map(|parts| parts.get_cost() < self.cost)
+            .unwrap_or(false)
+    }
+}
+
+/// Hash with the preferred algorithm, verify with any known one
+pub struct PasswordPolicy {
+    preferred: Box<dyn PasswordHasher>,
+    /// Other algorithms accepted when verifying
+    legacy: Vec<Box<dyn PasswordHasher>>,
+    /// Hash of a throwaway password, see [`PasswordPolicy::verify_dummy`]
+    dummy_hash: OnceLock<String>,
+}
+
+impl PasswordPolicy {
+    pub fn new(preferred: Box<dyn PasswordHasher>, legacy: Vec<Box<dyn PasswordHasher>>) -> Self {
+        Self {
+            preferred,
+            legacy,
+            dummy_hash: OnceLock::new(),
+        }
+    }
+
+    /// Every compiled-in algorithm, preferring the configured one
+    ///
+    /// Invalid settings are rejected when the config is loaded, so the
+    /// fallbacks here only apply to hand-built configs.
+    pub fn from_config(config: &PasswordConfig) -> Self {
+        let params = config.argon2_params().unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Invalid argon2 parameters, using defaults");
+            Params::default()
+        });
+        let pepper = config.pepper.as_deref().map(str::as_bytes);
+
+        let mut hashers: Vec<Box<dyn PasswordHasher>> =
+            vec![Box::new(Argon2Hasher::new(params, pepper))];
+        #[cfg(feature = "bcrypt")]
+        hashers.push(Box::new(BcryptHasher::default()));
+
+        let preferred = match hashers.iter().position(|h| h.algorithm() == config.algorithm) {
+            Some(index) => hashers.remove(index),
+            None => {
+                tracing::warn!(
+                    algorithm = config.algorithm.as_str(),
+                    "Password hash algorithm not compiled in, using argon2"
+                );
+                hashers.remove(0)
+            }
+        };
+        Self::new(preferred, hashers)
+    }
+
+    pub fn preferred(&self) -> PasswordAlgorithm {
+        self.preferred.algorithm()
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String, AppError> {
+        self.preferred.hash(password)
+    }
+
+    pub fn verify(&self, password: &str, hash: &str) -> Result<(), AppError> {
+        self.hasher_for(hash)
+            .ok_or_else(|| invalid_hash("unrecognized password hash format"))?
+            .verify(password, hash)
+    }
+
+    /// Verify `password` against a hash no password matches, for logins
+    /// naming an unknown user
+    ///
+    /// Costs as much as checking a real password under the current policy,
+    /// so an attacker can't tell missing accounts from wrong passwords by
+    /// response time. The dummy hash is made on first use, which makes that
+    /// one call about twice as slow.
+    pub fn verify_dummy(&self, password: &str) {
+        let hash = self.dummy_hash.get_or_init(|| {
+            let placeholder = SaltString::generate(&mut OsRng);
+            self.preferred.hash(placeholder.as_str()).unwrap_or_default()
+        });
+        let _ = self.verify(password, hash);
+    }
+
+    /// Whether a verified hash should be replaced with a fresh preferred one
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        if self.preferred.recognizes(hash) {
+            self.preferred.needs_rehash(hash)
+        } else {
+            self.hasher_for(hash).is_some()
+        }
+    }
+
+    fn hasher_for(&self, hash: &str) -> Option<&dyn PasswordHasher> {
+        std::iter::once(&self.preferred)
+            .chain(&self.legacy)
+            .find(|hasher| hasher.recognizes(hash))
+            .map(|hasher| hasher.as_ref())
+    }
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::from_config(&PasswordConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_params() -> Params {
+        Params::new(Params::MIN_M_COST, Params::MIN_T_COST, 1, None).unwrap()
+    }
+
+    fn argon2(pepper: Option<&[u8]>) -> Argon2Hasher {
+        Argon2Hasher::new(fast_params(), pepper)
+    }
+
+    #[test]
+    fn test_peppered_hash_round_trip() {
+        let hasher = argon2(Some(b"server-side-pepper"));
+        let hash = hasher.hash("SecurePass123!").unwrap();
+
+        assert!(hasher.verify("SecurePass123!", &hash).is_ok());
+        assert!(hasher.verify("WrongPass123!", &hash).is_err());
+        // The pepper never ends up in the stored hash
+        assert!(!hash.contains("server-side-pepper"));
+    }
+
+    #[test]
+    fn test_peppered_hash_requires_same_pepper() {
+        let hash = argon2(Some(b"old-pepper")).hash("SecurePass123!").unwrap();
+
+        assert!(argon2(Some(b"new-pepper")).verify("SecurePass123!", &hash).is_err());
+        assert!(argon2(None).verify("SecurePass123!", &hash).is_err());
+    }
+
+    #[test]
+    fn test_unpeppered_hash_unchanged_without_pepper() {
+        let hash = argon2(None).hash("SecurePass123!").unwrap();
+
+        assert!(argon2(None).verify("SecurePass123!", &hash).is_ok());
+        // Hashes from before a pepper was configured match argon2's defaults
+        assert!(Argon2::default()
+            .verify_password(b"SecurePass123!", &PasswordHash::new(&hash).unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_policy_rejects_unknown_hash_formats() {
+        let policy = PasswordPolicy::new(Box::new(argon2(None)), Vec::new());
+
+        assert!(matches!(
+            policy.verify("SecurePass123!", "md5$abcdef"),
+            Err(AppError::InternalServerError { .. })
+        ));
+        assert!(!policy.needs_rehash("md5$abcdef"));
+    }
+
+    #[cfg(feature = "bcrypt")]
+    #[test]
+    fn test_bcrypt_hash_verifies_and_needs_upgrade() {
+        let legacy = BcryptHasher::new(4).hash("SecurePass123!").unwrap();
+        let policy = PasswordPolicy::new(
+            Box::new(argon2(None)),
+            vec![Box::new(BcryptHasher::new(4))],
+        );
+
+        assert!(policy.verify("SecurePass123!", &legacy).is_ok());
+        assert!(matches!(
+            policy.verify("WrongPass123!", &legacy),
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(policy.needs_rehash(&legacy));
+
+        let upgraded = policy.hash("SecurePass123!").unwrap();
+        assert!(upgraded.starts_with("$argon2id$"));
+        assert!(!policy.needs_rehash(&upgraded));
+    }
+
+    #[test]
+    fn test_dummy_verify_uses_one_preferred_hash() {
+        let policy = PasswordPolicy::new(Box::new(argon2(None)), vec![]);
+        assert!(policy.dummy_hash.get().is_none());
+
+        policy.verify_dummy("SecurePass123!");
+        let hash = policy.dummy_hash.get().cloned().unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(policy.verify("SecurePass123!", &hash).is_err());
+
+        policy.verify_dummy("OtherPass123!");
+        assert_eq!(policy.dummy_hash.get(), Some(&hash));
+    }
+}
@@ -0,0 +1,61 @@
+//! Live secret rotation
+//!
+//! [`reload_secrets`] drops the secret manager's cache and re-reads the
+//! secrets a running server can swap without a restart: the JWT signing
+//! secret and its verification-only predecessors, and the rate-limit bypass
+//! keys. The database URL and the password pepper are read at startup only:
+//! the pool is already connected, and a new pepper would fail every existing
+//! password hash.
+use crate::{config::Config, error::AppError, Services};
+
+/// What a reload applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadedSecrets {
+    /// Whether the JWT signing secret changed
+    pub jwt_secret_rotated: bool,
+    /// Retired JWT secrets still accepted for verification
+    pub jwt_previous_secrets: usize,
+    pub rate_limit_bypass_keys: usize,
+}
+
+/// Re-fetch rotated secrets and apply them to the running services
+///
+/// A new signing secret is checked like at startup (production only) before
+/// anything is applied, so a bad rotation leaves the old keys in place.
+pub async fn reload_secrets(services: &Services, config: &Config) -> Result<ReloadedSecrets, AppError> {
+    let secrets = &services.secrets;
+    secrets.invalidate();
+
+    let jwt_secret = secrets.get_secret_or_env("JWT_SECRET", None).await?;
+    if config.is_production() {
+        config.jwt.validate_secret(&jwt_secret)?;
+    }
+    // Optional: only present while a rotation is in progress
+    let previous_secrets = Config::split_list(
+        &secrets
+            .get_secret_or_env("JWT_SECRET_PREVIOUS", None)
+            .await
+            .unwrap_or_default(),
+    );
+    let bypass_keys = Config::split_list(
+        &secrets
+            .get_secret_or_env("RATE_LIMIT_BYPASS_KEYS", None)
+            .await
+            .unwrap_or_default(),
+    );
+
+    let reloaded = ReloadedSecrets {
+        jwt_previous_secrets: previous_secrets.len(),
+        rate_limit_bypass_keys: bypass_keys.len(),
+        jwt_secret_rotated: services.jwt.rotate(jwt_secret, previous_secrets),
+    };
+    services.auth_rate_limiter.set_bypass_keys(bypass_keys);
+
+    tracing::info!(
+        jwt_secret_rotated = reloaded.jwt_secret_rotated,
+        jwt_previous_secrets = reloaded.jwt_previous_secrets,
+        rate_limit_bypass_keys = reloaded.rate_limit_bypass_keys,
+        "Secrets reloaded"
+    );
+    Ok(reloaded)
+}
@@ -0,0 +1,79 @@
+//! Global token-bucket throttle
+//!
+//! Unlike the per-key [`RateLimiter`](crate::middleware::rate_limit::RateLimiter),
+//! a [`TokenBucket`] is shared by every client: it caps the total rate of an
+//! operation across all IPs, e.g. registrations per minute
+//! (REGISTRATION_RATE_PER_MINUTE). The bucket holds up to `capacity` tokens
+//! and refills continuously, so a burst of `capacity` is allowed after a
+//! quiet period.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket shared across clones
+#[derive(Clone)]
+pub struct TokenBucket {
+    state: Arc<Mutex<BucketState>>,
+    capacity: f64,
+    refill_per_sec: f64,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Bucket allowing `per_minute` operations a minute, starting full
+    pub fn per_minute(per_minute: u32) -> Self {
+        let capacity = f64::from(per_minute.max(1));
+        Self {
+            state: Arc::new(Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            })),
+            capacity,
+            refill_per_sec: capacity / 60.0,
+        }
+    }
+
+    /// Take one token, or return how long until one is available
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - state.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_capacity_then_throttles() {
+        let bucket = TokenBucket::per_minute(3);
+        for _ in 0..3 {
+            assert!(bucket.try_acquire().is_ok());
+        }
+
+        let wait = bucket.try_acquire().unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_clones_share_tokens() {
+        let bucket = TokenBucket::per_minute(1);
+        let clone = bucket.clone();
+        assert!(bucket.try_acquire().is_ok());
+        assert!(clone.try_acquire().is_err());
+    }
+}
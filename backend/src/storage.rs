@@ -0,0 +1,301 @@
+//! File storage backends for uploads
+//!
+//! Handlers use [`StorageBackend`] through `AppState::storage()` and never
+//! touch the filesystem or S3 directly. `STORAGE_BACKEND` picks the
+//! implementation at startup:
+//! - "local" (default): files under `STORAGE_LOCAL_ROOT` (default: `uploads`)
+//! - "s3": an S3 bucket (requires the `s3` feature)
+
+use crate::error::AppError;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Storage backend trait for abstracting where uploaded files live
+#[async_trait::async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Short backend name for logs ("local", "s3")
+    fn name(&self) -> &'static str;
+
+    /// Store `data` under `key`, replacing any existing object
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: Option<&str>) -> Result<(), AppError>;
+
+    /// Read the object stored under `key`
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError>;
+
+    /// Remove the object under `key`; deleting a missing object succeeds
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+
+    /// URL a client can fetch the object from without credentials
+    async fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, AppError>;
+}
+
+/// Reject keys that could escape the storage root or are ambiguous
+fn validate_key(key: &str) -> Result<&Path, AppError> {
+    let path = Path::new(key);
+    let is_safe = !key.is_empty()
+        && !key.contains('\\')
+        && path.components().all(|c| matches!(c, Component::Normal(_)));
+
+    if is_safe {
+        Ok(path)
+    } else {
+        Err(AppError::BadRequest(format!("Invalid storage key: '{}'", key)))
+    }
+}
+
+/// Local filesystem backend (default)
+///
+/// Meant for development and single-instance deployments. Files are not
+/// signed: `signed_url` links to `STORAGE_LOCAL_BASE_URL`, which something
+/// else (a reverse proxy, a static file route) must serve.
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: Option<String>,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: None,
+        }
+    }
+
+    /// Public URL prefix the root directory is served from
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, AppError> {
+        Ok(self.root.join(validate_key(key)?))
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: Option<&str>) -> Result<(), AppError> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::internal("Failed to create storage directory", e))?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|e| AppError::internal("Failed to write stored file", e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.path_for(key)?;
+        tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(format!("File '{}' not found", key)),
+            _ => AppError::internal("Failed to read stored file", e),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let path = self.path_for(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::internal("Failed to delete stored file", e)),
+        }
+    }
+
+    async fn signed_url(&self, key: &str, _expires_in: Duration) -> Result<String, AppError> {
+        validate_key(key)?;
+        let base_url = self.base_url.as_ref().ok_or_else(|| {
+            AppError::ConfigError("STORAGE_LOCAL_BASE_URL must be set to build file URLs".to_string())
+        })?;
+        Ok(format!("{}/{}", base_url, key))
+    }
+}
+
+/// Amazon S3 backend
+#[cfg(feature = "s3")]
+pub struct S3Storage {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: Option<String>,
+}
+
+#[cfg(feature = "s3")]
+impl S3Storage {
+    pub async fn new(bucket: String, prefix: Option<String>) -> Result<Self, AppError> {
+        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let client = aws_sdk_s3::Client::new(&config);
+        Ok(Self { client, bucket, prefix })
+    }
+
+    fn object_key(&self, key: &str) -> Result<String, AppError> {
+        validate_key(key)?;
+        Ok(match &self.prefix {
+            Some(prefix) => format!("{}/{}", prefix, key),
+            None => key.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait::async_trait]
+impl StorageBackend for S3Storage {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: Option<&str>) -> Result<(), AppError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key)?)
+            .body(aws_sdk_s3::primitives::ByteStream::from(data))
+            .set_content_type(content_type.map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| AppError::external_service("S3", e))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key)?)
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(err) if err.is_no_such_key() => {
+                    AppError::NotFound(format!("File '{}' not found", key))
+                }
+                _ => AppError::external_service("S3", e),
+            })?;
+
+        let data = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| AppError::external_service("S3", e))?;
+        Ok(data.into_bytes().to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key)?)
+            .send()
+            .await
+            .map_err(|e| AppError::external_service("S3", e))?;
+        Ok(())
+    }
+
+    async fn signed_url(&self, key: &str, expires_in: Duration) -> Result<String, AppError> {
+        let presigning = aws_sdk_s3::presigning::PresigningConfig::expires_in(expires_in)
+            .map_err(|e| AppError::internal("Invalid presigned URL expiry", e))?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(key)?)
+            .presigned(presigning)
+            .await
+            .map_err(|e| AppError::external_service("S3", e))?;
+        Ok(request.uri().to_string())
+    }
+}
+
+/// Build the backend named by STORAGE_BACKEND (default: "local")
+pub async fn from_env() -> Result<Box<dyn StorageBackend>, AppError> {
+    let backend = env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+    from_name(&backend).await
+}
+
+/// Build a backend by name, reading its settings from the environment
+pub async fn from_name(backend: &str) -> Result<Box<dyn StorageBackend>, AppError> {
+    match backend {
+        "local" => {
+            let root = env::var("STORAGE_LOCAL_ROOT").unwrap_or_else(|_| "uploads".to_string());
+            let storage = LocalStorage::new(root);
+            Ok(Box::new(match env::var("STORAGE_LOCAL_BASE_URL") {
+                Ok(base_url) => storage.with_base_url(base_url),
+                Err(_) => storage,
+            }))
+        }
+
+        #[cfg(feature = "s3")]
+        "s3" => {
+            let bucket = env::var("S3_BUCKET")
+                .map_err(|_| AppError::ConfigError("S3_BUCKET must be set".to_string()))?;
+            let prefix = env::var("S3_PREFIX").ok().filter(|p| !p.is_empty());
+            Ok(Box::new(S3Storage::new(bucket, prefix).await?))
+        }
+
+        _ => Err(AppError::ConfigError(format!(
+            "Unknown storage backend: {}. Available: local{}",
+            backend,
+            if cfg!(feature = "s3") { ", s3" } else { "" }
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_storage() -> (LocalStorage, PathBuf) {
+        let root = env::temp_dir().join(format!("storage-test-{}", uuid::Uuid::new_v4()));
+        (LocalStorage::new(&root), root)
+    }
+
+    #[tokio::test]
+    async fn test_local_put_get_delete() {
+        let (storage, root) = temp_storage();
+
+        storage.put("avatars/1.png", b"png".to_vec(), Some("image/png")).await.unwrap();
+        assert_eq!(storage.get("avatars/1.png").await.unwrap(), b"png");
+
+        storage.delete("avatars/1.png").await.unwrap();
+        assert!(matches!(storage.get("avatars/1.png").await, Err(AppError::NotFound(_))));
+        // Deleting again is not an error
+        storage.delete("avatars/1.png").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_local_rejects_keys_outside_root() {
+        let (storage, _root) = temp_storage();
+
+        for key in ["../escape", "/etc/passwd", "a/../../b", ""] {
+            assert!(
+                matches!(storage.put(key, Vec::new(), None).await, Err(AppError::BadRequest(_))),
+                "{}",
+                key
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_signed_url_uses_base_url() {
+        let storage = LocalStorage::new("uploads").with_base_url("https://cdn.example.com/files/");
+
+        let url = storage.signed_url("a/b.txt", Duration::from_secs(60)).await.unwrap();
+
+        assert_eq!(url, "https://cdn.example.com/files/a/b.txt");
+    }
+
+    #[tokio::test]
+    async fn test_backend_selection_by_name() {
+        assert_eq!(from_name("local").await.unwrap().name(), "local");
+
+        let err = from_name("ftp").await.err().unwrap();
+        assert!(err.to_string().contains("Unknown storage backend: ftp"));
+    }
+}
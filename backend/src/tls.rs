@@ -0,0 +1,78 @@
+//! In-process TLS termination (`tls` feature)
+//!
+//! When TLS_CERT_PATH and TLS_KEY_PATH are set the server speaks HTTPS
+//! itself instead of relying on a reverse proxy. Both files are PEM; the
+//! certificate file may hold the full chain. On unix, SIGHUP reloads them
+//! from disk so renewed certificates are picked up without a restart.
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use std::net::SocketAddr;
+
+use crate::{config::TlsConfig, error::AppError};
+
+/// Load the certificate and key named by `config`
+pub async fn load(config: &TlsConfig) -> Result<RustlsConfig, AppError> {
+    // Several rustls providers may be compiled in; pick ring explicitly.
+    // Fails only when a provider is already installed, which is fine.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    RustlsConfig::from_pem_file(&config.cert_path, &config.key_path)
+        .await
+        .map_err(|e| {
+            AppError::ConfigError(format!(
+                "Failed to load TLS certificate {} / key {}: {}",
+                config.cert_path.display(),
+                config.key_path.display(),
+                e
+            ))
+        })
+}
+
+/// Serve `app` over HTTPS on an already-bound listener
+///
+/// Runs until the server fails; drop the future to stop it.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    config: &TlsConfig,
+) -> Result<(), AppError> {
+    let rustls = load(config).await?;
+    let listener = listener
+        .into_std()
+        .map_err(|e| AppError::internal("Failed to prepare TLS listener", e))?;
+
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(rustls.clone(), config.clone()));
+
+    axum_server::from_tcp_rustls(listener, rustls)
+        // Client addresses feed IP-based rate limiting
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .map_err(|e| AppError::internal("TLS server error", e))
+}
+
+/// Re-read the certificate and key whenever the process receives SIGHUP
+///
+/// A failed reload keeps serving the previous certificate.
+#[cfg(unix)]
+async fn reload_on_sighup(rustls: RustlsConfig, config: TlsConfig) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to install SIGHUP handler; TLS reload disabled");
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        match rustls
+            .reload_from_pem_file(&config.cert_path, &config.key_path)
+            .await
+        {
+            Ok(()) => tracing::info!("Reloaded TLS certificate"),
+            Err(e) => tracing::error!(error = %e, "Failed to reload TLS certificate, keeping the old one"),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::Environment;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::{global, KeyValue};
 use opentelemetry_sdk::trace::{Config, TracerProvider};
@@ -5,12 +6,52 @@ use opentelemetry_sdk::Resource;
 use opentelemetry_stdout::SpanExporter;
 use tracing_subscriber::{fmt::writer::MakeWriterExt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// How long shutdown waits for pending spans unless OTEL_SHUTDOWN_TIMEOUT_MS says otherwise
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The installed provider, kept so shutdown can flush it
+static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
+/// Log filter used unless RUST_LOG says otherwise
+const DEFAULT_FILTER: &str = "info,backend=debug,tower_http=debug";
+
+/// Target of the per-step boot logs, e.g. "Database pool created"
+///
+/// QUIET_STARTUP=1 raises it to warn, leaving the single `startup` summary
+/// event and any warnings or errors.
+pub const STARTUP_STEP_TARGET: &str = "startup::step";
+
+/// Whether QUIET_STARTUP is set, parsed like every other config flag
+///
+/// Read before the config loads, since the subscriber is installed first.
+pub fn quiet_startup() -> bool {
+    crate::config::Config::env_flag("QUIET_STARTUP")
+}
+
+/// `filter` with the boot steps silenced when `quiet`
+pub fn quiet_startup_filter(filter: EnvFilter, quiet: bool) -> EnvFilter {
+    if !quiet {
+        return filter;
+    }
+    match format!("{}=warn", STARTUP_STEP_TARGET).parse() {
+        Ok(directive) => filter.add_directive(directive),
+        Err(_) => filter,
+    }
+}
+
+/// RUST_LOG or the default filter, honouring QUIET_STARTUP
+fn env_filter() -> EnvFilter {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into());
+    quiet_startup_filter(filter, quiet_startup())
+}
 
 /// Initialize simple tracing without OpenTelemetry
 /// Perfect for development when you want cleaner, less verbose output
 pub fn init_simple_tracing() -> Result<(), Box<dyn std::error::Error>> {
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "info,backend=debug,tower_http=debug".into());
+    let env_filter = env_filter();
 
     tracing_subscriber::fmt()
         .with_env_filter(env_filter)
@@ -18,7 +59,7 @@ pub fn init_simple_tracing() -> Result<(), Box<dyn std::error::Error>> {
         .compact()
         .init();
 
-    tracing::info!("Simple tracing initialized (no OpenTelemetry)");
+    tracing::info!(target: STARTUP_STEP_TARGET, "Simple tracing initialized (no OpenTelemetry)");
     Ok(())
 }
 
@@ -36,8 +77,7 @@ pub fn init_dev_with_file_logging(log_file_path: &str) -> Result<(), Box<dyn std
         .truncate(true)
         .open(log_file_path)?;
 
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "info,backend=debug,tower_http=debug".into());
+    let env_filter = env_filter();
 
     // Write to both stdout and file
     let writer = io::stdout.and(file);
@@ -49,20 +89,30 @@ pub fn init_dev_with_file_logging(log_file_path: &str) -> Result<(), Box<dyn std
         .compact()
         .init();
 
-    tracing::info!("Development tracing initialized with file logging: {}", log_file_path);
+    tracing::info!(
+        target: STARTUP_STEP_TARGET,
+        "Development tracing initialized with file logging: {}",
+        log_file_path
+    );
     Ok(())
 }
 
+/// Whether full OpenTelemetry tracing is used for this environment
+///
+/// In development, use simple logs by default for better DX.
+/// Set OTEL=1 to enable full OpenTelemetry in development.
+/// In production, always use full OpenTelemetry tracing.
+pub fn otel_enabled(environment: Environment) -> bool {
+    environment.is_production() || std::env::var("OTEL").is_ok()
+}
+
 /// Initialize tracing with OpenTelemetry support
 /// Returns a guard that must be kept alive for the duration of the program
 pub fn init_tracing(
     service_name: &str,
-    environment: &str,
+    environment: Environment,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // In development, use simple logs by default for better DX
-    // Set OTEL=1 to enable full OpenTelemetry in development
-    // In production, always use full OpenTelemetry tracing
-    let use_simple = environment != "production" && std::env::var("OTEL").is_err();
+    let use_simple = !otel_enabled(environment);
 
     if use_simple {
         tracing::info!("💡 Using simple tracing (set OTEL=1 for full OpenTelemetry, or ENVIRONMENT=production)");
@@ -89,17 +139,17 @@ pub fn init_tracing(
     let tracer = provider.tracer("backend");
 
     // Set the global tracer provider
+    let _ = PROVIDER.set(provider.clone());
     global::set_tracer_provider(provider);
 
     // Set up environment filter for logs
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "info,backend=debug,tower_http=debug".into());
+    let env_filter = env_filter();
 
     // Create OpenTelemetry layer
     let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer);
 
     // Determine log format based on environment
-    if environment == "production" {
+    if environment.is_production() {
         // JSON formatting for production (structured logging)
         tracing_subscriber::registry()
             .with(env_filter)
@@ -133,9 +183,67 @@ pub fn init_tracing(
 }
 
 /// Shutdown tracing and flush any pending spans
+///
+/// Flushing and shutting down the provider block on the exporter, so they
+/// run on their own thread and are given up on after OTEL_SHUTDOWN_TIMEOUT_MS
+/// (default 5s): a stuck collector delays exit but can't hang it.
 pub async fn shutdown_tracing() {
     tracing::info!("Shutting down tracing");
-    global::shutdown_tracer_provider();
+    let timeout = std::env::var("OTEL_SHUTDOWN_TIMEOUT_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+
+    run_bounded(timeout, || {
+        if let Some(provider) = PROVIDER.get() {
+            for result in provider.force_flush() {
+                if let Err(e) = result {
+                    eprintln!("Failed to flush spans: {}", e);
+                }
+            }
+        }
+        global::shutdown_tracer_provider();
+    })
+    .await;
+}
+
+/// Run a blocking shutdown step, waiting at most `timeout` for it
+///
+/// The step gets a plain thread rather than the blocking pool, since the
+/// runtime waits for pool tasks when it drops and a hung exporter would hang
+/// exit with it. Returns whether the step finished in time. Reports go to
+/// stderr because the subscriber may already be gone.
+pub async fn run_bounded<F>(timeout: Duration, step: F) -> bool
+where
+    F: FnOnce() + Send + 'static,
+{
+    let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+    let spawned = std::thread::Builder::new()
+        .name("tracing-shutdown".to_string())
+        .spawn(move || {
+            step();
+            let _ = done_tx.send(());
+        });
+    if let Err(e) = spawned {
+        eprintln!("Could not start tracing shutdown: {}", e);
+        return false;
+    }
+
+    match tokio::time::timeout(timeout, done_rx).await {
+        Ok(Ok(())) => true,
+        Ok(Err(_)) => {
+            eprintln!("Tracing shutdown panicked; pending spans may be lost");
+            false
+        }
+        Err(_) => {
+            eprintln!(
+                "Tracing shutdown did not finish within {:?}; pending spans may be lost",
+                timeout
+            );
+            false
+        }
+    }
 }
 
 /// Helper macro for creating instrumented async functions
@@ -150,6 +258,17 @@ macro_rules! instrument_async {
     };
 }
 
+/// Trace id of the current span, when the OpenTelemetry layer is active
+pub fn current_trace_id() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| span_context.trace_id().to_string())
+}
+
 /// Helper for adding context to the current span
 pub fn add_span_context(key: &str, value: &str) {
     tracing::Span::current().record(key, value);
@@ -172,3 +291,61 @@ pub fn record_error(error: &dyn std::error::Error) {
         depth += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_slow_shutdown_is_abandoned_after_the_timeout() {
+        let start = Instant::now();
+
+        // Stands in for an exporter stuck on an unreachable collector
+        let finished = run_bounded(Duration::from_millis(100), || {
+            std::thread::sleep(Duration::from_secs(5))
+        })
+        .await;
+
+        assert!(!finished);
+        assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_shutdown_completes() {
+        assert!(run_bounded(Duration::from_secs(5), || ()).await);
+    }
+
+    /// Boot logs as `main` emits them, under the given QUIET_STARTUP setting
+    fn startup_logs(quiet: bool) -> String {
+        let logs = crate::test_support::CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(quiet_startup_filter(EnvFilter::new("info"), quiet))
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: STARTUP_STEP_TARGET, "Configuration loaded");
+            crate::config::summary::StartupSummary::from_config(&crate::config::Config::dev()).log();
+            tracing::error!(target: STARTUP_STEP_TARGET, "Startup failed: boom");
+        });
+
+        logs.contents()
+    }
+
+    #[test]
+    fn test_quiet_startup_keeps_summary_and_errors_only() {
+        let logs = startup_logs(true);
+        assert!(logs.contains("Startup configuration"), "{}", logs);
+        assert!(logs.contains("Startup failed"), "{}", logs);
+        assert!(!logs.contains("Configuration loaded"), "{}", logs);
+    }
+
+    #[test]
+    fn test_default_startup_logs_every_step() {
+        let logs = startup_logs(false);
+        assert!(logs.contains("Configuration loaded"), "{}", logs);
+        assert!(logs.contains("Startup configuration"), "{}", logs);
+    }
+}
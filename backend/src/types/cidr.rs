@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+
+/// An IPv4 or IPv6 network in CIDR notation
+///
+/// A bare address parses as a single-host network (`/32` or `/128`), so
+/// allowlists can mix `10.0.0.0/8` and `192.168.1.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    pub fn parse(value: &str) -> Result<Self, CidrError> {
+        let value = value.trim();
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| CidrError::InvalidAddress(value.to_string()))?;
+        let max_len = max_prefix_len(addr);
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_len)
+                .ok_or_else(|| CidrError::InvalidPrefix(value.to_string()))?,
+            None => max_len,
+        };
+
+        Ok(Self {
+            network: mask(addr, prefix_len),
+            prefix_len,
+        })
+    }
+
+    /// Whether `ip` falls inside this network
+    ///
+    /// IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) match IPv4 networks.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        max_prefix_len(ip) == max_prefix_len(self.network)
+            && mask(ip, self.prefix_len) == self.network
+    }
+}
+
+fn max_prefix_len(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// Zero the host bits of `addr`
+fn mask(addr: IpAddr, prefix_len: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(v4) => {
+            let bits = u32::from(v4)
+                & u32::MAX.checked_shl(32 - u32::from(prefix_len)).unwrap_or(0);
+            IpAddr::V4(bits.into())
+        }
+        IpAddr::V6(v6) => {
+            let bits = u128::from(v6)
+                & u128::MAX.checked_shl(128 - u32::from(prefix_len)).unwrap_or(0);
+            IpAddr::V6(bits.into())
+        }
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+impl std::str::FromStr for IpCidr {
+    type Err = CidrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = CidrError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(cidr: IpCidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+/// CIDR parsing errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CidrError {
+    #[error("'{0}' is not a valid IP address")]
+    InvalidAddress(String),
+
+    #[error("'{0}' has an invalid prefix length")]
+    InvalidPrefix(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_contains_matches_network() {
+        let cidr = IpCidr::parse("10.1.0.0/16").unwrap();
+        assert!(cidr.contains(ip("10.1.200.3")));
+        assert!(!cidr.contains(ip("10.2.0.1")));
+        assert!(cidr.contains(ip("::ffff:10.1.0.9")));
+    }
+
+    #[test]
+    fn test_bare_address_is_single_host() {
+        let cidr = IpCidr::parse("2001:db8::1").unwrap();
+        assert!(cidr.contains(ip("2001:db8::1")));
+        assert!(!cidr.contains(ip("2001:db8::2")));
+        assert_eq!(IpCidr::parse("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_values() {
+        assert!(matches!(IpCidr::parse("10.0.0/8"), Err(CidrError::InvalidAddress(_))));
+        assert!(matches!(IpCidr::parse("10.0.0.0/33"), Err(CidrError::InvalidPrefix(_))));
+    }
+}
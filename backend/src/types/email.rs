@@ -86,6 +86,7 @@ impl AsRef<str> for Email {
 }
 
 // Diesel support
+#[cfg(feature = "server")]
 impl<DB> diesel::serialize::ToSql<diesel::sql_types::Text, DB> for Email
 where
     DB: diesel::backend::Backend,
@@ -99,6 +100,7 @@ where
     }
 }
 
+#[cfg(feature = "server")]
 impl<DB> diesel::deserialize::FromSql<diesel::sql_types::Text, DB> for Email
 where
     DB: diesel::backend::Backend,
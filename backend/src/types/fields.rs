@@ -0,0 +1,72 @@
+use serde_json::Value;
+
+/// Response fields a client asked for with `?fields=id,email`
+///
+/// Parsed against the allowlist of the response type, so a typo is a 400
+/// instead of a silently empty object. [`retain`](Self::retain) then drops
+/// every other key from the serialized response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection(Vec<String>);
+
+impl FieldSelection {
+    /// Parse a comma-separated field list, rejecting names not in `allowed`
+    pub fn parse(raw: &str, allowed: &[&str]) -> Result<Self, String> {
+        let mut fields: Vec<String> = Vec::new();
+        for field in raw.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            if !allowed.contains(&field) {
+                return Err(format!(
+                    "Unknown field '{}'. Expected any of: {}",
+                    field,
+                    allowed.join(", ")
+                ));
+            }
+            if !fields.iter().any(|f| f == field) {
+                fields.push(field.to_string());
+            }
+        }
+
+        if fields.is_empty() {
+            return Err("fields must name at least one field".to_string());
+        }
+        Ok(Self(fields))
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Remove every key of the JSON object `value` that wasn't selected
+    pub fn retain(&self, value: &mut Value) {
+        if let Value::Object(map) = value {
+            map.retain(|key, _| self.0.iter().any(|f| f == key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ALLOWED: &[&str] = &["id", "email", "username"];
+
+    #[test]
+    fn test_parse_trims_and_dedupes() {
+        let selection = FieldSelection::parse(" id, email,id ", ALLOWED).unwrap();
+        assert_eq!(selection.fields(), ["id", "email"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_and_empty() {
+        assert!(FieldSelection::parse("id,password_hash", ALLOWED).is_err());
+        assert!(FieldSelection::parse(" , ", ALLOWED).is_err());
+    }
+
+    #[test]
+    fn test_retain_keeps_selected_keys() {
+        let selection = FieldSelection::parse("id,email", ALLOWED).unwrap();
+        let mut value = json!({ "id": 1, "email": "a@example.com", "username": "a" });
+        selection.retain(&mut value);
+        assert_eq!(value, json!({ "id": 1, "email": "a@example.com" }));
+    }
+}
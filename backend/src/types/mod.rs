@@ -1,6 +1,15 @@
 // Type-safe wrappers (newtypes) for domain primitives
 // Provides compile-time guarantees and prevents primitive obsession
 
+pub mod cidr;
 pub mod user_id;
 pub mod email;
+pub mod fields;
+pub mod patch;
+pub mod tenant_id;
+
+pub use cidr::IpCidr;
+pub use fields::FieldSelection;
+pub use patch::Patch;
+pub use tenant_id::TenantId;
 
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Deserializer};
+
+/// Tri-state field for PATCH request bodies
+///
+/// `Option<T>` can't tell "field omitted" from "field set to null". With
+/// `#[serde(default)]` on the field, an omitted key is `Undefined` (leave the
+/// column unchanged), `null` is `Null` (clear it), and anything else is `Value`.
+///
+/// ```no_run
+/// # use backend::types::patch::Patch;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct UpdateDto {
+///     #[serde(default)]
+///     bio: Patch<String>,
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Undefined,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Patch::Undefined)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Patch::Null)
+    }
+
+    /// The provided value, if any
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// `None` when omitted, `Some(None)` when null, `Some(Some(v))` when set
+    ///
+    /// This is the shape diesel's `AsChangeset` expects for nullable columns.
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Patch::Undefined => None,
+            Patch::Null => Some(None),
+            Patch::Value(value) => Some(Some(value)),
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Only called when the key is present; omitted keys use Default
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Body {
+        #[serde(default)]
+        name: Patch<String>,
+    }
+
+    #[test]
+    fn test_omitted_field_is_undefined() {
+        let body: Body = serde_json::from_str("{}").unwrap();
+        assert_eq!(body.name, Patch::Undefined);
+    }
+
+    #[test]
+    fn test_null_field_is_null() {
+        let body: Body = serde_json::from_str(r#"{"name": null}"#).unwrap();
+        assert_eq!(body.name, Patch::Null);
+        assert_eq!(body.name.into_option(), Some(None));
+    }
+
+    #[test]
+    fn test_present_field_is_value() {
+        let body: Body = serde_json::from_str(r#"{"name": "alice"}"#).unwrap();
+        assert_eq!(body.name.as_value().map(String::as_str), Some("alice"));
+    }
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Tenant that owns rows created before multi-tenancy and requests that
+/// don't name one
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Maximum tenant id length (one DNS label, so it can be a subdomain)
+pub const MAX_TENANT_ID_LEN: usize = 63;
+
+/// Type-safe wrapper for a tenant identifier
+///
+/// Tenant ids are lowercase DNS labels (`a-z`, `0-9`, `-`) so the same value
+/// works as a header, a subdomain and a JWT claim.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Parse and normalize a tenant id
+    pub fn parse(value: &str) -> Result<Self, TenantIdError> {
+        let value = value.trim().to_ascii_lowercase();
+
+        if value.is_empty() {
+            return Err(TenantIdError::Empty);
+        }
+        if value.len() > MAX_TENANT_ID_LEN {
+            return Err(TenantIdError::TooLong);
+        }
+        if value.starts_with('-')
+            || value.ends_with('-')
+            || !value
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(TenantIdError::InvalidCharacters);
+        }
+
+        Ok(Self(value))
+    }
+
+    /// The tenant used when none is specified
+    pub fn default_tenant() -> Self {
+        Self(DEFAULT_TENANT.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self::default_tenant()
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for TenantId {
+    type Err = TenantIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = TenantIdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(tenant: TenantId) -> Self {
+        tenant.0
+    }
+}
+
+/// Tenant id validation errors
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TenantIdError {
+    #[error("Tenant id cannot be empty")]
+    Empty,
+
+    #[error("Tenant id must be at most 63 characters")]
+    TooLong,
+
+    #[error("Tenant id may only contain a-z, 0-9 and inner hyphens")]
+    InvalidCharacters,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_normalizes_case() {
+        let tenant = TenantId::parse(" Acme-Corp ").unwrap();
+        assert_eq!(tenant.as_str(), "acme-corp");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_ids() {
+        assert_eq!(TenantId::parse(""), Err(TenantIdError::Empty));
+        assert_eq!(TenantId::parse(&"a".repeat(64)), Err(TenantIdError::TooLong));
+        assert_eq!(TenantId::parse("acme.corp"), Err(TenantIdError::InvalidCharacters));
+        assert_eq!(TenantId::parse("-acme"), Err(TenantIdError::InvalidCharacters));
+    }
+
+    #[test]
+    fn test_serde_validates() {
+        let tenant: TenantId = serde_json::from_str("\"acme\"").unwrap();
+        assert_eq!(tenant.as_str(), "acme");
+        assert!(serde_json::from_str::<TenantId>("\"not valid\"").is_err());
+    }
+}
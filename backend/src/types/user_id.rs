@@ -68,6 +68,7 @@ impl std::str::FromStr for UserId {
 }
 
 // Diesel support
+#[cfg(feature = "server")]
 impl<DB> diesel::serialize::ToSql<diesel::sql_types::Uuid, DB> for UserId
 where
     DB: diesel::backend::Backend,
@@ -81,6 +82,7 @@ where
     }
 }
 
+#[cfg(feature = "server")]
 impl<DB> diesel::deserialize::FromSql<diesel::sql_types::Uuid, DB> for UserId
 where
     DB: diesel::backend::Backend,
@@ -0,0 +1,665 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::{
+    models::user::ROLE_ADMIN,
+    repositories::UserRepositoryTrait,
+    routes,
+    types::TenantId,
+    AppState,
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+async fn get_stats(app: &axum::Router, token: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/admin/stats")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+fn setup() -> (AppState, axum::Router) {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state.clone());
+    (state, app)
+}
+
+#[tokio::test]
+async fn test_admin_can_fetch_stats() {
+    let (state, app) = setup();
+    let auth = common::register_user(&app, None).await;
+    let (user_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::default(), user_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+
+    let response = get_stats(&app, &token).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert!(json["uptime_seconds"].is_u64());
+    assert!(json["pool"]["max_size"].as_u64().unwrap() > 0);
+    assert!(json["pool"]["active_connections"].is_u64());
+    assert!(json["requests"]["total"].is_u64());
+    assert!(json["requests"]["in_flight"].is_u64());
+    assert!(json.get("memory_usage_mb").is_some());
+}
+
+#[tokio::test]
+async fn test_regular_user_gets_forbidden() {
+    let (_state, app) = setup();
+    let auth = common::register_user(&app, None).await;
+    let (_user_id, token) = (auth.user.id, auth.token);
+
+    let response = get_stats(&app, &token).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "FORBIDDEN");
+}
+
+#[tokio::test]
+async fn test_stats_requires_authentication() {
+    let (_state, app) = setup();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/admin/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn bulk_delete(app: &axum::Router, token: &str, body: serde_json::Value) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/v1/users")
+                .header("authorization", format!("Bearer {}", token))
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+/// Register an admin and `count` regular users, returning the admin token and user ids
+async fn admin_with_users(state: &AppState, app: &axum::Router, count: usize) -> (String, Vec<uuid::Uuid>) {
+    let auth = common::register_user(app, None).await;
+    let (admin_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::default(), admin_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for _ in 0..count {
+        ids.push(common::register_user(app, None).await.user.id);
+    }
+    (token, ids)
+}
+
+#[tokio::test]
+async fn test_admin_bulk_deletes_users() {
+    let (state, app) = setup();
+    let (token, ids) = admin_with_users(&state, &app, 3).await;
+    let missing = uuid::Uuid::new_v4();
+
+    let response = bulk_delete(&app, &token, json!({ "ids": [ids[0], ids[1], missing], "confirm": true })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["deleted"], 2);
+
+    let tenant = TenantId::default();
+    for id in &ids[..2] {
+        assert!(state.user_repo().find_by_id(&tenant, *id).await.unwrap().is_none());
+    }
+    assert!(state.user_repo().find_by_id(&tenant, ids[2]).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_bulk_delete_requires_confirmation() {
+    let (state, app) = setup();
+    let (token, ids) = admin_with_users(&state, &app, 1).await;
+
+    for body in [json!({ "ids": ids }), json!({ "ids": ids, "confirm": false })] {
+        let response = bulk_delete(&app, &token, body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    assert!(state
+        .user_repo()
+        .find_by_id(&TenantId::default(), ids[0])
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_regular_user_cannot_bulk_delete() {
+    let (_state, app) = setup();
+    let auth = common::register_user(&app, None).await;
+    let (user_id, token) = (auth.user.id, auth.token);
+
+    let response = bulk_delete(&app, &token, json!({ "ids": [user_id], "confirm": true })).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_exports_tenant_users_as_csv() {
+    let (state, app) = setup();
+    // A fresh tenant, so the export holds exactly the users created here
+    let tenant = format!("export-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    let auth = common::register_user(&app, Some(&tenant)).await;
+    let (admin_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::parse(&tenant).unwrap(), admin_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+    let mut ids = vec![admin_id];
+    for _ in 0..2 {
+        ids.push(common::register_user(&app, Some(&tenant)).await.user.id);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/users/export.csv")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/csv"));
+    assert!(response.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .starts_with("attachment"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,email,username,role,created_at,last_login_at")
+    );
+
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    ids.sort();
+    let exported: Vec<uuid::Uuid> = rows.iter().map(|row| row[0].parse().unwrap()).collect();
+    assert_eq!(exported, ids, "rows are ordered by id");
+    for row in &rows {
+        assert_eq!(row.len(), 6);
+        assert!(row[1].ends_with("@example.com"));
+        let expected_role = if row[0] == admin_id.to_string() { "admin" } else { "user" };
+        assert_eq!(row[3], expected_role);
+    }
+}
+
+#[tokio::test]
+async fn test_regular_user_cannot_export_users() {
+    let (_state, app) = setup();
+    let auth = common::register_user(&app, None).await;
+    let (_user_id, token) = (auth.user.id, auth.token);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/users/export.csv")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+/// An admin plus `count` users in a fresh tenant, in registration order
+async fn tenant_with_users(state: &AppState, app: &axum::Router, count: usize) -> (String, Vec<uuid::Uuid>) {
+    let tenant = format!("list-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    let auth = common::register_user(app, Some(&tenant)).await;
+    let (admin_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::parse(&tenant).unwrap(), admin_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+
+    let mut ids = vec![admin_id];
+    for _ in 0..count {
+        ids.push(common::register_user(app, Some(&tenant)).await.user.id);
+    }
+    (token, ids)
+}
+
+async fn list_users(app: &axum::Router, token: &str, query: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/users?{}", query))
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn listed_ids(json: &serde_json::Value) -> Vec<uuid::Uuid> {
+    json["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["id"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_users_defaults_to_newest_first_with_stable_pages() {
+    let (state, app) = setup();
+    let (token, mut ids) = tenant_with_users(&state, &app, 4).await;
+    ids.reverse();
+
+    let (status, json) = list_users(&app, &token, "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["total"], 5);
+    assert_eq!(listed_ids(&json), ids);
+
+    // Paging through yields the same order with no gaps or repeats
+    let mut paged = Vec::new();
+    for offset in [0, 2, 4] {
+        let (_, json) = list_users(&app, &token, &format!("limit=2&offset={}", offset)).await;
+        paged.extend(listed_ids(&json));
+    }
+    assert_eq!(paged, ids);
+}
+
+#[tokio::test]
+async fn test_list_users_with_custom_sort() {
+    let (state, app) = setup();
+    let (token, _ids) = tenant_with_users(&state, &app, 3).await;
+
+    let (status, json) = list_users(&app, &token, "sort=username:asc").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let usernames: Vec<&str> = json["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["username"].as_str().unwrap())
+        .collect();
+    let mut sorted = usernames.clone();
+    sorted.sort();
+    assert_eq!(usernames, sorted);
+    assert_eq!(usernames.len(), 4);
+}
+
+#[tokio::test]
+async fn test_list_users_rejects_unknown_sort_column() {
+    let (state, app) = setup();
+    let (token, _ids) = tenant_with_users(&state, &app, 0).await;
+
+    for query in ["sort=password_hash", "sort=username:sideways"] {
+        let (status, json) = list_users(&app, &token, query).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert_eq!(json["error_code"], "BAD_REQUEST");
+    }
+}
+
+#[tokio::test]
+async fn test_list_users_filters_and_dash_sort() {
+    let (state, app) = setup();
+    let (token, ids) = tenant_with_users(&state, &app, 3).await;
+    let mut user_ids = ids[1..].to_vec();
+    user_ids.reverse();
+
+    let (status, json) = list_users(&app, &token, "filter[role]=user&sort=-created_at&limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["total"], 3);
+    assert_eq!(listed_ids(&json), user_ids[..2]);
+
+    // `contains` matches a substring, ignoring case
+    let username = json["users"][0]["username"].as_str().unwrap().to_uppercase();
+    let (_, json) = list_users(&app, &token, &format!("filter[username][contains]={}", &username[2..])).await;
+    assert_eq!(listed_ids(&json), user_ids[..1]);
+
+    let (_, json) = list_users(&app, &token, "filter[role]=admin").await;
+    assert_eq!(json["total"], 1);
+    assert_eq!(listed_ids(&json), [ids[0]]);
+
+    let (_, json) = list_users(&app, &token, "filter[created_at][gte]=2000-01-01&sort=created_at").await;
+    assert_eq!(listed_ids(&json), ids);
+
+    let (_, json) = list_users(&app, &token, "filter[created_at][lt]=2000-01-01").await;
+    assert_eq!(json["total"], 0);
+
+    // LIKE wildcards in the value match literally
+    let (_, json) = list_users(&app, &token, "filter[username][contains]=%25").await;
+    assert_eq!(json["total"], 0);
+}
+
+#[tokio::test]
+async fn test_list_users_rejects_disallowed_filters() {
+    let (state, app) = setup();
+    let (token, _ids) = tenant_with_users(&state, &app, 0).await;
+
+    for query in [
+        "filter[password_hash]=x",
+        "filter[role][contains]=adm",
+        "filter[username][gt]=a",
+        "filter[username][like]=a",
+        "filter[created_at][gt]=yesterday",
+        "filter[username",
+        "sort=-password_hash",
+    ] {
+        let (status, json) = list_users(&app, &token, query).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+        assert_eq!(json["error_code"], "BAD_REQUEST");
+    }
+}
+
+async fn get_me(app: &axum::Router, token: &str, query: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/auth/me?{}", query))
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn keys(json: &serde_json::Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort();
+    keys
+}
+
+#[tokio::test]
+async fn test_fields_selects_a_subset_of_user_fields() {
+    let (state, app) = setup();
+    let (token, ids) = tenant_with_users(&state, &app, 2).await;
+
+    let (status, me) = get_me(&app, &token, "fields=id,email").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(keys(&me), ["email", "id"]);
+    assert_eq!(me["id"], ids[0].to_string());
+
+    let (status, json) = list_users(&app, &token, "fields=id,username").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["total"], 3);
+    for user in json["users"].as_array().unwrap() {
+        assert_eq!(keys(user), ["id", "username"]);
+    }
+}
+
+#[tokio::test]
+async fn test_fields_rejects_unknown_field() {
+    let (state, app) = setup();
+    let (token, _ids) = tenant_with_users(&state, &app, 0).await;
+
+    let (status, json) = get_me(&app, &token, "fields=id,password_hash").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error_code"], "BAD_REQUEST");
+
+    let (status, _) = list_users(&app, &token, "fields=role").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_without_fields_returns_full_user() {
+    let (state, app) = setup();
+    let (token, _ids) = tenant_with_users(&state, &app, 1).await;
+
+    let (status, me) = get_me(&app, &token, "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(keys(&me), ["created_at", "email", "id", "username"]);
+
+    let (_, json) = list_users(&app, &token, "").await;
+    for user in json["users"].as_array().unwrap() {
+        assert_eq!(keys(user), ["created_at", "email", "id", "username"]);
+    }
+}
+
+#[tokio::test]
+async fn test_list_users_streams_ndjson_when_accepted() {
+    let (state, app) = setup();
+    let (token, mut ids) = tenant_with_users(&state, &app, 3).await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/users?limit=1")
+                .header("authorization", format!("Bearer {}", token))
+                .header("accept", "application/x-ndjson, application/json;q=0.5")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let users: Vec<serde_json::Value> = String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    // Every user regardless of `limit`, one object per line in id order
+    ids.sort();
+    let streamed: Vec<uuid::Uuid> = users
+        .iter()
+        .map(|u| u["id"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(streamed, ids);
+    assert!(users.iter().all(|u| u["email"].is_string()));
+
+    // Plain JSON clients still get the paginated array
+    let (status, json) = list_users(&app, &token, "limit=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed_ids(&json).len(), 1);
+}
+
+async fn list_users_if_none_match(app: &axum::Router, token: &str, etag: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/users")
+                .header("authorization", format!("Bearer {}", token))
+                .header("if-none-match", etag)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_list_users_etag_revalidation() {
+    let (state, app) = setup();
+    let tenant = format!("etag-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    let auth = common::register_user(&app, Some(&tenant)).await;
+    let (admin_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::parse(&tenant).unwrap(), admin_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+
+    let first = list_users_if_none_match(&app, &token, "\"stale\"").await;
+    assert_eq!(first.status(), StatusCode::OK);
+    let etag = first.headers()["etag"].to_str().unwrap().to_string();
+
+    // Nothing changed: empty 304 with the same tag
+    let unchanged = list_users_if_none_match(&app, &token, &etag).await;
+    assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(unchanged.headers()["etag"], etag.as_str());
+    let body = axum::body::to_bytes(unchanged.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.is_empty());
+
+    // A new user changes the collection
+    common::register_user(&app, Some(&tenant)).await;
+    let changed = list_users_if_none_match(&app, &token, &etag).await;
+    assert_eq!(changed.status(), StatusCode::OK);
+    assert_ne!(changed.headers()["etag"], etag.as_str());
+}
+
+#[tokio::test]
+async fn test_list_users_etag_changes_after_login() {
+    let (state, app) = setup();
+    let tenant = format!("etag-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+    let tenant_id = TenantId::parse(&tenant).unwrap();
+    let auth = common::register_user(&app, Some(&tenant)).await;
+    let (admin_id, token) = (auth.user.id, auth.token);
+    state.user_repo().update_role(&tenant_id, admin_id, ROLE_ADMIN).await.unwrap();
+
+    let first = list_users_if_none_match(&app, &token, "\"stale\"").await;
+    assert_eq!(first.status(), StatusCode::OK);
+    let etag = first.headers()["etag"].to_str().unwrap().to_string();
+
+    // Logging in only moves last_login_at, which the listing shows
+    let admin = state.user_repo().find_by_id(&tenant_id, admin_id).await.unwrap().unwrap();
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/login")
+                .header("content-type", "application/json")
+                .header("x-tenant-id", tenant.as_str())
+                .body(Body::from(
+                    json!({ "email": admin.email, "password": common::TEST_PASSWORD }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let after_login = list_users_if_none_match(&app, &token, &etag).await;
+    assert_eq!(after_login.status(), StatusCode::OK);
+    assert_ne!(after_login.headers()["etag"], etag.as_str());
+}
+
+#[tokio::test]
+async fn test_reload_picks_up_rotated_jwt_secret() {
+    use backend::config::secrets::{MemorySecretProvider, SecretManager};
+    use backend::services::jwt::{JwtService, TokenOptions};
+
+    let provider = MemorySecretProvider::default();
+    let state = common::setup_test_state();
+    let old_secret = state.config.jwt.secret.clone();
+    provider.set("JWT_SECRET", &old_secret);
+    let state = state.with_secret_manager(std::sync::Arc::new(SecretManager::new(Box::new(provider.clone()))));
+    let app = routes::create_router(state.clone());
+
+    let auth = common::register_user(&app, None).await;
+    let (user_id, token) = (auth.user.id, auth.token);
+    state
+        .user_repo()
+        .update_role(&TenantId::default(), user_id, ROLE_ADMIN)
+        .await
+        .unwrap();
+
+    // Rotate in the secret store: new signing secret, old one kept for verification
+    let new_secret = "rotated-secret-with-plenty-of-entropy-0123456789";
+    provider.set("JWT_SECRET", new_secret);
+    provider.set("JWT_SECRET_PREVIOUS", &old_secret);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/admin/secrets/reload")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["jwt_secret_rotated"], true);
+    assert_eq!(json["jwt_previous_secrets"], 1);
+
+    // Tokens signed before the rotation still verify
+    assert_eq!(get_stats(&app, &token).await.status(), StatusCode::OK);
+
+    // New tokens are signed with the rotated secret
+    let issued = state
+        .jwt()
+        .issue_token(
+            user_id,
+            "a@example.com".to_string(),
+            "a".to_string(),
+            TenantId::default(),
+            TokenOptions::default(),
+        )
+        .unwrap()
+        .token;
+    assert!(JwtService::new(new_secret.to_string(), 24).verify_token(&issued).is_ok());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::{
+    repositories::UserRepository,
+    routes,
+    services::{
+        audit::{AuditAction, MemoryAuditLog},
+        auth::AuthService,
+    },
+    types::TenantId,
+    AppState,
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_registration_audit_entry_carries_request_ip_and_id() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.trust_proxy = true;
+    let mut state = AppState::new(config, state.db_pool.clone());
+
+    let audit = Arc::new(MemoryAuditLog::default());
+    state.services.auth = Arc::new(
+        AuthService::new(UserRepository::new(state.db_pool.clone()), state.jwt().clone())
+            .with_audit_log(audit.clone()),
+    );
+    let app = routes::create_router(state);
+
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .header("x-request-id", "audit-req-1")
+                .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+                .header("user-agent", "audit-test/1.0")
+                .body(Body::from(
+                    json!({
+                        "email": format!("audit-{}@example.com", suffix),
+                        "username": format!("audit{}", suffix),
+                        "password": "SecurePass123!"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let entries = audit.entries();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.action, AuditAction::Register);
+    assert_eq!(entry.tenant, TenantId::default());
+    assert_eq!(entry.request_id.as_deref(), Some("audit-req-1"));
+    assert_eq!(entry.ip.as_deref(), Some("203.0.113.7"));
+    assert_eq!(entry.user_agent.as_deref(), Some("audit-test/1.0"));
+}
@@ -4,7 +4,7 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use backend::{models::dto::*, routes};
+use backend::{middleware::context::RequestContext, models::dto::*, routes, types::TenantId};
 use serde_json::json;
 use tower::ServiceExt;
 
@@ -315,6 +315,45 @@ async fn test_invalid_email_validation() {
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 }
 
+/// Current value of `validation_errors_total{field="<field>"}`, 0 if unset
+async fn validation_error_count(field: &str) -> u64 {
+    let prefix = format!("validation_errors_total{{field=\"{}\"}} ", field);
+    backend::metrics::metrics_handler()
+        .await
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix)?.parse().ok())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_invalid_email_increments_validation_counter() {
+    backend::metrics::init_metrics().unwrap();
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+    let before = validation_error_count("email").await;
+
+    let register_payload = json!({
+        "email": "not-an-email",
+        "username": "testuser",
+        "password": "SecurePass123!"
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(register_payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(validation_error_count("email").await > before);
+}
+
 #[tokio::test]
 async fn test_short_password_validation() {
     let state = common::setup_test_state();
@@ -340,3 +379,900 @@ async fn test_short_password_validation() {
 
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 }
+
+/// Hash a password with deliberately weak argon2 parameters
+fn weak_password_hash(password: &str) -> String {
+    use argon2::{password_hash::SaltString, Algorithm, Argon2, Params, PasswordHasher, Version};
+
+    let params = Params::new(8, 1, 1, None).unwrap();
+    let salt = SaltString::generate(&mut password_hash::rand_core::OsRng);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_login_upgrades_weak_password_hash() {
+    use backend::models::user::LoginRequest;
+    use backend::repositories::UserRepositoryTrait;
+
+    let state = common::setup_test_state();
+    common::cleanup_test_data(&state.db_pool).await;
+
+    let user = state
+        .user_repo()
+        .create(
+            &TenantId::default(),
+            backend::models::user::NewUser {
+                email: "rehash@example.com".to_string(),
+                username: "rehashuser".to_string(),
+                password_hash: weak_password_hash("SecurePass123!"),
+                provider: None,
+                provider_id: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    state
+        .auth()
+        .login(
+            &RequestContext::default(),
+            &TenantId::default(),
+            LoginRequest {
+                email: "rehash@example.com".to_string(),
+                password: "SecurePass123!".to_string(),
+            },
+            None,
+        )
+        .await
+        .expect("login with weak hash should succeed");
+
+    let stored = state
+        .user_repo()
+        .find_by_id(&TenantId::default(), user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let parsed = argon2::PasswordHash::new(&stored.password_hash).unwrap();
+    let params = argon2::Params::try_from(&parsed).unwrap();
+    assert_eq!(params.m_cost(), state.config.password.argon2_memory_kib);
+    assert_eq!(params.t_cost(), state.config.password.argon2_iterations);
+    assert_eq!(params.p_cost(), state.config.password.argon2_parallelism);
+
+    // The upgraded hash must still verify
+    state
+        .auth()
+        .login(
+            &RequestContext::default(),
+            &TenantId::default(),
+            LoginRequest {
+                email: "rehash@example.com".to_string(),
+                password: "SecurePass123!".to_string(),
+            },
+            None,
+        )
+        .await
+        .expect("login with upgraded hash should succeed");
+}
+
+#[tokio::test]
+async fn test_login_keeps_weak_hash_when_rehash_disabled() {
+    use backend::config::PasswordConfig;
+    use backend::models::user::LoginRequest;
+    use backend::repositories::{UserRepository, UserRepositoryTrait};
+    use backend::services::auth::AuthService;
+
+    let state = common::setup_test_state();
+    common::cleanup_test_data(&state.db_pool).await;
+
+    let weak_hash = weak_password_hash("SecurePass123!");
+    let user = state
+        .user_repo()
+        .create(
+            &TenantId::default(),
+            backend::models::user::NewUser {
+                email: "norehash@example.com".to_string(),
+                username: "norehashuser".to_string(),
+                password_hash: weak_hash.clone(),
+                provider: None,
+                provider_id: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let auth = AuthService::new(UserRepository::new(state.db_pool.clone()), state.jwt().clone())
+        .with_password_config(&PasswordConfig {
+            rehash_on_login: false,
+            ..PasswordConfig::default()
+        });
+
+    auth.login(
+        &RequestContext::default(),
+        &TenantId::default(),
+        LoginRequest {
+            email: "norehash@example.com".to_string(),
+            password: "SecurePass123!".to_string(),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let stored = state
+        .user_repo()
+        .find_by_id(&TenantId::default(), user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.password_hash, weak_hash);
+}
+
+#[tokio::test]
+async fn test_unknown_email_login_takes_as_long_as_wrong_password() {
+    use backend::config::PasswordConfig;
+    use backend::models::user::{LoginRequest, RegisterRequest};
+    use backend::repositories::UserRepository;
+    use backend::services::auth::AuthService;
+    use std::time::{Duration, Instant};
+
+    let state = common::setup_test_state();
+    let auth = AuthService::new(UserRepository::new(state.db_pool.clone()), state.jwt().clone())
+        .with_password_config(&PasswordConfig {
+            equalize_login_timing: true,
+            ..PasswordConfig::default()
+        });
+
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let email = format!("timing-{}@example.com", suffix);
+    auth.register(
+        &RequestContext::default(),
+        &TenantId::default(),
+        RegisterRequest {
+            email: email.clone(),
+            username: format!("timing{}", suffix),
+            password: "SecurePass123!".to_string(),
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let fastest_failed_login = |email: String| {
+        let auth = auth.clone();
+        async move {
+            let mut fastest = Duration::MAX;
+            // The first unknown-email login also creates the dummy hash
+            for _ in 0..4 {
+                let start = Instant::now();
+                let result = auth
+                    .login(
+                        &RequestContext::default(),
+                        &TenantId::default(),
+                        LoginRequest {
+                            email: email.clone(),
+                            password: "WrongPass123!".to_string(),
+                        },
+                        None,
+                    )
+                    .await;
+                fastest = fastest.min(start.elapsed());
+                assert!(result.is_err());
+            }
+            fastest
+        }
+    };
+
+    let wrong_password = fastest_failed_login(email).await;
+    let unknown_email = fastest_failed_login(format!("nobody-{}@example.com", suffix)).await;
+
+    // Only a verify explains the time; a bare lookup is orders of magnitude faster
+    assert!(
+        unknown_email * 3 >= wrong_password,
+        "unknown email took {:?}, wrong password {:?}",
+        unknown_email,
+        wrong_password
+    );
+}
+
+#[tokio::test]
+async fn test_peppered_register_and_login_round_trip() {
+    use backend::config::PasswordConfig;
+    use backend::models::user::{LoginRequest, RegisterRequest};
+    use backend::repositories::UserRepository;
+    use backend::services::auth::AuthService;
+
+    let state = common::setup_test_state();
+    common::cleanup_test_data(&state.db_pool).await;
+
+    let service = |pepper: Option<&str>| {
+        AuthService::new(UserRepository::new(state.db_pool.clone()), state.jwt().clone())
+            .with_password_config(&PasswordConfig {
+                pepper: pepper.map(str::to_string),
+                ..PasswordConfig::default()
+            })
+    };
+    let login = || LoginRequest {
+        email: "pepper@example.com".to_string(),
+        password: "SecurePass123!".to_string(),
+    };
+    let tenant = TenantId::default();
+
+    service(Some("pepper-one"))
+        .register(
+            &RequestContext::default(),
+            &tenant,
+            RegisterRequest {
+                email: "pepper@example.com".to_string(),
+                username: "pepperuser".to_string(),
+                password: "SecurePass123!".to_string(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    service(Some("pepper-one"))
+        .login(&RequestContext::default(), &tenant, login(), None)
+        .await
+        .expect("login with the same pepper should succeed");
+
+    // Rotating or dropping the pepper invalidates the stored hash
+    assert!(service(Some("pepper-two")).login(&RequestContext::default(), &tenant, login(), None).await.is_err());
+    assert!(service(None).login(&RequestContext::default(), &tenant, login(), None).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_concurrent_logins_do_not_starve_health_check() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.database.pool_size = 8;
+    config.password.max_concurrent_hashes = 2;
+    let db_pool =
+        backend::db::create_pool(&config.database.url, config.database.pool_size).unwrap();
+    let app = routes::create_router(backend::AppState::new(config, db_pool));
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let email = format!("concurrent-{}@example.com", &suffix[..12]);
+    let register_payload = json!({
+        "email": email,
+        "username": email.split('@').next().unwrap(),
+        "password": "SecurePass123!"
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(register_payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let login_payload = json!({ "email": email, "password": "SecurePass123!" }).to_string();
+    let logins: Vec<_> = (0..32)
+        .map(|_| {
+            let app = app.clone();
+            let payload = login_payload.clone();
+            tokio::spawn(async move {
+                app.oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/v1/auth/login")
+                        .header("content-type", "application/json")
+                        .body(Body::from(payload))
+                        .unwrap(),
+                )
+                .await
+                .unwrap()
+                .status()
+            })
+        })
+        .collect();
+
+    // Let the logins start hashing before probing
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    let start = std::time::Instant::now();
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let health_latency = start.elapsed();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for login in logins {
+        assert_eq!(login.await.unwrap(), StatusCode::OK);
+    }
+
+    assert!(
+        health_latency < std::time::Duration::from_millis(500),
+        "health check took {:?} while logins were hashing",
+        health_latency
+    );
+}
+
+/// Call GET /me with a bearer token and return (status, error_code, WWW-Authenticate)
+async fn me_with_token(token: &str) -> (StatusCode, String, String) {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/me")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let challenge = response
+        .headers()
+        .get("www-authenticate")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    (status, json["error_code"].as_str().unwrap_or_default().to_string(), challenge)
+}
+
+#[tokio::test]
+async fn test_expired_token_returns_token_expired() {
+    use backend::services::jwt::Claims;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    let secret = backend::config::Config::default_test_config().jwt.secret;
+    let now = chrono::Utc::now();
+    let claims = Claims {
+        sub: uuid::Uuid::new_v4().to_string(),
+        email: "expired@example.com".to_string(),
+        username: "expired".to_string(),
+        tenant: TenantId::default(),
+        exp: (now - chrono::Duration::hours(1)).timestamp(),
+        iat: (now - chrono::Duration::hours(2)).timestamp(),
+        nbf: None,
+        fpt: None,
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap();
+
+    let (status, error_code, challenge) = me_with_token(&token).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error_code, "TOKEN_EXPIRED");
+    assert!(challenge.contains(r#"error="invalid_token""#));
+    assert!(challenge.contains("expired"));
+}
+
+#[tokio::test]
+async fn test_garbage_token_returns_token_invalid() {
+    let (status, error_code, challenge) = me_with_token("not.a.jwt").await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error_code, "TOKEN_INVALID");
+    assert!(challenge.contains(r#"error="invalid_token""#));
+    assert!(!challenge.contains("expired"));
+}
+
+/// A fresh app with one registered user, returning (app, token, email)
+async fn app_with_user() -> (axum::Router, String, String) {
+    let app = routes::create_router(common::setup_test_state());
+    let auth = common::register_user(&app, None).await;
+    (app, auth.token, auth.user.email)
+}
+
+async fn patch_me(
+    app: &axum::Router,
+    token: &str,
+    payload: serde_json::Value,
+) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri("/api/v1/auth/me")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_patch_me_leaves_omitted_fields_unchanged() {
+    let (app, token, email) = app_with_user().await;
+    let new_username = format!("renamed{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+
+    let response = patch_me(&app, &token, json!({ "username": new_username })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let user: UserResponseDto = serde_json::from_slice(&body).unwrap();
+    assert_eq!(user.username, new_username);
+    assert_eq!(user.email, email, "omitted email must not change");
+}
+
+#[tokio::test]
+async fn test_patch_me_with_empty_body_changes_nothing() {
+    let (app, token, email) = app_with_user().await;
+
+    let response = patch_me(&app, &token, json!({})).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let user: UserResponseDto = serde_json::from_slice(&body).unwrap();
+    assert_eq!(user.email, email);
+}
+
+#[tokio::test]
+async fn test_patch_me_rejects_null_and_invalid_fields() {
+    let (app, token, _) = app_with_user().await;
+
+    // Explicit null on a non-nullable column is distinct from omitting it
+    let response = patch_me(&app, &token, json!({ "email": null })).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = patch_me(&app, &token, json!({ "email": "not-an-email" })).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+async fn patch_me_unmodified_since(
+    app: &axum::Router,
+    token: &str,
+    since: chrono::DateTime<chrono::Utc>,
+    payload: serde_json::Value,
+) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri("/api/v1/auth/me")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .header(
+                    "if-unmodified-since",
+                    since.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+                )
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_patch_me_stale_if_unmodified_since_is_412() {
+    let (app, token, email) = app_with_user().await;
+    let an_hour_ago = chrono::Utc::now() - chrono::Duration::hours(1);
+
+    let response =
+        patch_me_unmodified_since(&app, &token, an_hour_ago, json!({ "email": "stale@example.com" }))
+            .await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "PRECONDITION_FAILED");
+
+    // The stale update was not applied
+    let response = patch_me(&app, &token, json!({})).await;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let user: UserResponseDto = serde_json::from_slice(&body).unwrap();
+    assert_eq!(user.email, email);
+}
+
+#[tokio::test]
+async fn test_patch_me_current_if_unmodified_since_succeeds() {
+    let (app, token, _) = app_with_user().await;
+    let new_username = format!("current{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+
+    let response =
+        patch_me_unmodified_since(&app, &token, chrono::Utc::now(), json!({ "username": new_username }))
+            .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let user: UserResponseDto = serde_json::from_slice(&body).unwrap();
+    assert_eq!(user.username, new_username);
+}
+
+async fn fetch_nonce(app: &axum::Router, token: &str) -> String {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/nonce")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let dto: NonceResponseDto = serde_json::from_slice(&body).unwrap();
+    dto.nonce
+}
+
+async fn delete_me(app: &axum::Router, token: &str, nonce: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/v1/auth/me")
+                .header("authorization", format!("Bearer {}", token))
+                .header("x-nonce", nonce)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_delete_me_with_nonce_deletes_account() {
+    let (app, token, _) = app_with_user().await;
+    let nonce = fetch_nonce(&app, &token).await;
+
+    let response = delete_me(&app, &token, &nonce).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/me")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_replayed_nonce_is_rejected_with_conflict() {
+    let (app, token, _) = app_with_user().await;
+    let nonce = fetch_nonce(&app, &token).await;
+
+    assert_eq!(delete_me(&app, &token, &nonce).await.status(), StatusCode::NO_CONTENT);
+
+    let response = delete_me(&app, &token, &nonce).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "CONFLICT");
+}
+
+#[tokio::test]
+async fn test_delete_me_without_nonce_is_rejected() {
+    let (app, token, _) = app_with_user().await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/v1/auth/me")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Register a uniquely named user with the given token transport and return
+/// (app, response status, Set-Cookie header, JSON body)
+async fn register_with_transport(
+    transport: backend::config::TokenTransport,
+) -> (axum::Router, StatusCode, Option<String>, serde_json::Value) {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.jwt.transport = transport;
+    let app = routes::create_router(backend::AppState::new(config, state.db_pool.clone()));
+
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let payload = json!({
+        "email": format!("transport-{}@example.com", suffix),
+        "username": format!("transport{}", suffix),
+        "password": "SecurePass123!"
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let cookie = response
+        .headers()
+        .get("set-cookie")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    (app, status, cookie, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_header_mode_returns_token_in_body() {
+    let (_, status, cookie, body) =
+        register_with_transport(backend::config::TokenTransport::Header).await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["token"].as_str().is_some_and(|t| !t.is_empty()));
+    assert!(cookie.is_none());
+}
+
+#[tokio::test]
+async fn test_cookie_mode_omits_body_token_and_sets_cookie() {
+    let (app, status, cookie, body) =
+        register_with_transport(backend::config::TokenTransport::Cookie).await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body.get("token").is_none(), "token leaked into body: {}", body);
+
+    let cookie = cookie.expect("Set-Cookie header");
+    assert!(cookie.starts_with("auth_token="));
+    assert!(cookie.contains("HttpOnly"));
+
+    // The cookie alone authenticates
+    let token_pair = cookie.split(';').next().unwrap().to_string();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/me")
+                .header("cookie", token_pair)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_logout_clears_the_auth_cookie() {
+    let (app, _, cookie, _) =
+        register_with_transport(backend::config::TokenTransport::Cookie).await;
+    let token_pair = cookie.unwrap().split(';').next().unwrap().to_string();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/logout")
+                .header("cookie", token_pair)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let cleared = response.headers()["set-cookie"].to_str().unwrap();
+    assert!(cleared.starts_with("auth_token=;"), "unexpected cookie: {}", cleared);
+    assert!(cleared.contains("Max-Age=0"));
+    assert!(cleared.contains("HttpOnly"));
+}
+
+#[tokio::test]
+async fn test_bound_token_only_works_from_the_same_client() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.jwt.bind_fingerprint = true;
+    let app = routes::create_router(backend::AppState::new(config, state.db_pool.clone()));
+
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let payload = json!({
+        "email": format!("bound-{}@example.com", suffix),
+        "username": format!("bound{}", suffix),
+        "password": "SecurePass123!"
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .header("user-agent", "TestBrowser/1.0")
+                .header("x-client-id", "install-1")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let token = serde_json::from_slice::<AuthResponseDto>(&body).unwrap().token;
+
+    let me_from = |user_agent: &'static str, client_id: &'static str| {
+        let app = app.clone();
+        let token = token.clone();
+        async move {
+            app.oneshot(
+                Request::builder()
+                    .uri("/api/v1/auth/me")
+                    .header("authorization", format!("Bearer {}", token))
+                    .header("user-agent", user_agent)
+                    .header("x-client-id", client_id)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+        }
+    };
+
+    assert_eq!(me_from("TestBrowser/1.0", "install-1").await, StatusCode::OK);
+    assert_eq!(me_from("OtherBrowser/2.0", "install-1").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(me_from("TestBrowser/1.0", "install-2").await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_login_sets_last_login_at() {
+    use backend::repositories::UserRepositoryTrait;
+
+    let (app, _, email) = app_with_user().await;
+    let state = common::setup_test_state();
+    let tenant = TenantId::default();
+
+    let before = state.user_repo().find_by_email(&tenant, &email).await.unwrap().unwrap();
+    assert!(before.last_login_at.is_none(), "registering is not a login");
+
+    let started = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(5);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/login")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "email": email, "password": common::TEST_PASSWORD }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let auth: AuthResponseDto = serde_json::from_slice(&body).unwrap();
+    let reported = auth.user.last_login_at.expect("login response includes last_login_at");
+    assert!(reported >= started);
+
+    let stored = state.user_repo().find_by_email(&tenant, &email).await.unwrap().unwrap();
+    assert_eq!(stored.last_login_at, Some(reported));
+}
+
+#[tokio::test]
+async fn test_login_reports_token_expiry() {
+    let (app, _, email) = app_with_user().await;
+    let expiration_secs = common::setup_test_state().config.jwt.expiration_hours * 3600;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/login")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "email": email, "password": common::TEST_PASSWORD }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let raw: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let auth: AuthResponseDto = serde_json::from_value(raw.clone()).unwrap();
+
+    assert!(
+        (expiration_secs - auth.expires_in).abs() <= 5,
+        "expires_in {} vs configured {}",
+        auth.expires_in,
+        expiration_secs
+    );
+    // expires_at is RFC 3339 and agrees with the token's own exp claim
+    assert!(chrono::DateTime::parse_from_rfc3339(raw["expires_at"].as_str().unwrap()).is_ok());
+    let claims = common::setup_test_state().jwt().verify_token(&auth.token).unwrap();
+    assert_eq!(auth.expires_at.timestamp(), claims.exp);
+}
+
+#[tokio::test]
+async fn test_global_registration_cap_throttles_every_ip() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.trust_proxy = true;
+    config.rate_limit.registrations_per_minute = 2;
+    let app = routes::create_router(backend::AppState::new(config, state.db_pool.clone()));
+
+    let register_from = |ip: &'static str| {
+        let app = app.clone();
+        async move {
+            let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+            let payload = json!({
+                "email": format!("cap{}@example.com", suffix),
+                "username": format!("cap{}", suffix),
+                "password": "SecurePass123!"
+            });
+            app.oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/v1/auth/register")
+                    .header("content-type", "application/json")
+                    .header("x-forwarded-for", ip)
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    assert_eq!(register_from("203.0.113.1").await.status(), StatusCode::CREATED);
+    assert_eq!(register_from("203.0.113.2").await.status(), StatusCode::CREATED);
+
+    // The cap is global: a fresh IP is throttled too
+    let response = register_from("198.51.100.7").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "TOO_MANY_REQUESTS");
+}
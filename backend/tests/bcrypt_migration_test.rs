@@ -0,0 +1,59 @@
+mod common;
+
+use backend::{
+    middleware::context::RequestContext,
+    models::user::{LoginRequest, NewUser},
+    repositories::UserRepositoryTrait,
+    types::TenantId,
+};
+
+#[tokio::test]
+async fn test_imported_bcrypt_hash_logs_in_and_is_rehashed_to_argon2() {
+    let state = common::setup_test_state();
+    let tenant = TenantId::default();
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let email = format!("bcrypt-{}@example.com", suffix);
+
+    // As exported from a legacy system
+    let legacy_hash = bcrypt::hash("SecurePass123!", 4).unwrap();
+    let user = state
+        .user_repo()
+        .create(
+            &tenant,
+            NewUser {
+                email: email.clone(),
+                username: format!("bcrypt{}", suffix),
+                password_hash: legacy_hash.clone(),
+                provider: None,
+                provider_id: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let login = || LoginRequest {
+        email: email.clone(),
+        password: "SecurePass123!".to_string(),
+    };
+    state
+        .auth()
+        .login(&RequestContext::default(), &tenant, login(), None)
+        .await
+        .expect("bcrypt hash should authenticate");
+
+    let stored = state
+        .user_repo()
+        .find_by_id(&tenant, user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.password_hash.starts_with("$argon2id$"), "{}", stored.password_hash);
+
+    // The upgraded hash still verifies, and a wrong password is still rejected
+    state.auth().login(&RequestContext::default(), &tenant, login(), None).await.unwrap();
+    let wrong = LoginRequest {
+        password: "WrongPass123!".to_string(),
+        ..login()
+    };
+    assert!(state.auth().login(&RequestContext::default(), &tenant, wrong, None).await.is_err());
+}
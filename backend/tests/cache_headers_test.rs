@@ -0,0 +1,55 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use backend::routes;
+use tower::ServiceExt;
+
+fn get(uri: &str, if_none_match: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri(uri);
+    if let Some(etag) = if_none_match {
+        builder = builder.header(header::IF_NONE_MATCH, etag);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_version_is_cacheable_and_revalidates_with_etag() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let response = app.clone().oneshot(get("/api/v1/version", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=300");
+    let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+    let response = app
+        .clone()
+        .oneshot(get("/api/v1/version", Some(&etag)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(body.is_empty());
+
+    // A stale ETag gets the full response
+    let response = app
+        .oneshot(get("/api/v1/version", Some("\"stale\"")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_dynamic_endpoints_are_not_stored() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let response = app.oneshot(get("/api/v1/ready", None)).await.unwrap();
+
+    assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+    assert!(response.headers().get(header::ETAG).is_none());
+}
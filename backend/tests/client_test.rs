@@ -0,0 +1,49 @@
+mod common;
+
+use backend::client::{ApiClient, ClientError, LoginRequestDto, RegisterRequestDto};
+
+#[tokio::test]
+async fn test_client_register_login_and_me() {
+    let app = common::spawn_app().await;
+    let client = ApiClient::new(app.address.clone());
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let email = format!("client-{}@example.com", &suffix[..12]);
+
+    let registered = client
+        .register(&RegisterRequestDto {
+            email: email.clone(),
+            username: format!("client{}", &suffix[..12]),
+            password: "SecurePass123!".to_string(),
+        })
+        .await
+        .expect("register should succeed");
+    assert_eq!(registered.user.email, email);
+
+    let me = client.me(&registered.token).await.expect("me should succeed");
+    assert_eq!(me.id, registered.user.id);
+
+    let logged_in = client
+        .login(&LoginRequestDto {
+            email: email.clone(),
+            password: "SecurePass123!".to_string(),
+        })
+        .await
+        .expect("login should succeed");
+    assert_eq!(logged_in.user.id, registered.user.id);
+}
+
+#[tokio::test]
+async fn test_client_surfaces_api_errors() {
+    let app = common::spawn_app().await;
+    let client = ApiClient::new(app.address.clone());
+
+    let err = client.me("not-a-token").await.unwrap_err();
+
+    match err {
+        ClientError::Api { status, error_code, .. } => {
+            assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+            assert_eq!(error_code, "TOKEN_INVALID");
+        }
+        other => panic!("expected API error, got {:?}", other),
+    }
+}
@@ -0,0 +1,118 @@
+//! A private database per test, cloned from a migrated template
+//!
+//! The shared test database needs unique emails and `cleanup_test_data` to
+//! keep tests apart. [`IsolatedDb::create`] instead copies a template
+//! database (`<test db>_template`, migrated once per process) with
+//! `CREATE DATABASE ... TEMPLATE`, which takes milliseconds, and drops the
+//! copy when the handle goes out of scope. Tests using it can run in
+//! parallel and assert on whole-table state.
+//!
+//! # Example
+//! ```ignore
+//! #[tokio::test]
+//! async fn test_counts_users() {
+//!     let db = common::IsolatedDb::create().await;
+//!     let state = db.state();
+//!     // ... the users table starts empty and nobody else writes to it
+//! }
+//! ```
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use backend::{config::Config, db, AppState};
+use diesel::prelude::*;
+use diesel::sql_query;
+
+static TEMPLATE_INIT: OnceLock<String> = OnceLock::new();
+
+/// Clone attempts while another clone still holds the template open
+const CLONE_ATTEMPTS: u32 = 20;
+
+/// A database of its own for one test; dropped with the handle
+#[allow(dead_code)]
+pub struct IsolatedDb {
+    pub name: String,
+    pub url: String,
+    pub pool: db::DbPool,
+}
+
+#[allow(dead_code)]
+impl IsolatedDb {
+    /// Clone the migrated template into a fresh, uniquely named database
+    pub async fn create() -> Self {
+        let test_db_url = super::test_database_url();
+        let template = template_once(&test_db_url).clone();
+        let name = format!("{}_{}", template, &uuid::Uuid::new_v4().simple().to_string()[..12]);
+
+        let maintenance = super::maintenance_url(&test_db_url);
+        let clone_name = name.clone();
+        tokio::task::spawn_blocking(move || clone_template(&maintenance, &template, &clone_name))
+            .await
+            .expect("Template clone task panicked");
+
+        let url = with_db_name(&test_db_url, &name);
+        let pool = db::create_pool(&url, 5).expect("Failed to create isolated database pool");
+        Self { name, url, pool }
+    }
+
+    /// Application state backed by this database
+    pub fn state(&self) -> AppState {
+        let mut config = Config::default_test_config();
+        config.database.url = self.url.clone();
+        AppState::new(config, self.pool.clone())
+    }
+}
+
+impl Drop for IsolatedDb {
+    fn drop(&mut self) {
+        // Pools held elsewhere (e.g. by a state) are cut off by FORCE
+        let maintenance = super::maintenance_url(&super::test_database_url());
+        if let Ok(mut conn) = PgConnection::establish(&maintenance) {
+            let _ = sql_query(format!("DROP DATABASE IF EXISTS \"{}\" WITH (FORCE)", self.name))
+                .execute(&mut conn);
+        }
+    }
+}
+
+/// Create and migrate the template database once per process
+fn template_once(test_db_url: &str) -> &'static String {
+    TEMPLATE_INIT.get_or_init(|| {
+        let template = format!("{}_template", super::extract_db_name(test_db_url));
+        let template_url = with_db_name(test_db_url, &template);
+        super::ensure_test_database_exists(&template_url);
+        // The connection closes when this returns; a template can't be
+        // cloned while anyone is connected to it
+        super::run_test_migrations(&template_url);
+        template
+    })
+}
+
+fn clone_template(maintenance_url: &str, template: &str, name: &str) {
+    let mut conn = PgConnection::establish(maintenance_url)
+        .unwrap_or_else(|e| panic!("Failed to connect to maintenance DB: {}", e));
+
+    // Concurrent clones of the same template briefly see each other as users of it
+    for attempt in 1..=CLONE_ATTEMPTS {
+        match sql_query(format!("CREATE DATABASE \"{}\" TEMPLATE \"{}\"", name, template))
+            .execute(&mut conn)
+        {
+            Ok(_) => return,
+            Err(e)
+                if attempt < CLONE_ATTEMPTS
+                    && e.to_string().contains("being accessed by other users") =>
+            {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => panic!("Failed to clone template database {}: {}", template, e),
+        }
+    }
+}
+
+/// `url` pointing at database `name` instead
+fn with_db_name(url: &str, name: &str) -> String {
+    let (prefix, rest) = url.rsplit_once('/').expect("database URL has a path");
+    match rest.split_once('?') {
+        Some((_, query)) => format!("{}/{}?{}", prefix, name, query),
+        None => format!("{}/{}", prefix, name),
+    }
+}
@@ -1,5 +1,8 @@
 pub mod builder;
+pub mod isolated_db;
+pub mod logs;
 pub mod macros;
+pub mod server;
 pub mod test_db;
 pub mod test_helpers;
 
@@ -11,6 +14,8 @@ use backend::{config::Config, db, AppState};
 #[allow(unused_imports)]
 pub use builder::TestStateBuilder;
 #[allow(unused_imports)]
+pub use logs::CapturedLogs;
+#[allow(unused_imports)]
 pub use macros::*;
 
 /// Hardcoded test database URL.
@@ -209,6 +214,9 @@ pub async fn cleanup_test_data(pool: &db::DbPool) {
         "logintest@example.com",
         "wrongpass@example.com",
         "currentuser@example.com",
+        "rehash@example.com",
+        "norehash@example.com",
+        "pepper@example.com",
     ];
 
     let mut conn = pool.get().await.expect("Failed to get connection for cleanup");
@@ -221,7 +229,11 @@ pub async fn cleanup_test_data(pool: &db::DbPool) {
     }
 }
 
+#[allow(unused_imports)]
+pub use isolated_db::IsolatedDb;
 #[allow(unused_imports)]
 pub use test_db::{TestDb, create_mock_state};
 #[allow(unused_imports)]
-pub use test_helpers::{TestClient, TestResponse, create_test_jwt};
+pub use test_helpers::{TestClient, TestResponse, create_test_jwt, register_user, TEST_PASSWORD};
+#[allow(unused_imports)]
+pub use server::{spawn_app, spawn_app_with, TestApp};
@@ -0,0 +1,66 @@
+use std::net::SocketAddr;
+
+use backend::{routes, AppState};
+use tokio::{net::TcpListener, sync::oneshot, task::JoinHandle};
+
+/// The application served over a real socket on a random local port
+///
+/// Unlike `oneshot` against the router, requests go through the TCP listener
+/// and `axum::serve`, so `ConnectInfo` is populated (IP-based rate limiting
+/// sees the client address) and graceful shutdown can be exercised.
+#[allow(dead_code)]
+pub struct TestApp {
+    /// Base URL, e.g. `http://127.0.0.1:49152`
+    pub address: String,
+    pub client: reqwest::Client,
+    /// Send to start a graceful shutdown; see [`TestApp::stop`]
+    pub shutdown: oneshot::Sender<()>,
+    server: JoinHandle<()>,
+}
+
+#[allow(dead_code)]
+impl TestApp {
+    /// Absolute URL for `path`
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.address, path)
+    }
+
+    /// Shut the server down gracefully and wait until it has stopped
+    pub async fn stop(self) {
+        let _ = self.shutdown.send(());
+        self.server.await.expect("test server panicked");
+    }
+}
+
+/// Serve the default test state; see [`spawn_app_with`]
+#[allow(dead_code)]
+pub async fn spawn_app() -> TestApp {
+    spawn_app_with(super::setup_test_state()).await
+}
+
+/// Serve `state` on 127.0.0.1 with an OS-assigned port
+#[allow(dead_code)]
+pub async fn spawn_app_with(state: AppState) -> TestApp {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind test server");
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    let app = routes::create_router(state);
+    let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+            .expect("Test server failed");
+    });
+
+    TestApp {
+        address,
+        client: reqwest::Client::new(),
+        shutdown,
+        server,
+    }
+}
@@ -101,7 +101,7 @@ where
     let pool = db::create_pool(&database_url, 1)
         .expect("Failed to create test pool");
 
-    let mut conn = pool.get()
+    let mut conn = db::get_connection(&pool)
         .await
         .expect("Failed to get connection from test pool");
 
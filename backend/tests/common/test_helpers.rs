@@ -232,6 +232,11 @@ pub fn fake_user() -> User {
         password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test$hash".to_string(),
         created_at: Utc::now().naive_utc(),
         updated_at: Utc::now().naive_utc(),
+        role: "user".to_string(),
+        tenant_id: "default".to_string(),
+        provider: None,
+        provider_id: None,
+        last_login_at: None,
     }
 }
 
@@ -264,3 +269,37 @@ pub fn fake_user_with_username(username: &str) -> User {
         ..fake_user()
     }
 }
+
+/// Password of every user created by [`register_user`]
+#[allow(dead_code)]
+pub const TEST_PASSWORD: &str = "SecurePass123!";
+
+/// Register a user with a unique email and username through the API
+///
+/// `tenant` is sent as `X-Tenant-ID`; `None` registers in the default tenant.
+#[allow(dead_code)]
+pub async fn register_user(app: &axum::Router, tenant: Option<&str>) -> backend::models::dto::AuthResponseDto {
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let payload = serde_json::json!({
+        "email": format!("user-{}@example.com", suffix),
+        "username": format!("user{}", suffix),
+        "password": TEST_PASSWORD,
+    });
+
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/auth/register")
+        .header("content-type", "application/json");
+    if let Some(tenant) = tenant {
+        request = request.header("x-tenant-id", tenant);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::from(payload.to_string())).unwrap())
+        .await
+        .expect("Failed to execute request");
+    let response = TestResponse::new(response).await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use backend::{db, middleware::connection_leases::ConnectionLeases};
+
+#[tokio::test]
+async fn test_nested_connections_in_one_request_are_flagged() {
+    let state = common::setup_test_state();
+    let pool = db::create_pool(&state.config.database.url, 2).unwrap();
+    let leases = ConnectionLeases::new(1);
+
+    leases
+        .clone()
+        .scope(async {
+            let _outer = db::get_connection(&pool).await.unwrap();
+            assert_eq!(leases.exceeded(), 0);
+            let _inner = db::get_connection(&pool).await.unwrap();
+            assert_eq!(leases.held(), 2);
+        })
+        .await;
+
+    assert_eq!(leases.exceeded(), 1);
+    assert_eq!(leases.held(), 0);
+}
+
+#[tokio::test]
+async fn test_waiting_for_a_second_connection_on_an_exhausted_pool_is_flagged() {
+    let state = common::setup_test_state();
+    let pool = db::create_pool(&state.config.database.url, 1).unwrap();
+    let leases = ConnectionLeases::new(1);
+
+    leases
+        .clone()
+        .scope(async {
+            let _outer = db::get_connection(&pool).await.unwrap();
+            // The only connection is ours, so this wait never succeeds
+            let inner = tokio::time::timeout(
+                std::time::Duration::from_millis(200),
+                db::get_connection(&pool),
+            )
+            .await;
+            assert!(inner.is_err());
+            assert_eq!(leases.exceeded(), 1);
+        })
+        .await;
+
+    assert_eq!(leases.held(), 0);
+}
+
+#[tokio::test]
+async fn test_sequential_connections_are_not_flagged() {
+    let state = common::setup_test_state();
+    let leases = ConnectionLeases::new(1);
+
+    leases
+        .clone()
+        .scope(async {
+            for _ in 0..3 {
+                db::get_connection(&state.db_pool).await.unwrap();
+            }
+        })
+        .await;
+
+    assert_eq!(leases.exceeded(), 0);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use backend::db;
+
+/// Current value of `db_connection_acquire_seconds_count{outcome="ok"}`, 0 if unset
+async fn acquire_count() -> u64 {
+    let prefix = "db_connection_acquire_seconds_count{outcome=\"ok\"} ";
+    backend::metrics::metrics_handler()
+        .await
+        .lines()
+        .find_map(|line| line.strip_prefix(prefix)?.parse().ok())
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+async fn test_connection_acquisition_is_recorded_in_histogram() {
+    backend::metrics::init_metrics().unwrap();
+    let state = common::setup_test_state();
+    let before = acquire_count().await;
+
+    let conn = db::get_connection(&state.db_pool).await.unwrap();
+    drop(conn);
+
+    assert!(acquire_count().await > before);
+    let rendered = backend::metrics::metrics_handler().await;
+    assert!(
+        rendered.contains("db_connection_acquire_seconds_bucket{outcome=\"ok\",le=\"0.005\"}"),
+        "{}",
+        rendered
+    );
+}
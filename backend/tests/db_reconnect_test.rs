@@ -0,0 +1,97 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use axum::{http::header, response::IntoResponse};
+use backend::{
+    db::{self, DbPool},
+    error::AppError,
+    retry_read,
+};
+use diesel::{sql_types::Integer, QueryableByName};
+use diesel_async::RunQueryDsl;
+
+#[derive(QueryableByName)]
+struct Pid {
+    #[diesel(sql_type = Integer)]
+    pid: i32,
+}
+
+async fn backend_pid(conn: &mut db::DbConnection) -> Result<i32, diesel::result::Error> {
+    diesel::sql_query("SELECT pg_backend_pid() AS pid")
+        .get_result::<Pid>(conn)
+        .await
+        .map(|row| row.pid)
+}
+
+/// Kill a backend from another connection, as a database restart would
+async fn terminate(admin: &DbPool, pid: i32) {
+    let mut conn = db::get_connection(admin).await.unwrap();
+    diesel::sql_query(format!("SELECT pg_terminate_backend({})", pid))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+}
+
+/// Read the backend pid, killing the connection before the first `kills`
+/// attempts get to query
+async fn read_pid(pool: &DbPool, admin: &DbPool, kills: usize, attempts: &AtomicUsize) -> Result<i32, AppError> {
+    retry_read!(pool, |conn| {
+        if attempts.fetch_add(1, Ordering::SeqCst) < kills {
+            terminate(admin, backend_pid(&mut conn).await.unwrap()).await;
+        }
+        backend_pid(&mut conn).await
+    })
+    .map_err(|e| AppError::database("Failed to read backend pid", e))
+}
+
+fn pools() -> (DbPool, DbPool) {
+    let state = common::setup_test_state();
+    let url = &state.config.database.url;
+    (db::create_pool(url, 1).unwrap(), db::create_pool(url, 1).unwrap())
+}
+
+#[tokio::test]
+async fn test_read_retries_once_on_dropped_connection() {
+    let (pool, admin) = pools();
+    let attempts = AtomicUsize::new(0);
+
+    let pid = read_pid(&pool, &admin, 1, &attempts).await.unwrap();
+
+    assert_eq!(attempts.into_inner(), 2);
+    // The retry ran on a replacement connection
+    let mut conn = db::get_connection(&pool).await.unwrap();
+    assert_eq!(backend_pid(&mut conn).await.unwrap(), pid);
+}
+
+#[tokio::test]
+async fn test_repeated_drops_become_503_with_retry_after() {
+    let (pool, admin) = pools();
+    let attempts = AtomicUsize::new(0);
+
+    let err = read_pid(&pool, &admin, 2, &attempts).await.unwrap_err();
+
+    assert_eq!(attempts.into_inner(), 2);
+    assert!(matches!(err, AppError::DatabaseUnavailable { .. }), "{:?}", err);
+    let response = err.into_response();
+    assert_eq!(response.status(), 503);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+    // The pool recovers on its own once the database is back
+    let mut conn = db::get_connection(&pool).await.unwrap();
+    assert!(backend_pid(&mut conn).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_idle_connection_dropped_by_server_is_not_reused() {
+    let (pool, admin) = pools();
+    let mut conn = db::get_connection(&pool).await.unwrap();
+    let killed = backend_pid(&mut conn).await.unwrap();
+    drop(conn);
+
+    terminate(&admin, killed).await;
+
+    let mut conn = db::get_connection(&pool).await.unwrap();
+    let pid = backend_pid(&mut conn).await.expect("pool should hand out a live connection");
+    assert_ne!(pid, killed);
+}
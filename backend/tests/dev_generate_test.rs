@@ -0,0 +1,47 @@
+//! POST /dev/generate, built with `--features dev-tools` in debug builds
+#![cfg(debug_assertions)]
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::routes;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_generate_reports_inserted_users() {
+    use backend::repositories::{UserRepository, UserRepositoryTrait};
+    use backend::types::TenantId;
+
+    let state = common::setup_test_state();
+    let app = routes::create_router(state.clone());
+    let tenant = format!("gen-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+
+    let generate = |users: usize| {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/dev/generate?users={}", users))
+            .header("x-tenant-id", tenant.clone())
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(generate(5)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["requested"], 5);
+    assert_eq!(json["created"], 5);
+    assert!(json["timing_ms"]["total"].is_u64());
+
+    let repo = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::parse(&tenant).unwrap();
+    assert_eq!(repo.count(&tenant).await.unwrap(), 5);
+
+    let response = app.oneshot(generate(1_000_000)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::{
+    middleware::context::RequestContext,
+    models::user::{LoginRequest, NewUser},
+    repositories::UserRepositoryTrait,
+    routes,
+    services::events::DomainEvent,
+    types::TenantId,
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_registration_publishes_user_registered() {
+    let state = common::setup_test_state();
+    let mut events = state.events().subscribe();
+    let app = routes::create_router(state);
+
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let email = format!("events-{}@example.com", suffix);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "email": email,
+                        "username": format!("events{}", suffix),
+                        "password": "SecurePass123!"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    match events.try_recv().expect("an event was published") {
+        DomainEvent::UserRegistered {
+            tenant,
+            email: event_email,
+            ..
+        } => {
+            assert_eq!(tenant, TenantId::default());
+            assert_eq!(event_email, email);
+        }
+        other => panic!("expected UserRegistered, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_password_rehash_on_login_publishes_password_changed() {
+    use argon2::{password_hash::SaltString, Algorithm, Argon2, Params, PasswordHasher, Version};
+
+    let state = common::setup_test_state();
+    let ctx = RequestContext::default();
+    let tenant = TenantId::default();
+
+    // A hash weaker than the configured policy, so login upgrades it
+    let salt = SaltString::generate(&mut password_hash::rand_core::OsRng);
+    let weak_hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::new(8, 1, 1, None).unwrap())
+        .hash_password(b"SecurePass123!", &salt)
+        .unwrap()
+        .to_string();
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+    let email = format!("events-pw-{}@example.com", suffix);
+    let user_id = state
+        .user_repo()
+        .create(
+            &tenant,
+            NewUser {
+                email: email.clone(),
+                username: format!("eventspw{}", suffix),
+                password_hash: weak_hash,
+                provider: None,
+                provider_id: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id;
+    let mut events = state.events().subscribe();
+
+    state
+        .auth()
+        .login(
+            &ctx,
+            &tenant,
+            LoginRequest {
+                email,
+                password: "SecurePass123!".to_string(),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        events.try_recv(),
+        Ok(DomainEvent::PasswordChanged { user_id: id, .. }) if id == user_id
+    ));
+    assert!(matches!(events.try_recv(), Ok(DomainEvent::UserLoggedIn { .. })));
+}
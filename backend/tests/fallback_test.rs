@@ -0,0 +1,157 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use backend::routes;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_unknown_route_returns_json_404() {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/does-not-exist")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["error_code"], "NOT_FOUND");
+    assert_eq!(json["error"], "Route not found");
+    assert!(json["error_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_wrong_method_returns_json_405_with_allow() {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/v1/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    let allow = response
+        .headers()
+        .get(header::ALLOW)
+        .and_then(|v| v.to_str().ok())
+        .expect("405 response should carry an Allow header")
+        .to_string();
+    assert!(allow.contains("GET"), "unexpected Allow header: {}", allow);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["error_code"], "METHOD_NOT_ALLOWED");
+}
+
+#[tokio::test]
+async fn test_oversized_body_returns_json_413() {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+
+    let oversized = format!(
+        "{{\"email\":\"{}\",\"username\":\"big\",\"password\":\"SecurePass123!\"}}",
+        "a".repeat(routes::DEFAULT_BODY_LIMIT + 1)
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/register")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(oversized))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["error_code"], "PAYLOAD_TOO_LARGE");
+    assert!(json["error_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_over_long_query_returns_json_414() {
+    let state = common::setup_test_state();
+    let max_length = state.config.server.max_uri_length;
+    let app = routes::create_router(state);
+    let get = |uri: String| {
+        app.clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+    };
+
+    let response = get(format!("/api/v1/version?q={}", "a".repeat(max_length)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::URI_TOO_LONG);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "URI_TOO_LONG");
+
+    let response = get("/api/v1/version?q=rust".to_string()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_non_json_body_returns_json_415() {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+    let login = |content_type: &'static str| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/auth/login")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(
+                    r#"{"email":"nobody@example.com","password":"SecurePass123!"}"#,
+                ))
+                .unwrap(),
+        )
+    };
+
+    let response = login("text/plain").await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "UNSUPPORTED_MEDIA_TYPE");
+    assert!(json["error_id"].is_string());
+
+    // Reaches the handler, which rejects the unknown account
+    let response = login("application/json; charset=utf-8").await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
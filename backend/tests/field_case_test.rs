@@ -0,0 +1,75 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::{config::FieldCase, routes, AppState};
+use serde_json::json;
+use tower::ServiceExt;
+
+fn camel_case_app() -> axum::Router {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.field_case = FieldCase::Camel;
+    routes::create_router(AppState::new(config, state.db_pool.clone()))
+}
+
+async fn post_json(app: &axum::Router, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_camel_case_responses() {
+    let app = camel_case_app();
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..12];
+
+    let (status, json) = post_json(
+        &app,
+        "/api/v1/auth/register",
+        json!({
+            "email": format!("camel-{}@example.com", suffix),
+            "username": format!("camel{}", suffix),
+            "password": "SecurePass123!"
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(json["expiresAt"].is_string(), "{}", json);
+    assert!(json["expiresIn"].is_number());
+    assert!(json["user"]["createdAt"].is_string());
+    assert!(json.get("expires_at").is_none());
+}
+
+#[tokio::test]
+async fn test_camel_case_error_bodies() {
+    let app = camel_case_app();
+
+    let (status, json) = post_json(
+        &app,
+        "/api/v1/auth/login",
+        json!({ "email": "nobody@example.com", "password": "WrongPass123!" }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(json["errorCode"].is_string(), "{}", json);
+    assert!(json.get("error_code").is_none());
+}
@@ -16,6 +16,11 @@ pub fn create_fake_user() -> User {
         password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test$test".to_string(),
         created_at: Utc::now().naive_utc(),
         updated_at: Utc::now().naive_utc(),
+        role: "user".to_string(),
+        tenant_id: "default".to_string(),
+        provider: None,
+        provider_id: None,
+        last_login_at: None,
     }
 }
 
@@ -25,6 +30,8 @@ pub fn create_new_user(email: &str, username: &str, password_hash: &str) -> NewU
         email: email.to_string(),
         username: username.to_string(),
         password_hash: password_hash.to_string(),
+        provider: None,
+        provider_id: None,
     }
 }
 
@@ -37,6 +44,11 @@ pub fn create_test_user() -> User {
         password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test$test".to_string(),
         created_at: Utc::now().naive_utc(),
         updated_at: Utc::now().naive_utc(),
+        role: "user".to_string(),
+        tenant_id: "default".to_string(),
+        provider: None,
+        provider_id: None,
+        last_login_at: None,
     }
 }
 
@@ -50,6 +62,11 @@ pub fn create_admin_user() -> User {
         password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test$test".to_string(),
         created_at: Utc::now().naive_utc(),
         updated_at: Utc::now().naive_utc(),
+        role: "admin".to_string(),
+        tenant_id: "default".to_string(),
+        provider: None,
+        provider_id: None,
+        last_login_at: None,
     }
 }
 
@@ -63,6 +80,11 @@ pub fn create_test_users(count: usize) -> Vec<User> {
             password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test$test".to_string(),
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            role: "user".to_string(),
+            tenant_id: "default".to_string(),
+            provider: None,
+            provider_id: None,
+            last_login_at: None,
         })
         .collect()
 }
@@ -109,6 +131,11 @@ impl UserFixtureBuilder {
                 .unwrap_or_else(|| "$argon2id$v=19$m=19456,t=2,p=1$test$test".to_string()),
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
+            role: "user".to_string(),
+            tenant_id: "default".to_string(),
+            provider: None,
+            provider_id: None,
+            last_login_at: None,
         }
     }
 }
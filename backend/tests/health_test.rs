@@ -24,3 +24,154 @@ async fn test_health_check() {
 
     assert_eq!(response.status(), StatusCode::OK);
 }
+
+async fn liveness(accept: &str) -> (StatusCode, String, Vec<u8>) {
+    let state = common::setup_test_state();
+    let app = routes::create_router(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health/live")
+                .header("accept", accept)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let content_type = response.headers()["content-type"].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_liveness_answers_plain_text_probes_with_ok() {
+    let (status, content_type, body) = liveness("text/plain").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/plain"), "{}", content_type);
+    assert_eq!(body, b"OK");
+}
+
+#[tokio::test]
+async fn test_liveness_answers_json_clients_with_structured_body() {
+    let (status, content_type, body) = liveness("application/json").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["status"], "alive");
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn test_health_check_times_out_on_stalled_database() {
+    // A TCP server that accepts connections but never speaks the Postgres protocol
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let mut config = backend::config::Config::default_test_config();
+    config.database.url = format!("postgres://postgres:postgres@{}/stalled", addr);
+    config.health.db_timeout_ms = 200;
+    let db_pool = backend::db::create_pool(&config.database.url, 1).unwrap();
+    let app = routes::create_router(backend::AppState::new(config, db_pool));
+
+    let start = std::time::Instant::now();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["checks"]["database"]["status"], "unhealthy");
+    assert!(json["checks"]["database"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("timed out"));
+}
+
+#[tokio::test]
+async fn test_readiness_waits_for_migrations() {
+    let state = common::setup_test_state();
+    let ready = || {
+        routes::create_router(state.clone()).oneshot(
+            Request::builder()
+                .uri("/api/v1/ready")
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let response = ready().await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    state.readiness.mark_migrations_done();
+
+    let response = ready().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_probe_connection_executes_the_probe_query() {
+    let state = common::setup_test_state();
+
+    assert!(backend::db::probe_connection(&state.db_pool, "SELECT 1").await.is_ok());
+    // Only fails if the query actually runs on the checked-out connection
+    assert!(backend::db::probe_connection(&state.db_pool, "SELECT 1/0").await.is_err());
+}
+
+#[tokio::test]
+async fn test_failing_probe_query_fails_health_and_readiness() {
+    let state = common::setup_test_state();
+    state.readiness.mark_migrations_done();
+    let mut config = (*state.config).clone();
+    config.database.probe_query = "SELECT 1/0".to_string();
+    let broken = backend::AppState::new(config, state.db_pool.clone());
+    broken.readiness.mark_migrations_done();
+    let get = |app: axum::Router, uri: &'static str| {
+        app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+    };
+
+    let response = get(routes::create_router(state), "/api/v1/ready").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = get(routes::create_router(broken.clone()), "/api/v1/health")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["checks"]["database"]["status"], "unhealthy");
+
+    let response = get(routes::create_router(broken), "/api/v1/ready").await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["migrations"], true);
+    assert_eq!(json["database"], false);
+}
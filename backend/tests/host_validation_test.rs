@@ -0,0 +1,74 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use backend::{routes, AppState};
+use tower::ServiceExt;
+
+fn state_with_allowed_hosts(hosts: &[&str]) -> AppState {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.allowed_hosts = hosts.iter().map(|h| h.to_string()).collect();
+    AppState::new(config, state.db_pool.clone())
+}
+
+#[tokio::test]
+async fn test_allowed_host_passes() {
+    let app = routes::create_router(state_with_allowed_hosts(&["api.example.com"]));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health")
+                .header(header::HOST, "api.example.com:2999")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_spoofed_host_rejected() {
+    let app = routes::create_router(state_with_allowed_hosts(&["api.example.com"]));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health")
+                .header(header::HOST, "evil.example.net")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error_code"], "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn test_missing_host_rejected_without_wildcard() {
+    let app = routes::create_router(state_with_allowed_hosts(&["api.example.com"]));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,97 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::any, Router};
+use backend::{config::HttpClientConfig, services::http::HttpClient};
+
+/// Serve a mock upstream answering 503 for the first `failures` requests,
+/// then 200; returns its URL and the request counter
+async fn flaky_upstream(
+    failures: usize,
+    retry_after: Option<&'static str>,
+) -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/",
+            any(move |State(hits): State<Arc<AtomicUsize>>| async move {
+                if hits.fetch_add(1, Ordering::SeqCst) < failures {
+                    let mut response = StatusCode::SERVICE_UNAVAILABLE.into_response();
+                    if let Some(retry_after) = retry_after {
+                        response
+                            .headers_mut()
+                            .insert("retry-after", retry_after.parse().unwrap());
+                    }
+                    response
+                } else {
+                    "ok".into_response()
+                }
+            }),
+        )
+        .with_state(hits.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (format!("http://{}/", addr), hits)
+}
+
+fn fast_policy() -> HttpClientConfig {
+    HttpClientConfig {
+        base_backoff_ms: 10,
+        max_backoff_ms: 100,
+        ..HttpClientConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_get_succeeds_after_transient_503s() {
+    let (url, hits) = flaky_upstream(2, None).await;
+    let client = HttpClient::new(fast_policy());
+
+    let response = client.send(client.get(&url)).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_post_is_not_retried() {
+    let (url, hits) = flaky_upstream(2, None).await;
+    let client = HttpClient::new(fast_policy());
+
+    let response = client.send(client.post(&url).body("{}")).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let (url, hits) = flaky_upstream(5, None).await;
+    let client = HttpClient::new(HttpClientConfig {
+        max_attempts: 2,
+        ..fast_policy()
+    });
+
+    let response = client.send(client.get(&url)).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_max_backoff_is_not_waited_for() {
+    let (url, hits) = flaky_upstream(1, Some("120")).await;
+    let client = HttpClient::new(fast_policy());
+
+    let response = client.send(client.get(&url)).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use backend::{models::user::NewUser, repositories::UserRepositoryTrait, types::TenantId};
+
+async fn register_same_user(db: &common::IsolatedDb) -> usize {
+    let state = db.state();
+    let tenant = TenantId::default();
+    state
+        .user_repo()
+        .create(
+            &tenant,
+            NewUser {
+                email: "isolated@example.com".to_string(),
+                username: "isolated".to_string(),
+                password_hash: "not-a-real-hash".to_string(),
+                provider: None,
+                provider_id: None,
+            },
+        )
+        .await
+        .expect("the email is only taken in the other database");
+
+    state.user_repo().list(&tenant, 100, 0).await.unwrap().len()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_isolated_databases_do_not_share_rows() {
+    let (first, second) = tokio::join!(common::IsolatedDb::create(), common::IsolatedDb::create());
+    assert_ne!(first.name, second.name);
+
+    // The same unique email in both, at the same time, and each table holds
+    // only its own row
+    let (first_count, second_count) =
+        tokio::join!(register_same_user(&first), register_same_user(&second));
+    assert_eq!((first_count, second_count), (1, 1));
+}
+
+#[tokio::test]
+async fn test_isolated_database_is_dropped_with_its_handle() {
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+
+    let db = common::IsolatedDb::create().await;
+    let name = db.name.clone();
+    let state = common::setup_test_state();
+    drop(db);
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        count: i64,
+    }
+    let mut conn = backend::db::get_connection(&state.db_pool).await.unwrap();
+    let remaining: Count = diesel::sql_query(format!(
+        "SELECT count(*) AS count FROM pg_database WHERE datname = '{}'",
+        name
+    ))
+    .get_result(&mut conn)
+    .await
+    .unwrap();
+    assert_eq!(remaining.count, 0);
+}
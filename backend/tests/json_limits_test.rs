@@ -0,0 +1,43 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::routes;
+use tower::ServiceExt;
+
+fn login_request(body: String) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/api/v1/auth/login")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_over_deep_body_rejected() {
+    let app = routes::create_router(common::setup_test_state());
+
+    // Well under the byte limit, far over JSON_MAX_DEPTH
+    let body = format!(
+        r#"{{"email": "a@example.com", "password": "x", "extra": {}1{}}}"#,
+        "[".repeat(1000),
+        "]".repeat(1000)
+    );
+
+    let response = app.oneshot(login_request(body)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_normal_body_passes_limits() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let body = r#"{"email": "nobody@example.com", "password": "SecurePass123!"}"#;
+
+    // Reaches the handler, which rejects the unknown user
+    let response = app.oneshot(login_request(body.to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use axum::{
+    body::Body,
+    extract::Form,
+    http::{header, HeaderMap, Request, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use backend::{
+    config::OAuthProviderConfig,
+    models::dto::AuthResponseDto,
+    repositories::UserRepositoryTrait,
+    routes,
+    types::TenantId,
+    AppState,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use tower::ServiceExt;
+
+/// Serve fake token and userinfo endpoints for a single Google account
+async fn spawn_mock_provider(sub: String, email: String) -> String {
+    let provider = Router::new()
+        .route(
+            "/token",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(form["grant_type"], "authorization_code");
+                assert_eq!(form["code"], "mock-code");
+                assert_eq!(form["client_secret"], "client-secret");
+                Json(json!({ "access_token": "mock-access-token", "token_type": "Bearer" }))
+            }),
+        )
+        .route(
+            "/userinfo",
+            get(move |headers: HeaderMap| async move {
+                let auth = headers.get(header::AUTHORIZATION).unwrap().to_str().unwrap();
+                assert_eq!(auth, "Bearer mock-access-token");
+                Json(json!({ "sub": sub, "email": email, "email_verified": true }))
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, provider).await.unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+fn app_with_provider(state: &AppState, provider_url: &str) -> Router {
+    let mut config = (*state.config).clone();
+    config.oauth.google = Some(OAuthProviderConfig {
+        auth_url: format!("{}/auth", provider_url),
+        token_url: format!("{}/token", provider_url),
+        userinfo_url: format!("{}/userinfo", provider_url),
+        ..OAuthProviderConfig::google(
+            "client-id".to_string(),
+            "client-secret".to_string(),
+            "http://localhost/api/v1/auth/oauth/google/callback".to_string(),
+        )
+    });
+    routes::create_router(AppState::new(config, state.db_pool.clone()))
+}
+
+#[tokio::test]
+async fn test_google_sign_in_provisions_user_and_issues_token() {
+    let state = common::setup_test_state();
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let email = format!("oauth-{}@example.com", &suffix[..12]);
+    let sub = format!("10{}", &suffix[..18]);
+    let app = app_with_provider(&state, &spawn_mock_provider(sub.clone(), email.clone()).await);
+
+    // Start: redirect to the provider with a state remembered in a cookie
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/api/v1/auth/oauth/google").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let location = response.headers()[header::LOCATION].to_str().unwrap().to_string();
+    let oauth_state = reqwest::Url::parse(&location)
+        .unwrap()
+        .query_pairs()
+        .find(|(key, _)| key == "state")
+        .map(|(_, value)| value.into_owned())
+        .expect("redirect should carry a state");
+    let cookie = response.headers()[header::SET_COOKIE]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+
+    // Callback: code exchanged, user provisioned, our own JWT returned
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/api/v1/auth/oauth/google/callback?code=mock-code&state={}",
+                    oauth_state
+                ))
+                .header(header::COOKIE, cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let auth: AuthResponseDto = serde_json::from_slice(&body).unwrap();
+    assert_eq!(auth.user.email, email);
+    assert!(state.jwt().verify_token(&auth.token).is_ok());
+
+    let user = state
+        .user_repo()
+        .find_by_email(&TenantId::default(), &email)
+        .await
+        .unwrap()
+        .expect("user should be provisioned");
+    assert_eq!(user.provider.as_deref(), Some("google"));
+    assert_eq!(user.provider_id.as_deref(), Some(sub.as_str()));
+}
+
+#[tokio::test]
+async fn test_google_callback_rejects_mismatched_state() {
+    let state = common::setup_test_state();
+    let app = app_with_provider(&state, "http://127.0.0.1:9");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/oauth/google/callback?code=mock-code&state=forged")
+                .header(header::COOKIE, "oauth_state=expected.default")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use axum::{body::Body, http::Request};
+use backend::{routes, AppState};
+use tower::ServiceExt;
+
+async fn served_spec(state: AppState) -> serde_json::Value {
+    let response = routes::create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api-docs/openapi.json")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_spec_server_matches_configured_address() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.host = "127.0.0.1".to_string();
+    config.server.port = 9123;
+
+    let spec = served_spec(AppState::new(config, state.db_pool.clone())).await;
+
+    assert_eq!(spec["servers"][0]["url"], "http://127.0.0.1:9123");
+}
+
+#[tokio::test]
+async fn test_spec_server_and_info_overrides() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.docs.server_url = Some("https://api.example.com".to_string());
+    config.docs.title = Some("Example API".to_string());
+    config.docs.version = Some("2.0.0".to_string());
+
+    let spec = served_spec(AppState::new(config, state.db_pool.clone())).await;
+
+    assert_eq!(spec["servers"][0]["url"], "https://api.example.com");
+    assert_eq!(spec["info"]["title"], "Example API");
+    assert_eq!(spec["info"]["version"], "2.0.0");
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use backend::{
+    jobs::tasks::{self, PrunedEntries},
+    middleware::rate_limit::RateLimiter,
+    services::nonce::NonceStore,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_prune_removes_expired_entries_and_keeps_live_ones() {
+    let mut state = common::setup_test_state();
+    let window = Duration::from_millis(50);
+    state.services.nonces = NonceStore::new(window);
+    state.services.auth_rate_limiter = RateLimiter::new(5, window, false);
+    let state = Arc::new(state);
+    let limiter = &state.services.auth_rate_limiter;
+
+    // Entries from before the pause expire; the key seen after it stays live
+    state.services.nonces.issue("default:alice");
+    state.services.nonces.issue("default:bob");
+    assert!(limiter.check("10.0.0.1").await);
+    assert!(limiter.check("10.0.0.2").await);
+    tokio::time::sleep(window * 2).await;
+    assert!(limiter.check("10.0.0.3").await);
+
+    let pruned = tasks::prune_expired_entries(state.clone()).await.unwrap();
+    assert_eq!(
+        pruned,
+        PrunedEntries {
+            rate_limit_keys: 2,
+            nonces: 2,
+        }
+    );
+    assert_eq!(limiter.tracked_keys().await, 1);
+    assert!(state.services.nonces.is_empty());
+
+    // Nothing left to prune
+    let pruned = tasks::prune_expired_entries(state.clone()).await.unwrap();
+    assert_eq!(pruned, PrunedEntries::default());
+    assert_eq!(limiter.tracked_keys().await, 1);
+}
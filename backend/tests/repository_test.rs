@@ -4,7 +4,11 @@
 mod common;
 mod fixtures;
 
-use backend::repositories::{UserRepository, UserRepositoryTrait};
+use backend::{
+    models::{timestamps::Timestamped, user::UserChangeset},
+    repositories::{UserRepository, UserRepositoryTrait},
+    types::TenantId,
+};
 use fixtures::*;
 use uuid::Uuid;
 
@@ -12,6 +16,7 @@ use uuid::Uuid;
 async fn test_create_user() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     let unique_id = Uuid::new_v4();
     let email = format!("repo_test_{}@example.com", unique_id);
@@ -23,7 +28,7 @@ async fn test_create_user() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$test",
     );
 
-    let result = repository.create(new_user).await;
+    let result = repository.create(&tenant, new_user).await;
     assert!(result.is_ok());
 
     let user = result.unwrap();
@@ -35,6 +40,7 @@ async fn test_create_user() {
 async fn test_find_user_by_id() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create user first
     let unique_id = Uuid::new_v4();
@@ -47,10 +53,10 @@ async fn test_find_user_by_id() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$test",
     );
 
-    let created_user = repository.create(new_user).await.unwrap();
+    let created_user = repository.create(&tenant, new_user).await.unwrap();
 
     // Find by ID
-    let result = repository.find_by_id(created_user.id).await;
+    let result = repository.find_by_id(&tenant, created_user.id).await;
     assert!(result.is_ok());
 
     let found_user = result.unwrap();
@@ -62,6 +68,7 @@ async fn test_find_user_by_id() {
 async fn test_find_user_by_email() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create user
     let unique_id = Uuid::new_v4();
@@ -74,10 +81,10 @@ async fn test_find_user_by_email() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$test",
     );
 
-    repository.create(new_user).await.unwrap();
+    repository.create(&tenant, new_user).await.unwrap();
 
     // Find by email
-    let result = repository.find_by_email(&email).await;
+    let result = repository.find_by_email(&tenant, &email).await;
     assert!(result.is_ok());
 
     let found_user = result.unwrap();
@@ -89,6 +96,7 @@ async fn test_find_user_by_email() {
 async fn test_find_user_by_username() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create user
     let unique_id = Uuid::new_v4();
@@ -101,10 +109,10 @@ async fn test_find_user_by_username() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$test",
     );
 
-    repository.create(new_user).await.unwrap();
+    repository.create(&tenant, new_user).await.unwrap();
 
     // Find by username
-    let result = repository.find_by_username(&username).await;
+    let result = repository.find_by_username(&tenant, &username).await;
     assert!(result.is_ok());
 
     let found_user = result.unwrap();
@@ -112,12 +120,55 @@ async fn test_find_user_by_username() {
     assert_eq!(found_user.unwrap().username, username);
 }
 
+#[tokio::test]
+async fn test_exists_by_email_or_username() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("exists_{}@example.com", unique_id);
+    let username = format!("exists_{}", unique_id);
+    let other = Uuid::new_v4();
+
+    assert!(!repository
+        .exists_by_email_or_username(&tenant, &email, &username)
+        .await
+        .unwrap());
+
+    let new_user = create_new_user(
+        &email,
+        &username,
+        "$argon2id$v=19$m=19456,t=2,p=1$test$test",
+    );
+    repository.create(&tenant, new_user).await.unwrap();
+
+    // Either field matching is enough
+    assert!(repository
+        .exists_by_email_or_username(&tenant, &email, &format!("exists_{}", other))
+        .await
+        .unwrap());
+    assert!(repository
+        .exists_by_email_or_username(&tenant, &format!("exists_{}@example.com", other), &username)
+        .await
+        .unwrap());
+    assert!(!repository
+        .exists_by_email_or_username(
+            &TenantId::parse("tenant-b").unwrap(),
+            &email,
+            &username
+        )
+        .await
+        .unwrap());
+}
+
 #[tokio::test]
 async fn test_find_nonexistent_user() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
-    let result = repository.find_by_email("nonexistent@example.com").await;
+    let result = repository.find_by_email(&tenant, "nonexistent@example.com").await;
     assert!(result.is_ok());
 
     let found_user = result.unwrap();
@@ -128,6 +179,7 @@ async fn test_find_nonexistent_user() {
 async fn test_update_user_password() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create user
     let unique_id = Uuid::new_v4();
@@ -140,21 +192,51 @@ async fn test_update_user_password() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$old",
     );
 
-    let created_user = repository.create(new_user).await.unwrap();
+    let created_user = repository.create(&tenant, new_user).await.unwrap();
 
     // Update password
     let new_hash = "$argon2id$v=19$m=19456,t=2,p=1$test$new";
-    let result = repository.update_password(created_user.id, new_hash.to_string()).await;
+    let result = repository.update_password(&tenant, created_user.id, new_hash.to_string()).await;
     assert!(result.is_ok());
 
     let updated_user = result.unwrap();
     assert_eq!(updated_user.password_hash, new_hash);
+    assert!(!created_user.was_updated());
+    assert!(updated_user.was_updated());
+}
+
+#[tokio::test]
+async fn test_update_profile_only_changes_given_fields() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("patch_{}@example.com", unique_id);
+    let new_user = create_new_user(
+        &email,
+        &format!("patch_{}", unique_id),
+        "$argon2id$v=19$m=19456,t=2,p=1$test$test",
+    );
+    let created_user = repository.create(&tenant, new_user).await.unwrap();
+
+    let renamed = format!("renamed_{}", unique_id);
+    let changes = UserChangeset {
+        username: Some(renamed.clone()),
+        ..Default::default()
+    };
+    let updated_user = repository.update_profile(&tenant, created_user.id, changes).await.unwrap();
+
+    assert_eq!(updated_user.username, renamed);
+    assert_eq!(updated_user.email, email);
+    assert_eq!(updated_user.password_hash, created_user.password_hash);
 }
 
 #[tokio::test]
 async fn test_delete_user() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create user
     let unique_id = Uuid::new_v4();
@@ -167,14 +249,14 @@ async fn test_delete_user() {
         "$argon2id$v=19$m=19456,t=2,p=1$test$test",
     );
 
-    let created_user = repository.create(new_user).await.unwrap();
+    let created_user = repository.create(&tenant, new_user).await.unwrap();
 
     // Delete user
-    let result = repository.delete(created_user.id).await;
+    let result = repository.delete(&tenant, created_user.id).await;
     assert!(result.is_ok());
 
     // Verify user is deleted
-    let found = repository.find_by_id(created_user.id).await.unwrap();
+    let found = repository.find_by_id(&tenant, created_user.id).await.unwrap();
     assert!(found.is_none());
 }
 
@@ -182,6 +264,7 @@ async fn test_delete_user() {
 async fn test_list_users() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create multiple users with unique IDs per test run
     let test_id = Uuid::new_v4();
@@ -191,11 +274,11 @@ async fn test_list_users() {
             &format!("listuser_{}_{}", test_id, i),
             "$argon2id$v=19$m=19456,t=2,p=1$test$test",
         );
-        repository.create(new_user).await.unwrap();
+        repository.create(&tenant, new_user).await.unwrap();
     }
 
     // List users
-    let result = repository.list(10, 0).await;
+    let result = repository.list(&tenant, 10, 0).await;
     assert!(result.is_ok());
 
     let users = result.unwrap();
@@ -206,6 +289,7 @@ async fn test_list_users() {
 async fn test_list_users_with_pagination() {
     let state = common::setup_test_state();
     let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
 
     // Create users with unique IDs per test run
     let test_id = Uuid::new_v4();
@@ -215,15 +299,15 @@ async fn test_list_users_with_pagination() {
             &format!("pageuser_{}_{}", test_id, i),
             "$argon2id$v=19$m=19456,t=2,p=1$test$test",
         );
-        repository.create(new_user).await.unwrap();
+        repository.create(&tenant, new_user).await.unwrap();
     }
 
     // Get first page
-    let page1 = repository.list(5, 0).await.unwrap();
+    let page1 = repository.list(&tenant, 5, 0).await.unwrap();
     assert!(page1.len() <= 5);
 
     // Get second page
-    let page2 = repository.list(5, 5).await.unwrap();
+    let page2 = repository.list(&tenant, 5, 5).await.unwrap();
     assert!(page2.len() <= 5);
 
     // Verify different users
@@ -231,3 +315,101 @@ async fn test_list_users_with_pagination() {
         assert_ne!(page1[0].id, page2[0].id);
     }
 }
+
+#[tokio::test]
+async fn test_user_is_invisible_to_other_tenant() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+    let tenant_a = TenantId::parse("tenant-a").unwrap();
+    let tenant_b = TenantId::parse("tenant-b").unwrap();
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("tenant_{}@example.com", unique_id);
+    let username = format!("tenant_{}", unique_id);
+    let new_user = create_new_user(&email, &username, "$argon2id$v=19$m=19456,t=2,p=1$test$test");
+
+    let user = repository.create(&tenant_a, new_user).await.unwrap();
+    assert_eq!(user.tenant_id, "tenant-a");
+
+    // Reads scoped to tenant B never see tenant A's row
+    assert!(repository.find_by_id(&tenant_b, user.id).await.unwrap().is_none());
+    assert!(repository.find_by_email(&tenant_b, &email).await.unwrap().is_none());
+    assert!(repository.find_by_username(&tenant_b, &username).await.unwrap().is_none());
+    assert!(repository
+        .find_by_email_or_username(&tenant_b, &email, &username)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(repository
+        .list(&tenant_b, 1000, 0)
+        .await
+        .unwrap()
+        .iter()
+        .all(|u| u.id != user.id));
+
+    // Writes scoped to tenant B can't touch it either
+    assert!(repository.update_role(&tenant_b, user.id, "admin").await.is_err());
+    repository.delete(&tenant_b, user.id).await.unwrap();
+
+    let still_there = repository.find_by_id(&tenant_a, user.id).await.unwrap().unwrap();
+    assert_eq!(still_there.role, "user");
+}
+
+#[tokio::test]
+async fn test_same_email_can_exist_in_two_tenants() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("shared_{}@example.com", unique_id);
+    let username = format!("shared_{}", unique_id);
+
+    for tenant in ["tenant-a", "tenant-b"] {
+        let tenant = TenantId::parse(tenant).unwrap();
+        let new_user = create_new_user(&email, &username, "$argon2id$v=19$m=19456,t=2,p=1$test$test");
+        repository.create(&tenant, new_user).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_find_or_create_creates_missing_user() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("findorcreate_{}@example.com", unique_id);
+    let username = format!("findorcreate_{}", unique_id);
+
+    let new_user = create_new_user(&email, &username, "$argon2id$v=19$m=19456,t=2,p=1$test$test");
+    let (user, created) = repository.find_or_create(&tenant, new_user).await.unwrap();
+
+    assert!(created);
+    assert_eq!(user.email, email);
+    assert_eq!(user.username, username);
+}
+
+#[tokio::test]
+async fn test_find_or_create_returns_existing_user() {
+    let state = common::setup_test_state();
+    let repository = UserRepository::new(state.db_pool.clone());
+    let tenant = TenantId::default();
+
+    let unique_id = Uuid::new_v4();
+    let email = format!("findorcreate_{}@example.com", unique_id);
+    let username = format!("findorcreate_{}", unique_id);
+
+    let existing = repository
+        .create(&tenant, create_new_user(&email, &username, "$argon2id$v=19$m=19456,t=2,p=1$test$test"))
+        .await
+        .unwrap();
+
+    // Different username and hash: the existing row is returned untouched
+    let new_user = create_new_user(&email, &format!("other_{}", unique_id), "other-hash");
+    let (user, created) = repository.find_or_create(&tenant, new_user).await.unwrap();
+
+    assert!(!created);
+    assert_eq!(user.id, existing.id);
+    assert_eq!(user.username, username);
+    assert_eq!(user.password_hash, existing.password_hash);
+}
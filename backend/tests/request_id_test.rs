@@ -0,0 +1,69 @@
+mod common;
+
+use axum::{body::Body, http::Request};
+use backend::routes;
+use tower::ServiceExt;
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+fn health(headers: &[(&str, &str)]) -> Request<Body> {
+    let mut builder = Request::builder().uri("/api/v1/health");
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+fn app_with_header(header: &str) -> axum::Router {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.request_id_header = header.to_string();
+    routes::create_router(backend::AppState::new(config, state.db_pool.clone()))
+}
+
+#[tokio::test]
+async fn test_default_header_is_echoed_or_generated() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let response = app.clone().oneshot(health(&[("x-request-id", "abc-123")])).await.unwrap();
+    assert_eq!(response.headers()["x-request-id"], "abc-123");
+
+    let response = app.oneshot(health(&[])).await.unwrap();
+    let generated = response.headers()["x-request-id"].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(generated).is_ok());
+}
+
+#[tokio::test]
+async fn test_custom_header_name() {
+    let app = app_with_header("x-correlation-id");
+
+    let response = app
+        .oneshot(health(&[("x-correlation-id", "corr-42"), ("x-request-id", "ignored")]))
+        .await
+        .unwrap();
+
+    assert_eq!(response.headers()["x-correlation-id"], "corr-42");
+    assert!(response.headers().get("x-request-id").is_none());
+}
+
+#[tokio::test]
+async fn test_trace_id_is_taken_from_traceparent() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let response = app
+        .clone()
+        .oneshot(health(&[("traceparent", TRACEPARENT)]))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["x-request-id"],
+        "4bf92f3577b34da6a3ce929d0e0e4736"
+    );
+
+    // An explicit request ID still wins over the trace id
+    let response = app
+        .oneshot(health(&[("traceparent", TRACEPARENT), ("x-request-id", "explicit")]))
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["x-request-id"], "explicit");
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::routes;
+use tower::ServiceExt;
+
+/// The "Request completed" line logged for `uri`
+fn completed_line(logs: &str, uri: &str) -> String {
+    logs.lines()
+        .find(|line| line.contains("Request completed") && line.contains(uri))
+        .unwrap_or_else(|| panic!("no completion line for {}:\n{}", uri, logs))
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_request_span_records_authenticated_user_and_tenant() {
+    let app = routes::create_router(common::setup_test_state());
+    let auth = common::register_user(&app, None).await;
+
+    let logs = common::CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    let _default = tracing::subscriber::set_default(subscriber);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/auth/me")
+                .header("authorization", format!("Bearer {}", auth.token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/version")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let logs = logs.contents();
+    let authenticated = completed_line(&logs, "/api/v1/auth/me");
+    assert!(
+        authenticated.contains(&format!("user.id=\"{}\"", auth.user.id)),
+        "{}",
+        authenticated
+    );
+    assert!(authenticated.contains("tenant.id=\"default\""), "{}", authenticated);
+
+    let anonymous = completed_line(&logs, "/api/v1/version");
+    assert!(!anonymous.contains("user.id"), "{}", anonymous);
+    assert!(!anonymous.contains("tenant.id"), "{}", anonymous);
+}
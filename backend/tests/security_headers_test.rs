@@ -0,0 +1,167 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request},
+};
+use backend::{config::FrameOptions, routes, AppState};
+use tower::ServiceExt;
+
+/// A regular API endpoint, unlike the health probes in PROBE_PATHS
+fn api_request() -> Request<Body> {
+    Request::builder()
+        .uri("/api/v1/version")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_default_security_headers() {
+    let app = routes::create_router(common::setup_test_state());
+
+    let response = app.oneshot(api_request()).await.unwrap();
+    let headers = response.headers();
+
+    assert_eq!(headers["x-frame-options"], "DENY");
+    assert_eq!(
+        headers[header::CONTENT_SECURITY_POLICY],
+        backend::config::DEFAULT_CSP
+    );
+}
+
+#[tokio::test]
+async fn test_custom_csp_from_config() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.security_headers.content_security_policy =
+        "default-src 'self' https://cdn.example.com".to_string();
+    config.security_headers.frame_options = FrameOptions::SameOrigin;
+    config.security_headers.hsts = true;
+    config.server.trust_proxy = true;
+    let app = routes::create_router(AppState::new(config, state.db_pool.clone()));
+
+    let request = Request::builder()
+        .uri("/api/v1/version")
+        .header(header::HOST, "localhost")
+        .header("x-forwarded-proto", "https")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let headers = response.headers();
+
+    assert_eq!(
+        headers[header::CONTENT_SECURITY_POLICY],
+        "default-src 'self' https://cdn.example.com"
+    );
+    assert_eq!(headers["x-frame-options"], "SAMEORIGIN");
+    assert!(headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+}
+
+#[tokio::test]
+async fn test_hsts_only_sent_over_https() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.security_headers.hsts = true;
+    let app = routes::create_router(AppState::new(config, state.db_pool.clone()));
+
+    let request = Request::builder()
+        .uri("/api/v1/version")
+        .header(header::HOST, "localhost")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert!(!response.headers().contains_key(header::STRICT_TRANSPORT_SECURITY));
+}
+
+#[tokio::test]
+async fn test_hsts_max_age_and_preload_from_config() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.security_headers.hsts = true;
+    config.security_headers.hsts_max_age = 63_072_000;
+    config.security_headers.hsts_preload = true;
+    config.server.trust_proxy = true;
+    let app = routes::create_router(AppState::new(config, state.db_pool.clone()));
+
+    let request = Request::builder()
+        .uri("/api/v1/version")
+        .header(header::HOST, "localhost")
+        .header("x-forwarded-proto", "https")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(
+        response.headers()[header::STRICT_TRANSPORT_SECURITY],
+        "max-age=63072000; includeSubDomains; preload"
+    );
+}
+
+#[tokio::test]
+async fn test_probe_paths_skip_security_headers_and_cors() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.cors = backend::config::CorsConfig::parse(
+        &["https://app.example.com".to_string()],
+        config.server.environment,
+    )
+    .unwrap();
+    let app = routes::create_router(AppState::new(config, state.db_pool.clone()));
+    let get = |uri: &str| {
+        Request::builder()
+            .uri(uri)
+            .header(header::ORIGIN, "https://app.example.com")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let probe = app.clone().oneshot(get("/api/v1/health")).await.unwrap();
+    assert!(!probe.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+    assert!(!probe.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    let api = app.oneshot(get("/api/v1/version")).await.unwrap();
+    assert_eq!(
+        api.headers()[header::CONTENT_SECURITY_POLICY],
+        backend::config::DEFAULT_CSP
+    );
+    assert_eq!(
+        api.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://app.example.com"
+    );
+}
+
+#[tokio::test]
+async fn test_cookie_transport_allows_credentialed_cors() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.cors = backend::config::CorsConfig::parse(
+        &["http://localhost:3000".to_string()],
+        config.server.environment,
+    )
+    .unwrap();
+    let preflight = || {
+        Request::builder()
+            .method("OPTIONS")
+            .uri("/api/v1/auth/me")
+            .header(header::ORIGIN, "http://localhost:3000")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let app = routes::create_router(AppState::new(config.clone(), state.db_pool.clone()));
+    let response = app.oneshot(preflight()).await.unwrap();
+    assert!(!response
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+    config.jwt.transport = backend::config::TokenTransport::Cookie;
+    let app = routes::create_router(AppState::new(config, state.db_pool.clone()));
+    let response = app.oneshot(preflight()).await.unwrap();
+    let headers = response.headers();
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://localhost:3000");
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], "content-type");
+}
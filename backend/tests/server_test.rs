@@ -0,0 +1,33 @@
+mod common;
+
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn test_health_over_the_network() {
+    let app = common::spawn_app().await;
+
+    let response = app
+        .client
+        .get(app.url("/api/v1/health"))
+        .send()
+        .await
+        .expect("request should reach the server");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key("x-request-id"));
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["status"].is_string());
+
+    app.stop().await;
+}
+
+#[tokio::test]
+async fn test_server_stops_accepting_after_shutdown() {
+    let app = common::spawn_app().await;
+    let client = app.client.clone();
+    let url = app.url("/api/v1/health");
+
+    app.stop().await;
+
+    assert!(client.get(url).send().await.is_err());
+}
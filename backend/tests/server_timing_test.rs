@@ -0,0 +1,63 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use backend::{routes, services::jwt::TokenOptions, types::TenantId, AppState};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn app(server_timing: bool) -> (axum::Router, AppState) {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.server_timing = server_timing;
+    let state = AppState::new(config, state.db_pool.clone());
+    (routes::create_router(state.clone()), state)
+}
+
+async fn me_response(server_timing: bool) -> axum::response::Response {
+    let (app, state) = app(server_timing);
+    // A valid token for a user that doesn't exist: the lookup still hits the DB
+    let token = state
+        .services
+        .jwt
+        .issue_token(
+            Uuid::new_v4(),
+            "timing@example.com".to_string(),
+            "timing".to_string(),
+            TenantId::default_tenant(),
+            TokenOptions::default(),
+        )
+        .unwrap()
+        .token;
+
+    app.oneshot(
+        Request::builder()
+            .uri("/api/v1/auth/me")
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_db_backed_endpoint_reports_db_timing() {
+    let response = me_response(true).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let header = response.headers()["server-timing"].to_str().unwrap();
+    for phase in ["auth;dur=", "db;dur=", "handler;dur="] {
+        assert!(header.contains(phase), "missing {} in {}", phase, header);
+    }
+}
+
+#[tokio::test]
+async fn test_no_server_timing_when_disabled() {
+    let response = me_response(false).await;
+
+    assert!(!response.headers().contains_key("server-timing"));
+}
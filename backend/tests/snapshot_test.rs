@@ -3,11 +3,21 @@
 //! Snapshot tests help catch unintended changes to API responses
 //! and data structures over time.
 
-use backend::models::user::UserResponse;
-use chrono::Utc;
+use backend::{
+    error::ErrorCode,
+    models::{
+        dto::{AuthResponseDto, UserResponseDto},
+        user::UserResponse,
+    },
+};
+use chrono::{NaiveDateTime, Utc};
 use insta::{assert_json_snapshot, assert_yaml_snapshot};
 use uuid::Uuid;
 
+fn fixed_time() -> NaiveDateTime {
+    chrono::DateTime::from_timestamp(1_704_067_200, 0).unwrap().naive_utc()
+}
+
 #[test]
 fn test_user_response_snapshot() {
     let user = UserResponse {
@@ -15,6 +25,7 @@ fn test_user_response_snapshot() {
         email: "test@example.com".to_string(),
         username: "testuser".to_string(),
         created_at: Utc::now().naive_utc(),
+        last_login_at: None,
     };
 
     // Snapshot the JSON representation
@@ -32,12 +43,14 @@ fn test_multiple_users_snapshot() {
             email: "user1@example.com".to_string(),
             username: "user1".to_string(),
             created_at: Utc::now().naive_utc(),
+            last_login_at: None,
         },
         UserResponse {
             id: Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap(),
             email: "user2@example.com".to_string(),
             username: "user2".to_string(),
             created_at: Utc::now().naive_utc(),
+            last_login_at: None,
         },
     ];
 
@@ -62,6 +75,60 @@ fn test_error_response_format() {
     });
 }
 
+#[test]
+fn test_user_response_dto_omits_unset_fields() {
+    let user = UserResponseDto {
+        id: Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
+        email: "test@example.com".to_string(),
+        username: "testuser".to_string(),
+        created_at: fixed_time(),
+        last_login_at: None,
+    };
+
+    assert_json_snapshot!(user);
+}
+
+#[test]
+fn test_auth_response_dto_snapshot() {
+    let user = UserResponseDto {
+        id: Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap(),
+        email: "signed-in@example.com".to_string(),
+        username: "signedin".to_string(),
+        created_at: fixed_time(),
+        last_login_at: Some(fixed_time()),
+    };
+
+    let with_token = AuthResponseDto {
+        user,
+        token: "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...".to_string(),
+        expires_at: fixed_time().and_utc() + chrono::Duration::hours(24),
+        expires_in: 86400,
+    };
+    assert_json_snapshot!(with_token);
+
+    // Cookie transport: no token field at all
+    let cookie_only = AuthResponseDto {
+        token: String::new(),
+        ..with_token
+    };
+    assert_json_snapshot!(cookie_only);
+}
+
+#[test]
+fn test_error_codes_serialize_screaming_snake_case() {
+    let codes = [
+        ErrorCode::NotFound,
+        ErrorCode::TokenExpired,
+        ErrorCode::DatabaseUnavailable,
+        ErrorCode::ExternalServiceError,
+    ];
+
+    assert_json_snapshot!(codes);
+    for code in codes {
+        assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+    }
+}
+
 // Example: Snapshot testing for API endpoint responses
 // This would typically be in an integration test
 #[cfg(test)]
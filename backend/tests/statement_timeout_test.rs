@@ -0,0 +1,26 @@
+mod common;
+
+use backend::db;
+use diesel_async::RunQueryDsl;
+
+#[tokio::test]
+async fn test_long_query_hits_statement_timeout() {
+    let state = common::setup_test_state();
+    let pool =
+        db::create_pool_with_statement_timeout(&state.config.database.url, 1, 100).unwrap();
+
+    // Twice on a single-connection pool: the second query runs on a reused connection
+    for _ in 0..2 {
+        let mut conn = db::get_connection(&pool).await.unwrap();
+        let err = diesel::sql_query("SELECT pg_sleep(2)")
+            .execute(&mut conn)
+            .await
+            .expect_err("query should be cancelled");
+
+        assert!(
+            err.to_string().contains("statement timeout"),
+            "unexpected error: {}",
+            err
+        );
+    }
+}
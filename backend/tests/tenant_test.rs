@@ -0,0 +1,114 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use backend::{routes, AppState};
+use serde_json::json;
+use tower::ServiceExt;
+
+fn setup() -> axum::Router {
+    routes::create_router(common::setup_test_state())
+}
+
+fn setup_with_base_domain(base_domain: &str) -> axum::Router {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.tenant.base_domain = Some(base_domain.to_string());
+    routes::create_router(AppState::new(config, state.db_pool.clone()))
+}
+
+fn auth_request(uri: &str, tenant: Option<&str>, payload: serde_json::Value) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(tenant) = tenant {
+        builder = builder.header("x-tenant-id", tenant);
+    }
+    builder.body(Body::from(payload.to_string())).unwrap()
+}
+
+fn me_request(token: &str, tenant: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/api/v1/auth/me")
+        .header("authorization", format!("Bearer {}", token))
+        .header("x-tenant-id", tenant)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_login_is_scoped_to_tenant() {
+    let app = setup();
+    let email = common::register_user(&app, Some("tenant-a")).await.user.email;
+    let credentials = json!({ "email": email, "password": common::TEST_PASSWORD });
+
+    let response = app
+        .clone()
+        .oneshot(auth_request("/api/v1/auth/login", Some("tenant-a"), credentials.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(auth_request("/api/v1/auth/login", Some("tenant-b"), credentials.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // No header means the default tenant, which doesn't have this user either
+    let response = app
+        .oneshot(auth_request("/api/v1/auth/login", None, credentials))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_token_rejected_in_other_tenant() {
+    let app = setup();
+    let token = common::register_user(&app, Some("tenant-a")).await.token;
+
+    let response = app.clone().oneshot(me_request(&token, "tenant-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.oneshot(me_request(&token, "tenant-b")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_invalid_tenant_header_rejected() {
+    let response = setup()
+        .oneshot(auth_request("/api/v1/auth/login", Some("not a tenant"), json!({})))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_tenant_resolved_from_subdomain() {
+    let app = setup_with_base_domain("app.example.com");
+    let email = common::register_user(&app, Some("tenant-a")).await.user.email;
+
+    let login = |host: &'static str| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/auth/login")
+            .header(header::HOST, host)
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({ "email": email, "password": common::TEST_PASSWORD }).to_string(),
+            ))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(login("tenant-a.app.example.com")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.oneshot(login("tenant-b.app.example.com")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
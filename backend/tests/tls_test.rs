@@ -0,0 +1,65 @@
+mod common;
+
+use std::path::PathBuf;
+
+use backend::{config::TlsConfig, routes, tls};
+use reqwest::StatusCode;
+
+/// Self-signed certificate for `localhost` / 127.0.0.1, test use only
+fn fixture_tls_config() -> TlsConfig {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls");
+    TlsConfig {
+        cert_path: dir.join("cert.pem"),
+        key_path: dir.join("key.pem"),
+    }
+}
+
+#[tokio::test]
+async fn test_serves_https_with_self_signed_certificate() {
+    let state = common::setup_test_state();
+    let mut config = (*state.config).clone();
+    config.server.tls = Some(fixture_tls_config());
+    config.security_headers.hsts = true;
+    let state = backend::AppState::new(config.clone(), state.db_pool.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let tls_config = config.server.tls.clone().unwrap();
+    let server = tokio::spawn(async move {
+        tls::serve(listener, routes::create_router(state), &tls_config).await
+    });
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let response = client
+        .get(format!("https://{}/api/v1/health", address))
+        .send()
+        .await
+        .expect("TLS listener should accept the request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    // Served over TLS, so HSTS is asserted
+    assert!(response.headers().contains_key("strict-transport-security"));
+
+    // Plain HTTP against the TLS port does not get a response
+    let plain = reqwest::Client::new()
+        .get(format!("http://{}/api/v1/health", address))
+        .send()
+        .await;
+    assert!(plain.map(|r| !r.status().is_success()).unwrap_or(true));
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_missing_certificate_is_a_config_error() {
+    let tls_config = TlsConfig {
+        cert_path: PathBuf::from("does/not/exist.pem"),
+        key_path: fixture_tls_config().key_path,
+    };
+
+    let err = tls::load(&tls_config).await.err().unwrap();
+    assert!(err.to_string().contains("does/not/exist.pem"));
+}